@@ -0,0 +1,582 @@
+use crate::lexer::Token;
+use crate::lexer::TokenType;
+use crate::lexer::TokenCompData;
+use crate::lexer::VarDest;
+use crate::lexer::Span;
+use thiserror::Error;
+
+pub use crate::parser::parserTree::*;
+
+// semantic pass run between the parser and the compiler
+// every expression is given a type, values may widen on their own, anything else needs a cast
+
+#[derive(Debug, Error, Clone)]
+pub enum CheckerError {
+    #[error("(CheckerError) A {0:?} can not be used as a {1:?} without a cast.")]
+    NeedsCast(DataValueType, DataValueType, Span),
+
+    #[error("(CheckerError) A {0:?} and a {1:?} can not be mixed without a cast.")]
+    MixedTypes(DataValueType, DataValueType, Span),
+
+    #[error("(CheckerError) A {0} and a {1} are different enums, they can not be mixed without a cast.")]
+    MixedEnums(String, String, Span),
+
+    #[error("(CheckerError) The constant {0} does not fit in a {1:?}.")]
+    ConstantOutOfRange(i32, DataValueType, Span),
+
+    #[error("(CheckerError) Operator '{0}' can not be used on a {1:?}.")]
+    InvalidOperand(String, DataValueType, Span),
+
+    #[error("(CheckerError) Argument {0} of {1} is a {2:?} where a {3:?} is expected.")]
+    ArgumentType(usize, String, DataValueType, DataValueType, Span),
+
+    #[error("(CheckerError) The constant {0} passed as argument {1} of {2} does not fit in a {3:?}.")]
+    ArgumentOutOfRange(i32, usize, String, DataValueType, Span),
+
+    #[error("(CheckerError) {0} takes {1} argument(s), {2} given.")]
+    ArgumentCount(String, usize, usize, Span),
+
+    #[error("(CheckerError) {0} does not return a value.")]
+    VoidValue(String, Span),
+
+    #[error("(CheckerError) {0} is void and can not return a value.")]
+    UnexpectedReturnValue(String, Span),
+
+    #[error("(CheckerError) {0} returns a {1:?}, the value returned is a {2:?}.")]
+    ReturnType(String, DataValueType, DataValueType, Span),
+
+    #[error("(CheckerError) A condition has to be a number, found a {0:?}.")]
+    ConditionType(DataValueType, Span),
+
+    #[error("(CheckerError) A {0:?} can not be cast to a {1:?}.")]
+    InvalidCast(DataValueType, DataValueType, Span),
+
+    #[error("(CheckerError) An index has to be a 1 byte number, found a {0:?}.")]
+    IndexType(DataValueType, Span),
+
+    #[error("(CheckerError) Index {0} is past the end of an array of {1}.")]
+    IndexOutOfRange(i32, u8, Span),
+
+    #[error("(CheckerError) Only variables, fields and array elements have an address.")]
+    NoAddress(Span),
+
+    #[error("(CheckerError) A {0:?} has no fields.")]
+    NotAStruct(DataValueType, Span),
+
+    #[error("(CheckerError) {0} has no field '{1}'.")]
+    UnknownField(String, String, Span),
+}
+
+impl CheckerError {
+    pub fn span(&self) -> Span {
+        match self {
+            CheckerError::NeedsCast(.., s) |
+            CheckerError::MixedTypes(.., s) |
+            CheckerError::MixedEnums(.., s) |
+            CheckerError::ConstantOutOfRange(.., s) |
+            CheckerError::InvalidOperand(.., s) |
+            CheckerError::ArgumentType(.., s) |
+            CheckerError::ArgumentOutOfRange(.., s) |
+            CheckerError::ArgumentCount(.., s) |
+            CheckerError::VoidValue(.., s) |
+            CheckerError::UnexpectedReturnValue(.., s) |
+            CheckerError::ReturnType(.., s) |
+            CheckerError::ConditionType(.., s) |
+            CheckerError::InvalidCast(.., s) |
+            CheckerError::IndexType(.., s) |
+            CheckerError::IndexOutOfRange(.., s) |
+            CheckerError::NoAddress(.., s) |
+            CheckerError::NotAStruct(.., s) |
+            CheckerError::UnknownField(.., s) => *s,
+        }
+    }
+}
+
+// type of an expression, a constant has no type of its own and takes one from where it is used
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ExprType {
+    Value(DataValueType),
+    Constant(i32)
+}
+
+fn isNumber(v: DataValueType) -> bool {
+    match v {
+        DataValueType::Uint |
+        DataValueType::Int |
+        DataValueType::Short |
+        DataValueType::SShort |
+        DataValueType::Char |
+        DataValueType::Enum(..) => return true,
+        _ => return false,
+    }
+}
+
+// conversions done without a cast, the value is the same afterwards
+// a char and a uint are the same byte so a char widens wherever a uint does, anything narrower has to be asked for
+// an enum is used as the number it is held as, a number only becomes an enum through a cast
+fn widens(from: DataValueType, to: DataValueType) -> bool {
+    match (from, to) {
+        (DataValueType::Enum(a, _), DataValueType::Enum(b, _)) => return a == b,
+        (DataValueType::Enum(_, v), _) => return widens(*v, to),
+        (DataValueType::Uint, DataValueType::Short) |
+        (DataValueType::Uint, DataValueType::SShort) |
+        (DataValueType::Char, DataValueType::Short) |
+        (DataValueType::Char, DataValueType::SShort) |
+        (DataValueType::Int, DataValueType::SShort) |
+        (DataValueType::Uint, DataValueType::Char) |
+        (DataValueType::Char, DataValueType::Uint) => return true,
+        _ => return from == to,
+    }
+}
+
+// numbers cast to each other freely, a string or buffer only to and from a 2 byte number
+fn castable(from: DataValueType, to: DataValueType) -> bool {
+    if matches!(from, DataValueType::Struct(_)) || matches!(to, DataValueType::Struct(_)) {return from == to;}
+    if isNumber(from) && isNumber(to) {return true;}
+    if !isNumber(from) && !isNumber(to) {return true;}
+    return from.isWord() || to.isWord();
+}
+
+// the smallest type holding the constant next to a value of type v
+fn constantType(c: i32, v: DataValueType) -> Option<DataValueType> {
+    if v.fits(c) {return Some(v);}
+    let wider = if v.isSigned() {DataValueType::SShort} else {DataValueType::Short};
+    if wider.fits(c) {return Some(wider);}
+    return None;
+}
+
+fn variableType(tk: &Token<'_>, program: &Program, directory: &[BlockParent]) -> Option<DataValueType> {
+    let (index, dest) = tk.tk_comp_data.var()?;
+    let variable = match dest {
+        VarDest::CurrentProc => program.procs[directory.first()?.index].variables.get(index),
+        VarDest::Argument => program.procs[directory.first()?.index].arguments.get(index),
+        VarDest::Block(d) if (d as usize) < directory.len() => program.getBlock(&directory[..d as usize+1]).variables.get(index),
+        VarDest::Heap => program.heap_variables.get(index),
+        VarDest::ProgramStatic => program.static_variables.get(index),
+        VarDest::ProgramConst => program.const_variables.get(index),
+        _ => None,
+    };
+    return variable.map(|v| v.t.v);
+}
+
+// two different enums, named so the message says which
+fn enumMix(from: DataValueType, to: DataValueType, program: &Program, span: Span) -> Option<CheckerError> {
+    match (from, to) {
+        (DataValueType::Enum(a, _), DataValueType::Enum(b, _)) if a != b => {
+            return Some(CheckerError::MixedEnums(program.enums[a].label.to_string(), program.enums[b].label.to_string(), span));
+        }
+        _ => return None,
+    }
+}
+
+// an enum member is not folded away with the literals around it, Dir::UP == Color::RED is still two enums
+fn hasMember(node: &ExprNode<'_>) -> bool {
+    match node {
+        ExprNode::Literal(tk) => return matches!(tk.tk_comp_data, TokenCompData::Member(..)),
+        ExprNode::Unary(_, operand, _) => return hasMember(operand),
+        ExprNode::Binary(_, lhs, rhs, _) => return hasMember(lhs) || hasMember(rhs),
+        _ => return false,
+    }
+}
+
+// the type both sides of a binary operator are worked out in
+fn unify(op: &str, lhs: Option<ExprType>, rhs: Option<ExprType>, span: Span, program: &Program, errors: &mut Vec<CheckerError>) -> Option<DataValueType> {
+    let (l, r) = match (lhs?, rhs?) {
+        (ExprType::Value(l), ExprType::Value(r)) => (l, r),
+        (ExprType::Value(v), ExprType::Constant(c)) |
+        (ExprType::Constant(c), ExprType::Value(v)) => {
+            if !isNumber(v) {
+                errors.push(CheckerError::InvalidOperand(op.to_string(), v, span));
+                return None;
+            }
+            match constantType(c, v) {
+                Some(t) if widens(v, t) => return Some(t),
+                _ => {
+                    errors.push(CheckerError::ConstantOutOfRange(c, v, span));
+                    return None;
+                }
+            }
+        }
+        (ExprType::Constant(_), ExprType::Constant(_)) => return None,
+    };
+    for v in [l, r] {
+        if !isNumber(v) {
+            errors.push(CheckerError::InvalidOperand(op.to_string(), v, span));
+            return None;
+        }
+    }
+    if l == r {return Some(l);}
+    if (l, r) == (DataValueType::Char, DataValueType::Uint) || (l, r) == (DataValueType::Uint, DataValueType::Char) {
+        return Some(DataValueType::Uint);
+    }
+    if widens(l, r) {return Some(r);}
+    if widens(r, l) {return Some(l);}
+    match enumMix(l, r, program, span) {
+        Some(e) => errors.push(e),
+        None => errors.push(CheckerError::MixedTypes(l, r, span)),
+    }
+    return None;
+}
+
+fn pointerOf(t: Option<ExprType>) -> Option<DataValueType> {
+    match t {
+        Some(ExprType::Value(v @ DataValueType::Pointer(_))) => return Some(v),
+        _ => return None,
+    }
+}
+
+// a pointer moves by a number of elements and is only compared with a pointer of its type or an address
+// anything else is left to unify, which rejects the pointer
+fn pointerArithmetic(op: BinaryOp, l: Option<ExprType>, r: Option<ExprType>, span: Span, errors: &mut Vec<CheckerError>) -> Option<Option<ExprType>> {
+    let (ptr, other) = match (pointerOf(l), pointerOf(r)) {
+        (None, None) => return None,
+        (Some(p), Some(q)) if op.isComparison() => {
+            if p != q {errors.push(CheckerError::MixedTypes(p, q, span));}
+            return Some(Some(ExprType::Value(DataValueType::Uint)));
+        }
+        (Some(p), None) => (p, r),
+        (None, Some(p)) => (p, l),
+        _ => return None,
+    };
+    let moves = op == BinaryOp::Add || (op == BinaryOp::Subtract && pointerOf(l).is_some());
+    if !moves && !op.isComparison() {return None;}
+    match other {
+        Some(ExprType::Value(v)) if op.isComparison() => {
+            errors.push(CheckerError::MixedTypes(ptr, v, span));
+            return Some(None);
+        }
+        Some(ExprType::Value(v)) if !isNumber(v) => {
+            errors.push(CheckerError::InvalidOperand(opText(op).to_string(), v, span));
+            return Some(None);
+        }
+        _ if op.isComparison() => return Some(Some(ExprType::Value(DataValueType::Uint))),
+        _ => return Some(Some(ExprType::Value(ptr))),
+    }
+}
+
+fn opText(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => return "+",
+        BinaryOp::Subtract => return "-",
+        BinaryOp::Multiply => return "*",
+        BinaryOp::Divide => return "/",
+        BinaryOp::Modulo => return "%",
+        BinaryOp::BitAnd => return "&",
+        BinaryOp::BitOr => return "|",
+        BinaryOp::BitXor => return "^",
+        BinaryOp::ShiftLeft => return "<<",
+        BinaryOp::ShiftRight => return ">>",
+        BinaryOp::LogicalAnd => return "&&",
+        BinaryOp::LogicalOr => return "||",
+        BinaryOp::Eq => return "==",
+        BinaryOp::NEq => return "!=",
+        BinaryOp::Less => return "<",
+        BinaryOp::Great => return ">",
+        BinaryOp::LessEq => return "<=",
+        BinaryOp::GreatEq => return ">=",
+    }
+}
+
+// a value that can only be a number, ex: the operand of ! or a condition
+fn checkNumber(node: &ExprNode<'_>, program: &Program, directory: &[BlockParent], errors: &mut Vec<CheckerError>) {
+    if let Some(ExprType::Value(v)) = typeOf(node, program, directory, errors) {
+        if !isNumber(v) {errors.push(CheckerError::ConditionType(v, node.span()));}
+    }
+}
+
+// false when the value does not fit in a t, a constant is checked against the range of t
+fn assignable(value: Option<ExprType>, t: DataValueType) -> bool {
+    match value {
+        Some(ExprType::Value(v)) => return widens(v, t),
+        Some(ExprType::Constant(c)) => return t.fits(c),
+        None => return true,
+    }
+}
+
+fn typeOf(node: &ExprNode<'_>, program: &Program, directory: &[BlockParent], errors: &mut Vec<CheckerError>) -> Option<ExprType> {
+    match node {
+        ExprNode::Literal(tk) if tk.tk_type == TokenType::StringLiteral => return Some(ExprType::Value(DataValueType::String)),
+        ExprNode::Literal(tk) => match tk.tk_comp_data {
+            TokenCompData::Member(e, _) => return Some(ExprType::Value(program.enumType(e))),
+            _ => return node.constant().map(ExprType::Constant),
+        },
+        ExprNode::Variable(tk) => return variableType(tk, program, directory).map(ExprType::Value),
+        // registers are read as a byte
+        ExprNode::Register(_) => return Some(ExprType::Value(DataValueType::Uint)),
+        ExprNode::Label(_) => return Some(ExprType::Value(DataValueType::Short)),
+        ExprNode::List(..) => return None,
+        // a buffer is indexed as bytes and has no length to check against
+        ExprNode::Index(base, index, span) => {
+            let def = match typeOf(base, program, directory, errors) {
+                Some(ExprType::Value(DataValueType::Array(i))) => program.arrays.get(i).copied(),
+                Some(ExprType::Value(DataValueType::Buffer)) | None => None,
+                Some(ExprType::Value(v)) => {
+                    errors.push(CheckerError::InvalidOperand("[]".to_string(), v, *span));
+                    return None;
+                }
+                Some(ExprType::Constant(_)) => return None,
+            };
+            match typeOf(index, program, directory, errors) {
+                Some(ExprType::Value(v)) if !isNumber(v) || v.isWord() => errors.push(CheckerError::IndexType(v, index.span())),
+                Some(ExprType::Constant(c)) => match def {
+                    Some(d) if c < 0 || c >= d.len as i32 => errors.push(CheckerError::IndexOutOfRange(c, d.len, index.span())),
+                    None if !DataValueType::Uint.fits(c) => errors.push(CheckerError::ConstantOutOfRange(c, DataValueType::Uint, index.span())),
+                    _ => (),
+                },
+                _ => (),
+            }
+            return Some(ExprType::Value(def.map(|d| d.v).unwrap_or(DataValueType::Uint)));
+        }
+        // a call used as a value has to return one
+        ExprNode::Call(tk, args, span) => {
+            let t = checkCall(tk, args, *span, program, directory, errors);
+            if t.is_none() {errors.push(CheckerError::VoidValue(tk.tk_data.to_string(), *span));}
+            return t;
+        }
+        ExprNode::Unary(UnaryOp::Not, operand, _) => {
+            checkNumber(operand, program, directory, errors);
+            return Some(ExprType::Value(DataValueType::Uint));
+        }
+        // &x is a pointer to what x holds, &array to its first element
+        // a pointer type that is never written out can not be held anywhere so it is not checked
+        ExprNode::Unary(UnaryOp::AddressOf, operand, span) => {
+            if !matches!(**operand, ExprNode::Variable(_) | ExprNode::Field(..) | ExprNode::Index(..) | ExprNode::Unary(UnaryOp::Deref, ..)) {
+                errors.push(CheckerError::NoAddress(*span));
+                return None;
+            }
+            let v = match typeOf(operand, program, directory, errors)? {
+                ExprType::Value(DataValueType::Array(i)) => program.arrays.get(i)?.v,
+                ExprType::Value(v) => v,
+                ExprType::Constant(_) => return None,
+            };
+            return program.pointers.iter().position(|p| *p == v).map(|i| ExprType::Value(DataValueType::Pointer(i)));
+        }
+        // a constant address has to be cast to the pointer it is used as
+        ExprNode::Unary(UnaryOp::Deref, operand, span) => {
+            let v = match typeOf(operand, program, directory, errors)? {
+                ExprType::Value(v) => v,
+                ExprType::Constant(_) => DataValueType::Short,
+            };
+            match program.pointee(v) {
+                Some(t) => return Some(ExprType::Value(t)),
+                None => {
+                    errors.push(CheckerError::InvalidOperand("*".to_string(), v, *span));
+                    return None;
+                }
+            }
+        }
+        ExprNode::Unary(op, operand, span) => {
+            if let Some(c) = node.constant().filter(|_| !hasMember(node)) {return Some(ExprType::Constant(c));}
+            let t = typeOf(operand, program, directory, errors);
+            // p++ moves a pointer by one element like p + 1
+            if matches!(op, UnaryOp::Increment | UnaryOp::Decrement) && pointerOf(t).is_some() {return t;}
+            if let Some(ExprType::Value(v)) = t {
+                if !isNumber(v) {
+                    let text = match op {
+                        UnaryOp::Negate => "-",
+                        UnaryOp::BitNot => "~",
+                        UnaryOp::Increment => "++",
+                        _ => "--",
+                    };
+                    errors.push(CheckerError::InvalidOperand(text.to_string(), v, *span));
+                    return None;
+                }
+            }
+            return t;
+        }
+        ExprNode::Binary(op, lhs, rhs, span) => {
+            if let Some(c) = node.constant().filter(|_| !hasMember(node)) {return Some(ExprType::Constant(c));}
+            if op.isLogical() {
+                checkNumber(lhs, program, directory, errors);
+                checkNumber(rhs, program, directory, errors);
+                return Some(ExprType::Value(DataValueType::Uint));
+            }
+            let l = typeOf(lhs, program, directory, errors);
+            let r = typeOf(rhs, program, directory, errors);
+            // the count of a shift does not change the type
+            if *op == BinaryOp::ShiftLeft || *op == BinaryOp::ShiftRight {
+                for t in [l, r] {
+                    if let Some(ExprType::Value(v)) = t {
+                        if !isNumber(v) {
+                            errors.push(CheckerError::InvalidOperand(opText(*op).to_string(), v, *span));
+                            return None;
+                        }
+                    }
+                }
+                return l;
+            }
+            if let Some(t) = pointerArithmetic(*op, l, r, *span, errors) {return t;}
+            let t = unify(opText(*op), l, r, *span, program, errors)?;
+            if op.isComparison() {return Some(ExprType::Value(DataValueType::Uint));}
+            return Some(ExprType::Value(t));
+        }
+        ExprNode::Field(base, name, _) => {
+            let v = match typeOf(base, program, directory, errors)? {
+                ExprType::Value(v) => v,
+                ExprType::Constant(_) => return None,
+            };
+            let def = match v {
+                DataValueType::Struct(i) => program.structs.get(i)?,
+                _ => {
+                    errors.push(CheckerError::NotAStruct(v, base.span()));
+                    return None;
+                }
+            };
+            match def.fields.iter().find(|f| f.label == name.tk_data) {
+                Some(f) => return Some(ExprType::Value(f.v)),
+                None => {
+                    errors.push(CheckerError::UnknownField(def.label.to_string(), name.tk_data.to_string(), name.span));
+                    return None;
+                }
+            }
+        }
+        ExprNode::Cast(t, operand, span) => {
+            if let Some(ExprType::Value(v)) = typeOf(operand, program, directory, errors) {
+                if !castable(v, *t) {errors.push(CheckerError::InvalidCast(v, *t, *span));}
+            }
+            return Some(ExprType::Value(*t));
+        }
+        ExprNode::Assign(target, value, span) => {
+            let t = typeOf(target, program, directory, errors);
+            let v = typeOf(value, program, directory, errors);
+            // a register takes any byte
+            if let ExprNode::Register(_) = **target {
+                match v {
+                    Some(ExprType::Constant(c)) if (-128..=0xFF).contains(&c) => {}
+                    Some(ExprType::Value(DataValueType::Int)) => {}
+                    _ if assignable(v, DataValueType::Uint) => {}
+                    _ => errors.push(mismatch(v, DataValueType::Uint, program, value.span())),
+                }
+                return t;
+            }
+            if let Some(ExprType::Value(target_type)) = t {
+                if !assignable(v, target_type) {errors.push(mismatch(v, target_type, program, *span));}
+            }
+            return t;
+        }
+    }
+}
+
+fn mismatch(value: Option<ExprType>, t: DataValueType, program: &Program, span: Span) -> CheckerError {
+    match value {
+        Some(ExprType::Constant(c)) => return CheckerError::ConstantOutOfRange(c, t, span),
+        Some(ExprType::Value(v)) => return enumMix(v, t, program, span).unwrap_or(CheckerError::NeedsCast(v, t, span)),
+        None => return CheckerError::NeedsCast(DataValueType::Void, t, span),
+    }
+}
+
+// the type a call gives, None when it gives nothing
+fn checkCall(
+    tk: &Token<'_>,
+    args: &[ExprNode<'_>],
+    span: Span,
+    program: &Program,
+    directory: &[BlockParent],
+    errors: &mut Vec<CheckerError>
+) -> Option<ExprType> {
+    let called_proc = match tk.tk_type {
+        TokenType::ProcedureCall => tk.tk_comp_data.proc().map(|i| &program.procs[i]),
+        _ => None,
+    };
+    let called_proc = match called_proc {
+        Some(p) => p,
+        // embedded functions take whatever fits in their bytes
+        None => {
+            for arg in args {typeOf(arg, program, directory, errors);}
+            if tk.tk_data == "sys" {return Some(ExprType::Value(DataValueType::Uint));}
+            return None;
+        }
+    };
+    if args.len() != called_proc.arguments.len() {
+        errors.push(CheckerError::ArgumentCount(tk.tk_data.to_string(), called_proc.arguments.len(), args.len(), span));
+    }
+    for (i, arg) in args.iter().enumerate() {
+        let t = typeOf(arg, program, directory, errors);
+        let param = match called_proc.arguments.get(i) {
+            Some(p) => p.t.v,
+            None => continue,
+        };
+        if assignable(t, param) {continue;}
+        match t {
+            Some(ExprType::Constant(c)) => errors.push(CheckerError::ArgumentOutOfRange(c, i+1, tk.tk_data.to_string(), param, arg.span())),
+            Some(ExprType::Value(v)) => match enumMix(v, param, program, arg.span()) {
+                Some(e) => errors.push(e),
+                None => errors.push(CheckerError::ArgumentType(i+1, tk.tk_data.to_string(), v, param, arg.span())),
+            },
+            None => {}
+        }
+    }
+    match called_proc.retType.v {
+        DataValueType::Void => return None,
+        v => return Some(ExprType::Value(v)),
+    }
+}
+
+fn checkStatement(expr: &Expression<'_>, program: &Program, directory: &[BlockParent], errors: &mut Vec<CheckerError>) {
+    let node = match expr.tree {
+        Some(ref n) => n,
+        None => return,
+    };
+    match expr.t {
+        ExpressionType::Return => {
+            let called_proc = &program.procs[directory[0].index];
+            if called_proc.retType.v == DataValueType::Void {
+                errors.push(CheckerError::UnexpectedReturnValue(called_proc.label.to_string(), expr.span()));
+                return;
+            }
+            let t = typeOf(node, program, directory, errors);
+            if assignable(t, called_proc.retType.v) {return;}
+            match t {
+                Some(ExprType::Constant(c)) => errors.push(CheckerError::ConstantOutOfRange(c, called_proc.retType.v, node.span())),
+                Some(ExprType::Value(v)) => match enumMix(v, called_proc.retType.v, program, node.span()) {
+                    Some(e) => errors.push(e),
+                    None => errors.push(CheckerError::ReturnType(called_proc.label.to_string(), called_proc.retType.v, v, node.span())),
+                },
+                None => {}
+            }
+        }
+        _ => match node {
+            // a call on its own line may give nothing
+            ExprNode::Call(tk, args, span) => {checkCall(tk, args, *span, program, directory, errors);}
+            _ => {typeOf(node, program, directory, errors);}
+        },
+    }
+}
+
+fn checkLines(
+    lines: &[Line],
+    expressions: &[Expression<'_>],
+    program: &Program,
+    directory: &[BlockParent],
+    errors: &mut Vec<CheckerError>
+) {
+    for line in lines {
+        match line.t {
+            LineType::Expression => checkStatement(&expressions[line.index], program, directory, errors),
+            LineType::Block => {
+                let mut block_dir = directory.to_vec();
+                block_dir.push(BlockParent {index: line.index, t: BlockParentType::Block});
+                let block = program.getBlock(&block_dir);
+                // the condition and step are worked out in the block around it, like the compiler does
+                if let Some(node) = block.con.as_ref().and_then(|c| c.tree.as_ref()) {
+                    checkNumber(node, program, directory, errors);
+                }
+                if let Some(ref step) = block.step {
+                    checkStatement(step, program, directory, errors);
+                }
+                checkLines(&block.lines, &block.expressions, program, &block_dir, errors);
+            }
+        }
+    }
+}
+
+pub fn runChecker(program: &Program, errors: &mut Vec<CheckerError>) {
+    // heap, const and static initial values
+    for expr in &program.expressions {
+        checkStatement(expr, program, &[], errors);
+    }
+    for i in 0..program.procs.len() {
+        let directory = vec![BlockParent {index: i, t: BlockParentType::Procedure}];
+        checkLines(&program.procs[i].lines, &program.procs[i].expressions, program, &directory, errors);
+    }
+}
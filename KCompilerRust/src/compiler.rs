@@ -2,16 +2,20 @@ use crate::lexer::Token;
 use crate::lexer::TokenType;
 use crate::lexer::VarDest;
 use crate::lexer::TokenCompData;
+use crate::lexer::Span;
 use thiserror::Error;
 use std::fs::File;
 use std::io::Write;
 
 pub use crate::parser::parserTree::*;
 
+pub mod runtime;
+use crate::compiler::runtime::*;
+
 #[derive(Debug, Error, Clone)]
 pub enum CompilerError {
     #[error("(CompilerError) Unidentified error thrown. Reconsider life.")]
-    UnidentifiedError,
+    UnidentifiedError(Span),
     
     #[error("(CompilerError) No main procedure.")]
     NoMainProc,
@@ -23,76 +27,218 @@ pub enum CompilerError {
     UnableToWriteOutFile(String),
 
     #[error("(CompilerError) Unimplemented ExpressionType of '{0:?}'..")]
-    UnimplementedExprType(ExpressionType),
+    UnimplementedExprType(ExpressionType, Span),
 
     #[error("(CompilerError) Unimplemented LineType of '{0:?}'.")]
-    UnimplementedLineType(LineType),
+    UnimplementedLineType(LineType, Span),
 
     #[error("(CompilerError) Unimplemented VarDest of '{0:?}'.")]
-    UnimplementedVarDest(VarDest),
+    UnimplementedVarDest(VarDest, Span),
 
     #[error("(CompilerError) Unimplemented DataAllocationType of '{0:?}'.")]
-    UnimplementedDataAllocType(DataAllocationType),
+    UnimplementedDataAllocType(DataAllocationType, Span),
 
     #[error("(CompilerError) Unimplemented TokenType of '{0:?}'.")]
-    UnimplementedTokenType(TokenType),
+    UnimplementedTokenType(TokenType, Span),
 
     #[error("(CompilerError) Unimplemented argument type of '{0:?}'.")]
-    UnimplementedArgumentType(ExpressionOutLocation),
+    UnimplementedArgumentType(ExpressionOutLocation, Span),
 
     #[error("(CompilerError) Unimplemented register '{0}'.")]
-    UnimplementedReg(String),
+    UnimplementedReg(String, Span),
 
     #[error("(CompilerError) Unimplemented Embedded Procedure '{0}'.")]
-    UnimplementedEmbeddedFunction(String),
+    UnimplementedEmbeddedFunction(String, Span),
 
     #[error("(CompilerError) Unimplemented BlockType '{0:?}'.")]
-    UnimplementedBlockType(BlockType),
+    UnimplementedBlockType(BlockType, Span),
 
     #[error("(CompilerError) Unimplemented ConditionType '{0:?}'.")]
-    UnimplementedConditionType(ConditionType),
+    UnimplementedConditionType(ConditionType, Span),
 
     #[error("(CompilerError) Stand alone token of {1} of type {0:?}.")]
-    InvalidStandAloneToken(TokenType, String),
+    InvalidStandAloneToken(TokenType, String, Span),
 
     #[error("(CompilerError) Invalid assignment to token of {1} of type {0:?}.")]
-    InvalidAssignment(TokenType, String),
+    InvalidAssignment(TokenType, String, Span),
 
     #[error("(CompilerError) Could not move {0:?} to {1:?} as there is no implemented opcode.")]
-    InvalidMove(ExpressionOutLocation, ExpressionOutLocation),
+    InvalidMove(ExpressionOutLocation, ExpressionOutLocation, Span),
 
     #[error("(CompilerError) Invalid procedure call of {0}.")]
-    InvalidProcCall(String),
+    InvalidProcCall(String, Span),
 
     #[error("(CompilerError) Invalid argument count of {0} calling {1}.")]
-    InvalidArgCount(usize, String),
+    InvalidArgCount(usize, String, Span),
 
     #[error("(CompilerError) Invalid store of {0:?} into {1:?}.")]
-    InvalidStore(ExpressionOutLocation, ExpressionOutLocation),
+    InvalidStore(ExpressionOutLocation, ExpressionOutLocation, Span),
 
     #[error("(CompilerError) Invalid literal address {0}.")]
-    InvalidAddress(String),
+    InvalidAddress(String, Span),
 
     #[error("(CompilerError) Invalid buffer indexing.")]
-    InvalidBufferIndexing,
+    InvalidBufferIndexing(Span),
 
     #[error("(CompilerError) Missing condition in block type {0:?}.")]
-    MissingCondition(BlockType),
+    MissingCondition(BlockType, Span),
     
     #[error("(CompilerError) Register {0:?} overriden within expression with operator '{1}'.")]
-    RegOverridden(ExpressionOutLocation, String),
+    RegOverridden(ExpressionOutLocation, String, Span),
 
     #[error("(CompilerError) Encountered blank expression.")]
-    EncounteredBlankExpression,
+    EncounteredBlankExpression(Span),
 
     #[error("(CompilerError) The first argument of a syscall must ALWAYS be single byte literal, instead found {0:?}.")]
-    SysArg1LiteralEnforce(ExpressionOutLocation),
+    SysArg1LiteralEnforce(ExpressionOutLocation, Span),
 
     #[error("(CompilerError) Syscall provided with no arguments.")]
-    SysArgEnforce(usize),
+    SysArgEnforce(usize, Span),
 
     #[error("(CompilerError) Exit provided with {0} arguments instead of 1.")]
-    ExitArgEnforce(usize),
+    ExitArgEnforce(usize, Span),
+
+    #[error("(CompilerError) Left hand side of the assignment can not be assigned to.")]
+    InvalidAssignmentTarget(Span),
+
+    #[error("(CompilerError) Raw data lists can only initialise static buffers.")]
+    ListOutsideStatic(Span),
+
+    #[error("(CompilerError) Values assigned outside of a procedure must be literals, consts or arithmetic on them.")]
+    NonLiteralInitialValue(Span),
+
+    #[error("(CompilerError) Division by a constant 0.")]
+    DivisionByZero(Span),
+
+    #[error("(CompilerError) Passing a {1:?} to {0} is not supported yet.")]
+    UnsupportedParameter(String, DataValueType, Span),
+
+    #[error("(CompilerError) {0} has to return a {1:?}.")]
+    MissingReturnValue(String, DataValueType, Span),
+
+    #[error("(CompilerError) A {0:?} has no fields.")]
+    NotAStruct(DataValueType, Span),
+
+    #[error("(CompilerError) {0} has no field '{1}'.")]
+    UnknownField(String, String, Span),
+
+    #[error("(CompilerError) Fields can only be used on struct variables.")]
+    InvalidFieldAccess(Span),
+
+    #[error("(CompilerError) {0} is initialised with {1} values, it has {2} fields.")]
+    StructInitCount(String, usize, usize, Span),
+
+    #[error("(CompilerError) {0} is initialised with {1} values, it only holds {2}.")]
+    ArrayInitCount(String, usize, u8, Span),
+
+    #[error("(CompilerError) Static {0} is in the ROM and can not be written to.")]
+    ReadOnlyStatic(String, Span),
+
+    #[error("(CompilerError) A {0:?} is not a pointer and can not be dereferenced.")]
+    NotAPointer(DataValueType, Span),
+
+    #[error("(CompilerError) Only heap and stack variables, fields and array elements have an address, static data is in the ROM.")]
+    InvalidAddressOf(Span),
+
+    #[error("(CompilerError) A whole struct can not be copied through a pointer, copy its fields.")]
+    StructThroughPointer(Span),
+}
+
+impl CompilerError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CompilerError::UnidentifiedError(.., s) |
+            CompilerError::UnimplementedExprType(.., s) |
+            CompilerError::UnimplementedLineType(.., s) |
+            CompilerError::UnimplementedVarDest(.., s) |
+            CompilerError::UnimplementedDataAllocType(.., s) |
+            CompilerError::UnimplementedTokenType(.., s) |
+            CompilerError::UnimplementedArgumentType(.., s) |
+            CompilerError::UnimplementedReg(.., s) |
+            CompilerError::UnimplementedEmbeddedFunction(.., s) |
+            CompilerError::UnimplementedBlockType(.., s) |
+            CompilerError::UnimplementedConditionType(.., s) |
+            CompilerError::InvalidStandAloneToken(.., s) |
+            CompilerError::InvalidAssignment(.., s) |
+            CompilerError::InvalidMove(.., s) |
+            CompilerError::InvalidProcCall(.., s) |
+            CompilerError::InvalidArgCount(.., s) |
+            CompilerError::InvalidStore(.., s) |
+            CompilerError::InvalidAddress(.., s) |
+            CompilerError::InvalidBufferIndexing(.., s) |
+            CompilerError::MissingCondition(.., s) |
+            CompilerError::RegOverridden(.., s) |
+            CompilerError::EncounteredBlankExpression(.., s) |
+            CompilerError::SysArg1LiteralEnforce(.., s) |
+            CompilerError::SysArgEnforce(.., s) |
+            CompilerError::ExitArgEnforce(.., s) |
+            CompilerError::InvalidAssignmentTarget(.., s) |
+            CompilerError::ListOutsideStatic(.., s) |
+            CompilerError::NonLiteralInitialValue(.., s) |
+            CompilerError::DivisionByZero(.., s) |
+            CompilerError::UnsupportedParameter(.., s) |
+            CompilerError::MissingReturnValue(.., s) |
+            CompilerError::NotAStruct(.., s) |
+            CompilerError::UnknownField(.., s) |
+            CompilerError::InvalidFieldAccess(.., s) |
+            CompilerError::StructInitCount(.., s) |
+            CompilerError::ArrayInitCount(.., s) |
+            CompilerError::ReadOnlyStatic(.., s) |
+            CompilerError::NotAPointer(.., s) |
+            CompilerError::InvalidAddressOf(.., s) |
+            CompilerError::StructThroughPointer(.., s) => Some(*s),
+            _ => None,
+        }
+    }
+
+    // errors raised without a token at hand get the span of whatever was being compiled
+    pub fn at(mut self, span: Span) -> CompilerError {
+        match &mut self {
+            CompilerError::UnidentifiedError(.., s) |
+            CompilerError::UnimplementedExprType(.., s) |
+            CompilerError::UnimplementedLineType(.., s) |
+            CompilerError::UnimplementedVarDest(.., s) |
+            CompilerError::UnimplementedDataAllocType(.., s) |
+            CompilerError::UnimplementedTokenType(.., s) |
+            CompilerError::UnimplementedArgumentType(.., s) |
+            CompilerError::UnimplementedReg(.., s) |
+            CompilerError::UnimplementedEmbeddedFunction(.., s) |
+            CompilerError::UnimplementedBlockType(.., s) |
+            CompilerError::UnimplementedConditionType(.., s) |
+            CompilerError::InvalidStandAloneToken(.., s) |
+            CompilerError::InvalidAssignment(.., s) |
+            CompilerError::InvalidMove(.., s) |
+            CompilerError::InvalidProcCall(.., s) |
+            CompilerError::InvalidArgCount(.., s) |
+            CompilerError::InvalidStore(.., s) |
+            CompilerError::InvalidAddress(.., s) |
+            CompilerError::InvalidBufferIndexing(.., s) |
+            CompilerError::MissingCondition(.., s) |
+            CompilerError::RegOverridden(.., s) |
+            CompilerError::EncounteredBlankExpression(.., s) |
+            CompilerError::SysArg1LiteralEnforce(.., s) |
+            CompilerError::SysArgEnforce(.., s) |
+            CompilerError::ExitArgEnforce(.., s) |
+            CompilerError::InvalidAssignmentTarget(.., s) |
+            CompilerError::ListOutsideStatic(.., s) |
+            CompilerError::NonLiteralInitialValue(.., s) |
+            CompilerError::DivisionByZero(.., s) |
+            CompilerError::UnsupportedParameter(.., s) |
+            CompilerError::MissingReturnValue(.., s) |
+            CompilerError::NotAStruct(.., s) |
+            CompilerError::UnknownField(.., s) |
+            CompilerError::InvalidFieldAccess(.., s) |
+            CompilerError::StructInitCount(.., s) |
+            CompilerError::ArrayInitCount(.., s) |
+            CompilerError::ReadOnlyStatic(.., s) |
+            CompilerError::NotAPointer(.., s) |
+            CompilerError::InvalidAddressOf(.., s) |
+            CompilerError::StructThroughPointer(.., s)
+                if s.line == 0 => {*s = span;}
+            _ => (),
+        }
+        return self;
+    }
 }
 
 #[derive(Default, Debug, PartialEq, Clone)]
@@ -102,9 +248,11 @@ pub enum ExpressionOutLocation{
     RegisterA,
     RegisterX,
     RegisterY,
+    RegisterAX, //16-bit value, A low byte and X high byte
     Stack(u8),
     Heap(u16),
     Static(String),
+    Rom(String, u8), //a byte of static data, label plus offset, read through Y
     Literal(String),
     StringLiteral(String)
 }
@@ -152,7 +300,14 @@ fn moveOutTo(
                     expressionString.push_str(&addr.to_string());
                     expressionString.push_str(";\n");
                 }
-                _ => return Err(CompilerError::InvalidMove(start_loc, dest)),
+                ExpressionOutLocation::Rom(label, offset) =>{
+                    expressionString.push_str("LDYC ");
+                    expressionString.push_str(&offset.to_string());
+                    expressionString.push_str(";\nLDARY ");
+                    expressionString.push_str(&label);
+                    expressionString.push_str(";\n");
+                }
+                _ => return Err(CompilerError::InvalidMove(start_loc, dest, Span::default())),
             }
         }
         ExpressionOutLocation::RegisterY => {
@@ -163,7 +318,7 @@ fn moveOutTo(
                     expressionString.push_str(";\n");
                 }
                 ExpressionOutLocation::RegisterX =>{
-                    expressionString.push_str("TXY;\n");
+                    expressionString.push_str("STX 0;\nLDY 0;\n");
                 }
                 ExpressionOutLocation::RegisterA =>{
                     expressionString.push_str("TAY;\n");
@@ -178,7 +333,40 @@ fn moveOutTo(
                     expressionString.push_str(&addr.to_string());
                     expressionString.push_str(";\n");
                 }
-                _ => return Err(CompilerError::InvalidMove(start_loc, dest)),
+                _ => return Err(CompilerError::InvalidMove(start_loc, dest, Span::default())),
+            }
+        }
+        ExpressionOutLocation::RegisterX => {
+            match start_loc {
+                ExpressionOutLocation::Literal(l) => {
+                    expressionString.push_str("LDXC ");
+                    expressionString.push_str(&l);
+                    expressionString.push_str(";\n");
+                }
+                ExpressionOutLocation::RegisterA =>{
+                    expressionString.push_str("TAX;\n");
+                }
+                ExpressionOutLocation::RegisterY =>{
+                    expressionString.push_str("STY 0;\nLDX 0;\n");
+                }
+                ExpressionOutLocation::Stack(addr) =>{
+                    expressionString.push_str("LDXS ");
+                    expressionString.push_str(&addr.to_string());
+                    expressionString.push_str(";\n");
+                }
+                ExpressionOutLocation::Heap(addr) =>{
+                    expressionString.push_str("LDX ");
+                    expressionString.push_str(&addr.to_string());
+                    expressionString.push_str(";\n");
+                }
+                ExpressionOutLocation::Rom(label, offset) =>{
+                    expressionString.push_str("LDYC ");
+                    expressionString.push_str(&offset.to_string());
+                    expressionString.push_str(";\nLDXRY ");
+                    expressionString.push_str(&label);
+                    expressionString.push_str(";\n");
+                }
+                _ => return Err(CompilerError::InvalidMove(start_loc, dest, Span::default())),
             }
         }
         ExpressionOutLocation::Heap(addr) => {
@@ -213,6 +401,11 @@ fn moveOutTo(
                     expressionString.push_str(&addr.to_string());
                     expressionString.push_str(";\n");
                 }
+                ExpressionOutLocation::RegisterX => {
+                    expressionString.push_str("STX ");
+                    expressionString.push_str(&addr.to_string());
+                    expressionString.push_str(";\n");
+                }
                 ExpressionOutLocation::Literal(_) =>{
                     expressionString.push_str(
                         &moveOutTo(start_loc.clone(), ExpressionOutLocation::RegisterY)?
@@ -229,7 +422,7 @@ fn moveOutTo(
                     expressionString.push_str(&label);
                     expressionString.push_str(";\n");
                 }
-                _ => return Err(CompilerError::InvalidMove(start_loc, dest)),
+                _ => return Err(CompilerError::InvalidMove(start_loc, dest, Span::default())),
             }
         }
         ExpressionOutLocation::Stack(s_addr) => {
@@ -253,14 +446,23 @@ fn moveOutTo(
                     expressionString.push_str(&s_addr.to_string());
                     expressionString.push_str(";\n");
                 }
-                ExpressionOutLocation::Heap(addr) =>{
-                    expressionString.push_str("STSH ");
+                ExpressionOutLocation::RegisterX => {
+                    expressionString.push_str("STXS ");
                     expressionString.push_str(&s_addr.to_string());
-                    expressionString.push_str(" ");
-                    expressionString.push_str(&addr.to_string());
                     expressionString.push_str(";\n");
                 }
-                _ => return Err(CompilerError::InvalidMove(start_loc, dest)),
+                ExpressionOutLocation::Stack(ref addr) if *addr == s_addr => (),
+                ExpressionOutLocation::Stack(_) |
+                ExpressionOutLocation::Heap(_) =>{
+                    expressionString.push_str(
+                        &moveOutTo(start_loc.clone(), ExpressionOutLocation::RegisterY)?
+                    );
+
+                    expressionString.push_str(
+                        &moveOutTo(ExpressionOutLocation::RegisterY, dest.clone())?
+                    );
+                }
+                _ => return Err(CompilerError::InvalidMove(start_loc, dest, Span::default())),
             }
         }
         ExpressionOutLocation::Static(ref label) => {
@@ -293,10 +495,10 @@ fn moveOutTo(
                     if !lastWasEscape {expressionString.push_str("\" ");}
                     expressionString.push_str("0\nEND\n");
                 }
-                _ => return Err(CompilerError::InvalidMove(start_loc, dest)),
+                _ => return Err(CompilerError::InvalidMove(start_loc, dest, Span::default())),
             }
         }
-        _ => return Err(CompilerError::InvalidMove(start_loc, dest))
+        _ => return Err(CompilerError::InvalidMove(start_loc, dest, Span::default()))
     }
     return Ok(expressionString);
 }
@@ -307,7 +509,8 @@ macro_rules! grabVariableCompNP {
         let variable = match $tuple.1 {
             VarDest::Heap => Ok($program.heap_variables[$tuple.0]),
             VarDest::ProgramStatic => Ok($program.static_variables[$tuple.0]),
-            _ => Err(CompilerError::UnimplementedVarDest($tuple.1)),
+            VarDest::ProgramConst => Ok($program.const_variables[$tuple.0]),
+            _ => Err(CompilerError::UnimplementedVarDest($tuple.1, Span::default())),
         }; 
         variable
     })
@@ -336,910 +539,2597 @@ struct EvaluationPackage<'a>{
 pub enum ConditionType {
     #[default]
     Eq, 
-    NEq, Lesser /*BMI*/, Greater /*BPL*/, EqLesser, EqGreater
+    NEq, Lesser /*BCC*/, Greater, EqLesser, EqGreater /*BCS*/,
+    // signed compares are read from N and V after a SBC
+    SignedLesser, SignedGreater, SignedEqLesser, SignedEqGreater
 }
 
-//implement for evaluation package
-fn evaluateExpr(
-    expr: Expression<'_>,
-    program: &Program, 
-    current_pkg: &EvaluationPackage
-) -> Result<(String, ExpressionOutLocation), CompilerError> {
-    #[macro_export]
-    macro_rules! grabVariableComp {
-        ($tuple: expr, $program: expr, $current_pkg: expr) => ({
-            let variable = match $tuple.1 {
-                VarDest::CurrentProc => Ok(program.procs[$current_pkg.directory[0].index].variables[$tuple.0]),
-                VarDest::Argument => Ok(program.procs[$current_pkg.directory[0].index].arguments[$tuple.0]),
-                VarDest::Heap => Ok($program.heap_variables[$tuple.0]),
-                VarDest::ProgramStatic => Ok($program.static_variables[$tuple.0]),
-                _ => Err(CompilerError::UnimplementedVarDest($tuple.1)),
-            }; 
-            variable
-        })
-    }
-
-    #[macro_export]
-    macro_rules! procSyntax  {
-        ($i: ident, $arg_count: ident, $exprTksLen: ident, $exprpkg: ident, $action: expr) => ({
-            if expr.tks[$i].tk_data == "("{
-                $i+=1;
-                while expr.tks[$i].tk_data != ")" && $i < $exprTksLen{
-                    // grab arg expression
-                    let arg_start_index = $i;
-                    while expr.tks[$i].tk_data != ")" && expr.tks[$i].tk_data != "," && $i < $exprTksLen {$i+=1;}
-                    if $i < $exprTksLen{
-                        $i-=1;
-                        let $exprpkg = evaluateExpr(
-                            Expression {
-                                t:ExpressionType::Unspecified, 
-                                tks: { 
-                                    match arg_start_index-$i {
-                                        0 => vec![expr.tks[$i]],
-                                        _ => expr.tks[arg_start_index..$i].to_vec()
-                                    }
-                                }
-                            },
-                            program,
-                            current_pkg
-                        )?;
-                        
-                        //action
-                        $action;
+// zero page used by generated code
+const SCRATCH_ADDR: u16 = 0x0000; //operand of the instruction being emitted
+const LOW_BYTE_ADDR: u16 = 0x0006; //low byte of a 16-bit result while the high byte is worked out
+const POINTER_ADDR: u16 = 0x0002; //address a pointer holds while it is read or written through
+const TEMP_ADDR: u16 = 0x0010; //results parked while the other side of an operator is evaluated, 2 bytes each
+
+// state kept while compiling the program
+#[derive(Default, Debug)]
+struct CompilerState {
+    pub label_discriminant: usize, //reset for every proc
+    pub temps: u16, //temps currently in use
+    pub frame_offset: u8, //bytes put on the stack for a call being set up, shifts every stack address
+    pub helpers: Vec<Helper>, //runtime routines the program calls
+    pub loops: Vec<LoopLabels> //every loop being compiled, innermost last
+}
 
-                        $arg_count+=1;
-                        $i+=2;
+#[derive(Default, Debug)]
+struct LoopLabels {
+    pub label: String,
+    pub continue_to: &'static str, //suffix of the label continue jumps to
+    pub depth: usize, //of the loop block, its frame and every frame inside it is freed when jumping out
+    pub broken: bool //break jumps to the _END label, only placed when used
+}
 
-                        if expr.tks[$i-1].tk_data == ")" {break;}
-                    } else {break;}
-                }
-            }
-        })
+impl CompilerState {
+    // next unique label of the proc, ex: main_IF3
+    fn label(&mut self, proc_label: &str, kind: &str) -> String {
+        let mut label: String = proc_label.to_string();
+        label.push('_');
+        label.push_str(kind);
+        label.push_str(&self.label_discriminant.to_string());
+        self.label_discriminant += 1;
+        return label;
     }
 
-    let mut conditionType: ConditionType = ConditionType::Eq;
-    let mut expressionString: String = Default::default();
-    let mut expressionOutput: ExpressionOutLocation = ExpressionOutLocation::None;
-    let mut startingIndex: usize = 0;
-    let mut args: Vec<ExpressionOutLocation> = vec![];
-    let exprTksLen = expr.tks.len();
-    if exprTksLen == 1 {
-        expressionOutput = match expr.tks[0].tk_type {
-            TokenType::Variable => {
-                let var = grabVariableComp!(expr.tks[0].tk_comp_data.var().ok_or(CompilerError::UnidentifiedError)?, program, current_pkg)?;
-                let o = match var.t.a{
-                    DataAllocationType::Stack(addr) => ExpressionOutLocation::Stack(addr),
-                    DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr),
-                    DataAllocationType::Static => ExpressionOutLocation::Static(var.label.to_string()),
-                    _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a)),
-                };
-                o
-            }
-            TokenType::CharLiteral |
-            TokenType::NumberLiteral |
-            TokenType::HexNumberLiteral => {
-                ExpressionOutLocation::Literal(expr.tks[0].tk_data.to_string())
-            }
-            // using a procedure as a label
-            TokenType::ProcedureCall => {
-                ExpressionOutLocation::Static(expr.tks[0].tk_data.to_string())
-            }
-            TokenType::Register => {
-                ExpressionOutLocation::reg(expr.tks[0].tk_data)
-                    .ok_or(CompilerError::UnimplementedReg(expr.tks[0].tk_data.to_string()))?
-            }
-            _ => return Err(CompilerError::InvalidStandAloneToken(expr.tks[0].tk_type, expr.tks[0].tk_data.to_string())),
-        };
-        match expr.t {
-            ExpressionType::ConditionalWhile |
-            ExpressionType::ConditionalIf => {
-                expressionString.push_str(
-                    &moveOutTo(expressionOutput.clone(), ExpressionOutLocation::RegisterA)?
-                );
-
-                expressionString.push_str("CMPC 0;\n");
-                conditionType = ConditionType::NEq;
-                expressionOutput = ExpressionOutLocation::None;
-            }
-            _ => {}
-        }
-    } else if exprTksLen == 0 {
-        return Err(CompilerError::EncounteredBlankExpression);
-    } else if expr.t == ExpressionType::Assignment {
-        startingIndex = 2;
-
-        match expr.tks[0].tk_type {
-            TokenType::Variable => {
-                // get variable
-                let var = grabVariableComp!(expr.tks[0].tk_comp_data.var().ok_or(CompilerError::UnidentifiedError)?, program, current_pkg)?;
-                let o = match var.t.a{
-                    //when stack
-                    DataAllocationType::Stack(addr) => {
-                        match var.t.v {
-                            // if buffer, offset for index
-                            DataValueType::Buffer => {
-                                if expr.tks.len() > 3 && 
-                                    expr.tks[1].tk_data == "[" &&
-                                    expr.tks[3].tk_data == "]"
-                                {
-                                    startingIndex = 5;
-                                    ExpressionOutLocation::Stack(
-                                        addr + {
-                                            match expr.tks[2].tk_data.parse::<u8>() {
-                                                Ok(v) => v,
-                                                Err(_e) => return Err(CompilerError::InvalidAddress(expr.tks[2].tk_data.to_string())),
-                                            }
-                                        }
-                                    )
-                                }
-                                else {return Err(CompilerError::InvalidBufferIndexing);}
-                            }
-                            //return stack addr
-                            _ => ExpressionOutLocation::Stack(addr),
-                        }
-                    }
-                    //when heap
-                    DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr),
-                    _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a)),
-                };
-
-                //eval expression
-                let exprpkg = evaluateExpr(
-                    Expression {t:ExpressionType::Unspecified, tks:expr.tks[startingIndex..exprTksLen].to_vec()},
-                    program,
-                    current_pkg
-                )?;
-                expressionString.push_str(&exprpkg.0);
-                expressionOutput = exprpkg.1;
-
-                //print move
-                expressionString.push_str(
-                    &moveOutTo(expressionOutput.clone(), o)?
-                );
-            }
-            TokenType::Register => {
-                //eval expression
-                let exprpkg = evaluateExpr(
-                    Expression {t:ExpressionType::Unspecified, tks:expr.tks[startingIndex..exprTksLen].to_vec()},
-                    program,
-                    current_pkg
-                )?;
-                expressionString.push_str(&exprpkg.0);
-                expressionOutput = exprpkg.1;
-
-                //print move
-                expressionString.push_str(
-                    &moveOutTo(
-                        expressionOutput.clone(),
-                        ExpressionOutLocation::reg(expr.tks[0].tk_data)
-                            .ok_or(CompilerError::UnimplementedReg(expr.tks[0].tk_data.to_string()))?
-                    )?
-                );
-            }
-            _ => return Err(CompilerError::InvalidAssignment(expr.tks[0].tk_type, expr.tks[0].tk_data.to_string()))
-        } 
-        
-    } else {
-        let mut i: usize = 0;
-        while i < exprTksLen {
-            let tk = expr.tks[i];
-            match tk.tk_type {
-                TokenType::CharLiteral |
-                TokenType::NumberLiteral |
-                TokenType::HexNumberLiteral => {
-                    args.push(ExpressionOutLocation::Literal(expr.tks[0].tk_data.to_string()));
-                }
-                TokenType::Register => {
-                    args.push(
-                        ExpressionOutLocation::reg(expr.tks[0].tk_data)
-                            .ok_or(CompilerError::UnimplementedReg(expr.tks[0].tk_data.to_string()))?
-                    );
-                }
-                TokenType::Variable => {
-                    let var = grabVariableComp!(expr.tks[i].tk_comp_data.var().ok_or(CompilerError::UnidentifiedError)?, program, current_pkg)?;
-                    let o = match var.t.a{
-                        DataAllocationType::Stack(addr) => {
-                            match var.t.v {
-                                // if buffer, offset for index
-                                DataValueType::Buffer => {
-                                    if expr.tks.len() > i+3 && 
-                                        expr.tks[i+1].tk_data == "[" &&
-                                        expr.tks[i+3].tk_data == "]"
-                                    {   
-                                        i+=3;
-                                        ExpressionOutLocation::Stack(
-                                            addr + {
-                                                match expr.tks[i-1].tk_data.parse::<u8>() {
-                                                    Ok(v) => v,
-                                                    Err(_e) => return Err(CompilerError::InvalidAddress(expr.tks[i-1].tk_data.to_string())),
-                                                }
-                                            }
-                                        )
-                                    }
-                                    else {return Err(CompilerError::InvalidBufferIndexing);}
-                                }
-                                //return stack addr
-                                _ => ExpressionOutLocation::Stack(addr),
-                            }
-                        }
-                        DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr),
-                        _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a)),
-                    };
-                    args.push(o);
-                }
-                TokenType::EmbeddedFunction =>{
-                    match tk.tk_data {
-                        "store" => {
-                            // make function syntax parser into macro
-                            // load up args
-                            i = 1;
-                            let mut args: Vec<ExpressionOutLocation> = vec![];
-                            let mut arg_count = 0;
-                            procSyntax!(i, arg_count, exprTksLen, exprpkg, {
-                                args.push(exprpkg.1.clone());
-                            });
-                            //aftermath
-                            if arg_count != 2 {
-                                return Err(CompilerError::InvalidArgCount(arg_count, tk.tk_data.to_string()));
-                            }
-
-                            match &args[1] {
-                                ExpressionOutLocation::Literal(l) => {
-                                    expressionString.push_str(
-                                        &moveOutTo(
-                                            args[0].clone(), 
-                                            ExpressionOutLocation::Heap({
-                                                if !l.starts_with("0x") {
-                                                    match l.parse::<u16>() {
-                                                        Ok(v) => v,
-                                                        Err(_e) => return Err(CompilerError::InvalidAddress(l.clone())),
-                                                    }
-                                                }
-                                                else{
-                                                    match u16::from_str_radix(l.trim_start_matches("0x"), 16) {
-                                                        Ok(v) => v,
-                                                        Err(_e) => return Err(CompilerError::InvalidAddress(l.clone())),
-                                                    }
-                                                }
-                                            })
-                                        )?
-                                    );
-                                }
-                                _ => return Err(CompilerError::InvalidStore(args[0].clone(), args[1].clone()))
-                            }
-                        }
-                        "sys" => {
-                            // load up args
-                            // no returns
-                            i = 1;
-                            let mut args: Vec<ExpressionOutLocation> = vec![];
-                            let mut arg_count = 0;
-                            procSyntax!(i, arg_count, exprTksLen, exprpkg, {
-                                args.push(exprpkg.1.clone());
-                                if arg_count > 0 {
-                                    expressionString.push_str(&exprpkg.0);
-                                    expressionString.push_str(
-                                        &moveOutTo(exprpkg.1, ExpressionOutLocation::Heap(0xFFFE))?
-                                    );
-                                }
-                            });
-                            expressionString.push_str("SYS ");
-                            if args.len() < 1 {
-                                return Err(CompilerError::SysArgEnforce(args.len()));
-                            }
-                            match &args[0]{
-                                ExpressionOutLocation::Literal(l) => expressionString.push_str(&l),
-                                _ => return Err(CompilerError::SysArg1LiteralEnforce(args[0].clone())),
-                            }
-                            expressionString.push_str(";\n");
-                        }
-                        "exit" => {
-                            // load up args
-                            // no returns
-                            i = 1;
-                            let mut args: Vec<ExpressionOutLocation> = vec![];
-                            let mut _arg_count = 0;
-                            procSyntax!(i, _arg_count, exprTksLen, exprpkg, {
-                                args.push(exprpkg.1.clone());
-                            });
-
-                            if args.len() < 1 || args.len() > 1 {
-                                return Err(CompilerError::ExitArgEnforce(args.len()));
-                            }
-
-                            expressionString.push_str(
-                                &moveOutTo(args[0].clone(), ExpressionOutLocation::RegisterA)?
-                            );
-
-                            expressionString.push_str("BRK;\n");
-                        }
-                        &_ => return Err(CompilerError::UnimplementedEmbeddedFunction(tk.tk_data.to_string()))
-                    }
-                }
-                TokenType::ProcedureCall => {
-                    // support returns later
-                    let _called_proc = program.procs.as_slice().into_iter()
-                        .find(|&p| p.label == tk.tk_data)
-                        .ok_or(CompilerError::InvalidProcCall(tk.tk_data.to_string()));
-                    
-                    // load up args
-                    let argSlots = [
-                        ExpressionOutLocation::Heap(0x0005),
-                        ExpressionOutLocation::Heap(0x0004),
-                        ExpressionOutLocation::Heap(0x0003),
-                        ExpressionOutLocation::Heap(0x0002),
-                        ExpressionOutLocation::Heap(0x0001),
-                        ExpressionOutLocation::Heap(0x0000),
-                    ];
-
-                    // proc call syntax
-                    let mut arg_count = 0;
-                    i+=1;
-                    procSyntax!(i, arg_count, exprTksLen, exprpkg, {
-                        expressionString.push_str(&exprpkg.0);
-                        expressionString.push_str(
-                            &moveOutTo(exprpkg.1, argSlots[arg_count].clone())?
-                        );
-                    });
-
-                    // push jsr
-                    expressionString.push_str("JSR ");
-                    expressionString.push_str(tk.tk_data);
-                    expressionString.push_str(";\n");
-                }
-                TokenType::OpAdd => {
-                    // eval any exprs after (this reverses priority, fix later)
-                    let exprpkg = evaluateExpr(
-                        Expression {t:ExpressionType::Unspecified, tks:expr.tks[(i+1)..exprTksLen].to_vec()},
-                        program,
-                        current_pkg
-                    )?;
-
-                    //check if both args exist
-                    let arg2: ExpressionOutLocation;
-                    if args.len() < 1 {
-                        return Err(CompilerError::UnidentifiedError);
-                    }
-
-                    // put arg1 in A reg
-                    expressionString.push_str(&exprpkg.0);
-                    if args[0] == ExpressionOutLocation::RegisterA && exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        return Err(CompilerError::RegOverridden(ExpressionOutLocation::RegisterA, "+".to_string()));
-                    }
-                    else if exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        arg2 = args[0].clone();
-                    }
-                    else if args[0] == ExpressionOutLocation::RegisterA{
-                        arg2 = exprpkg.1;
-                    }
-                    else {
-                        expressionString.push_str(
-                            &moveOutTo(args[0].clone(), ExpressionOutLocation::RegisterA)?
-                        );
-                        arg2 = exprpkg.1;
-                    }
+    fn pushTemp(&mut self) -> ExpressionOutLocation {
+        let temp = ExpressionOutLocation::Heap(TEMP_ADDR + self.temps*2);
+        self.temps += 1;
+        return temp;
+    }
 
-                    //do a diff add based on arg2 type
-                    match arg2 {
-                        ExpressionOutLocation::Stack(_) => {
-                            expressionString.push_str(
-                                &moveOutTo(arg2.clone(), ExpressionOutLocation::Heap(0x0000))?
-                            );
-                            expressionString.push_str("ADC 0x00;\n");
-                        }
-                        ExpressionOutLocation::Literal(l) => {
-                            expressionString.push_str("ADCC ");
-                            expressionString.push_str(&l);
-                            expressionString.push_str(";\n");
-                        }
-                        _ => return Err(CompilerError::UnimplementedArgumentType(arg2))
-                    }
+    fn popTemp(&mut self) {
+        self.temps -= 1;
+    }
 
-                    expressionOutput = ExpressionOutLocation::RegisterA;
+    fn stack(&self, addr: u8) -> ExpressionOutLocation {
+        return ExpressionOutLocation::Stack(addr + self.frame_offset);
+    }
 
-                    break;
-                }
-                TokenType::OpSubtract => {
-                    // eval any exprs after (this reverses priority, fix later)
-                    let exprpkg = evaluateExpr(
-                        Expression {t:ExpressionType::Unspecified, tks:expr.tks[(i+1)..exprTksLen].to_vec()},
-                        program,
-                        current_pkg
-                    )?;
+    // jump out of or back to the condition of the innermost loop
+    fn loopJump(
+        &mut self,
+        t: ExpressionType,
+        span: Span,
+        program: &Program,
+        directory: &[BlockParent]
+    ) -> Result<String, CompilerError> {
+        let innermost = match self.loops.last_mut() {
+            Some(l) => l,
+            None => return Err(CompilerError::UnimplementedExprType(t, span)),
+        };
+        let mut expressionString: String = Default::default();
+        let frames = blockFrames(program, directory, innermost.depth);
+        if frames != 0 {
+            expressionString.push_str("DAL ");
+            expressionString.push_str(&frames.to_string());
+            expressionString.push_str(";\n");
+        }
+        expressionString.push_str("JMPA ");
+        expressionString.push_str(&innermost.label);
+        if t == ExpressionType::Break {
+            innermost.broken = true;
+            expressionString.push_str("_END;\n");
+        } else {
+            expressionString.push_str(innermost.continue_to);
+            expressionString.push_str(";\n");
+        }
+        return Ok(expressionString);
+    }
 
-                    //check if both args exist
-                    let arg2: ExpressionOutLocation;
-                    if args.len() < 1 {
-                        return Err(CompilerError::UnidentifiedError);
-                    }
+    // marks the helper as used and gives the label to JSR to
+    fn useHelper(&mut self, helper: Helper) -> &'static str {
+        if !self.helpers.contains(&helper) {self.helpers.push(helper);}
+        if let Some(needed) = helper.needs() {self.useHelper(needed);}
+        return helper.label();
+    }
+}
 
-                    // put arg1 in A reg
-                    expressionString.push_str(&exprpkg.0);
-                    if args[0] == ExpressionOutLocation::RegisterA && exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        return Err(CompilerError::RegOverridden(ExpressionOutLocation::RegisterA, "+".to_string()));
-                    }
-                    else if exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        arg2 = args[0].clone();
-                        expressionString.push_str(
-                            &moveOutTo(args[0].clone(), ExpressionOutLocation::Heap(0x0000))?
-                        );
-                    }
-                    else if args[0] == ExpressionOutLocation::RegisterA{
-                        arg2 = exprpkg.1;
-                    }
-                    else {
-                        expressionString.push_str(
-                            &moveOutTo(args[0].clone(), ExpressionOutLocation::RegisterA)?
-                        );
-                        arg2 = exprpkg.1;
-                    }
+// bytes of the block frames from the depth down to the block being compiled
+// an if's frame is already gone while its elif or else runs
+fn blockFrames(program: &Program, directory: &[BlockParent], from_depth: usize) -> u8 {
+    let mut bytes: u8 = 0;
+    for depth in program.liveScopes(directory) {
+        if depth >= from_depth {bytes += program.getBlock(&directory[..depth+1]).allocated_bytes;}
+    }
+    return bytes;
+}
 
-                    //do a diff add based on arg2 type
-                    match arg2 {
-                        ExpressionOutLocation::Stack(_) => {
-                            expressionString.push_str(
-                                &moveOutTo(arg2.clone(), ExpressionOutLocation::Heap(0x0000))?
-                            );
-                            expressionString.push_str("SBC 0x00;\n");
-                        }
-                        ExpressionOutLocation::Literal(l) => {
-                            expressionString.push_str("SBCC ");
-                            expressionString.push_str(&l);
-                            expressionString.push_str(";\n");
-                        }
-                        _ => return Err(CompilerError::UnimplementedArgumentType(arg2))
+// stack addresses are given as seen from the block being compiled,
+// the frames of the blocks entered after the one a variable lives in are on top of it
+#[macro_export]
+macro_rules! grabVariableComp {
+    ($tk: expr, $program: expr, $current_pkg: expr) => ({
+        match $tk.tk_comp_data.var() {
+            Some(tuple) => {
+                let (found, depth) = match tuple.1 {
+                    VarDest::CurrentProc => (Ok($program.procs[$current_pkg.directory[0].index].variables[tuple.0]), 0),
+                    VarDest::Argument => (Ok($program.procs[$current_pkg.directory[0].index].arguments[tuple.0]), 0),
+                    VarDest::Block(d) => (Ok($program.getBlock(&$current_pkg.directory[..d as usize+1]).variables[tuple.0]), d as usize),
+                    VarDest::Heap => (Ok($program.heap_variables[tuple.0]), 0),
+                    VarDest::ProgramStatic => (Ok($program.static_variables[tuple.0]), 0),
+                    VarDest::ProgramConst => (Ok($program.const_variables[tuple.0]), 0),
+                    _ => (Err(CompilerError::UnimplementedVarDest(tuple.1, $tk.span)), 0),
+                };
+                found.map(|mut v: Variable| {
+                    if let DataAllocationType::Stack(addr) = v.t.a {
+                        v.t.a = DataAllocationType::Stack(addr + blockFrames($program, &$current_pkg.directory, depth+1));
                     }
+                    v
+                })
+            }
+            None => Err(CompilerError::UnidentifiedError($tk.span))
+        }
+    })
+}
 
-                    expressionOutput = ExpressionOutLocation::RegisterA;
-
-                    break;
-                }
-                TokenType::OpEq => {
-                    // eval any exprs after (this reverses priority, fix later)
-                    let exprpkg = evaluateExpr(
-                        Expression {t:ExpressionType::Unspecified, tks:expr.tks[(i+1)..exprTksLen].to_vec()},
-                        program,
-                        current_pkg
-                    )?;
+// the condition that holds when conditionType does not
+fn invertCondition(conditionType: ConditionType) -> ConditionType {
+    match conditionType {
+        ConditionType::Eq => return ConditionType::NEq,
+        ConditionType::NEq => return ConditionType::Eq,
+        ConditionType::Lesser => return ConditionType::EqGreater,
+        ConditionType::EqGreater => return ConditionType::Lesser,
+        ConditionType::Greater => return ConditionType::EqLesser,
+        ConditionType::EqLesser => return ConditionType::Greater,
+        ConditionType::SignedLesser => return ConditionType::SignedEqGreater,
+        ConditionType::SignedEqGreater => return ConditionType::SignedLesser,
+        ConditionType::SignedGreater => return ConditionType::SignedEqLesser,
+        ConditionType::SignedEqLesser => return ConditionType::SignedGreater,
+    }
+}
 
-                    //check if both args exist
-                    let arg2: ExpressionOutLocation;
-                    if args.len() < 1 {
-                        return Err(CompilerError::UnidentifiedError);
-                    }
+// branches over the JMPA (3 bytes) placed after it when the condition holds
+// CMP leaves C set when the register is >= the operand, signed is less when N and V differ
+fn skipWhenTrue(conditionType: ConditionType) -> &'static str {
+    match conditionType {
+        ConditionType::Eq => return "BEQ 3;\n",
+        ConditionType::NEq => return "BNE 3;\n",
+        ConditionType::Lesser => return "BCC 3;\n",
+        ConditionType::EqGreater => return "BCS 3;\n",
+        ConditionType::Greater => return "BEQ 2;\nBCS 3;\n",
+        ConditionType::EqLesser => return "BCC 5;\nBEQ 3;\n",
+        ConditionType::SignedLesser => return "BVS 4;\nBMI 7;\nBPL 2;\nBPL 3;\n",
+        ConditionType::SignedEqGreater => return "BVS 4;\nBPL 7;\nBMI 2;\nBMI 3;\n",
+        ConditionType::SignedGreater => return "BEQ 8;\nBVS 4;\nBPL 7;\nBMI 2;\nBMI 3;\n",
+        ConditionType::SignedEqLesser => return "BEQ 11;\nBVS 4;\nBMI 7;\nBPL 2;\nBPL 3;\n",
+    }
+}
 
-                    // put arg1 in A reg
-                    expressionString.push_str(&exprpkg.0);
-                    if args[0] == ExpressionOutLocation::RegisterA && exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        return Err(CompilerError::RegOverridden(ExpressionOutLocation::RegisterA, "+".to_string()));
-                    }
-                    else if exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        arg2 = args[0].clone();
-                    }
-                    else if args[0] == ExpressionOutLocation::RegisterA{
-                        arg2 = exprpkg.1;
-                    }
-                    else {
-                        expressionString.push_str(
-                            &moveOutTo(args[0].clone(), ExpressionOutLocation::RegisterA)?
-                        );
-                        arg2 = exprpkg.1;
-                    }
+// branches over the JMPA (3 bytes) placed after it when the condition fails
+fn skipWhenFalse(conditionType: ConditionType) -> &'static str {
+    return skipWhenTrue(invertCondition(conditionType));
+}
 
-                    //do a diff add based on arg2 type
-                    match arg2 {
-                        ExpressionOutLocation::Stack(_) => {
-                            expressionString.push_str(
-                                &moveOutTo(arg2.clone(), ExpressionOutLocation::Heap(0x0000))?
-                            );
-                            expressionString.push_str("CMP 0x00;\n");
-                        }
-                        ExpressionOutLocation::Literal(l) => {
-                            expressionString.push_str("CMPC ");
-                            expressionString.push_str(&l);
-                            expressionString.push_str(";\n");
-                        }
-                        _ => return Err(CompilerError::UnimplementedArgumentType(arg2))
-                    }
+fn parseAddress(l: &Token<'_>) -> Result<u16, CompilerError> {
+    match literalNumber(l) {
+        Some(v) if (0..=0xFFFF).contains(&v) => return Ok(v as u16),
+        _ => return Err(CompilerError::InvalidAddress(l.tk_data.to_string(), l.span)),
+    }
+}
 
-                    expressionOutput = ExpressionOutLocation::None;
+// number, hex or char literal as written in the source, a negative one gives its two's complement
+fn parseLiteral(l: &str) -> Option<u16> {
+    if let Some(v) = l.strip_prefix('-') {return parseLiteral(v).map(|v| v.wrapping_neg());}
+    if let Some(h) = l.strip_prefix("0x") {return u16::from_str_radix(h, 16).ok();}
+    if l.starts_with('\'') {return l.as_bytes().get(1).map(|c| *c as u16);}
+    return l.parse::<u16>().ok();
+}
 
-                    break;
-                }
-                TokenType::OpGreatEq |
-                TokenType::OpLessEq |
-                TokenType::OpGreat |
-                TokenType::OpLess => {
-                    // eval any exprs after (this reverses priority, fix later)
-                    let exprpkg = evaluateExpr(
-                        Expression {t:ExpressionType::Unspecified, tks:expr.tks[(i+1)..exprTksLen].to_vec()},
-                        program,
-                        current_pkg
-                    )?;
+// a literal as the assembler reads it, a const is written as its value
+// the assembler has no negative numbers, they are written as their two's complement
+fn literalText(tk: &Token<'_>) -> String {
+    if isNegative(tk) || tk.tk_comp_data.constant().is_some() {
+        return literalValue(tk).unwrap_or(0).to_string();
+    }
+    return tk.tk_data.to_string();
+}
 
-                    //check if both args exist
-                    let arg2: ExpressionOutLocation;
-                    if args.len() < 1 {
-                        return Err(CompilerError::UnidentifiedError);
-                    }
+fn literalLocation(tk: &Token<'_>) -> ExpressionOutLocation {
+    match tk.tk_type {
+        TokenType::StringLiteral => return ExpressionOutLocation::StringLiteral(tk.tk_data.to_string()),
+        _ => return ExpressionOutLocation::Literal(literalText(tk)),
+    }
+}
 
-                    // put arg1 in A reg
-                    expressionString.push_str(&exprpkg.0);
-                    if args[0] == ExpressionOutLocation::RegisterA && exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        return Err(CompilerError::RegOverridden(ExpressionOutLocation::RegisterA, "+".to_string()));
-                    }
-                    else if exprpkg.1 == ExpressionOutLocation::RegisterA {
-                        arg2 = args[0].clone();
-                        expressionString.push_str(
-                            &moveOutTo(args[0].clone(), ExpressionOutLocation::Heap(0x0000))?
-                        );
-                    }
-                    else if args[0] == ExpressionOutLocation::RegisterA{
-                        arg2 = exprpkg.1;
-                    }
-                    else {
-                        expressionString.push_str(
-                            &moveOutTo(args[0].clone(), ExpressionOutLocation::RegisterA)?
-                        );
-                        arg2 = exprpkg.1;
-                    }
+pub fn isNegative(tk: &Token<'_>) -> bool {
+    if let Some(c) = tk.tk_comp_data.constant() {return c < 0;}
+    return tk.tk_data.starts_with('-');
+}
 
-                    //do a diff add based on arg2 type
-                    match arg2 {
-                        ExpressionOutLocation::Stack(_) => {
-                            expressionString.push_str(
-                                &moveOutTo(arg2.clone(), ExpressionOutLocation::Heap(0x0000))?
-                            );
-                            expressionString.push_str("CMP 0x00;\n");
-                        }
-                        ExpressionOutLocation::Literal(l) => {
-                            expressionString.push_str("CMPC ");
-                            expressionString.push_str(&l);
-                            expressionString.push_str(";\n");
-                        }
-                        _ => return Err(CompilerError::UnimplementedArgumentType(arg2))
-                    }
+// a signed byte widened to 2 bytes
+fn extendByte(v: u16) -> u16 {
+    return v as u8 as i8 as i16 as u16;
+}
 
-                    conditionType = match tk.tk_type {
-                        TokenType::OpGreatEq => ConditionType::EqGreater,
-                        TokenType::OpLessEq => ConditionType::EqLesser,
-                        TokenType::OpGreat => ConditionType::Greater,
-                        TokenType::OpLess => ConditionType::Lesser,
-                        _ => return Err(CompilerError::UnidentifiedError)
-                    };
-                    expressionOutput = ExpressionOutLocation::None;
+fn literalValue(tk: &Token<'_>) -> Option<u16> {
+    return literalNumber(tk).map(|v| v as u16);
+}
 
-                    break;
-                }
-                _ => return Err(CompilerError::UnimplementedTokenType(tk.tk_type))
-            }
-            i+=1;
-        }
+// value of arithmetic made only of literals and consts, worked out at compile time
+// anything out of 16 bits wraps, the checker reports it when it does not fit where it goes
+pub fn constantValue(node: &ExprNode<'_>) -> Result<Option<u16>, CompilerError> {
+    if let ExprNode::Binary(BinaryOp::Divide | BinaryOp::Modulo, lhs, rhs, span) = node {
+        if lhs.constant().is_some() && rhs.constant() == Some(0) {return Err(CompilerError::DivisionByZero(*span));}
     }
+    return Ok(node.constant().map(|v| v as u16));
+}
 
-    match expr.t{
-        // store output in A reg on return
-        ExpressionType::Return => {
-            expressionString.push_str(
-                &moveOutTo(expressionOutput.clone(), ExpressionOutLocation::RegisterA)?
-            );
-        }
-        ExpressionType::ConditionalIf => {
-            match conditionType {
-                ConditionType::Eq => expressionString.push_str("BEQ 3;\n"),
-                ConditionType::NEq => expressionString.push_str("BNE 3;\n"),
-                ConditionType::EqGreater => expressionString.push_str("BPL 5;\nBEQ 3;\n"),
-                ConditionType::EqLesser => expressionString.push_str("BMI 5;\nBEQ 3;\n"),
-                ConditionType::Greater => expressionString.push_str("BPL 3;\n"),
-                ConditionType::Lesser => expressionString.push_str("BMI 3;\n"),
-                _ => return Err(CompilerError::UnimplementedConditionType(conditionType))
+// nodes that are read straight from where they live without emitting any code
+fn isSimple(node: &ExprNode<'_>, program: &Program, current_pkg: &EvaluationPackage) -> bool {
+    match node {
+        ExprNode::Literal(tk) => return tk.tk_type != TokenType::StringLiteral,
+        ExprNode::Variable(_) => return true,
+        // a field behind a pointer is loaded through it, a static one is read through Y
+        ExprNode::Field(..) => {
+            if matches!(throughPointer(node, program, current_pkg), Ok(Some(_))) {return false;}
+            match fieldOf(node, program, current_pkg) {
+                Ok((var, ..)) => return var.t.a != DataAllocationType::Static,
+                Err(_) => return true,
             }
         }
-        ExpressionType::ConditionalWhile => {
-            match conditionType {
-                ConditionType::NEq => expressionString.push_str("BEQ 3;\n"),
-                ConditionType::Eq => expressionString.push_str("BNE 3;\n"),
-                ConditionType::EqGreater => expressionString.push_str("BMI 3;\n"),
-                ConditionType::EqLesser => expressionString.push_str("BPL 3;\n"),
-                ConditionType::Greater => expressionString.push_str("BMI 5;\nBEQ 3;\n"),
-                ConditionType::Lesser => expressionString.push_str("BPL 5;\nBEQ 3;\n"),
-                _ => return Err(CompilerError::UnimplementedConditionType(conditionType))
+        // a static array is read through Y even with a constant index
+        ExprNode::Index(base, index, span) => {
+            if !matches!(constantValue(index), Ok(Some(_))) {return false;}
+            match indexedArray(base, *span, program, current_pkg) {
+                Ok((var, _)) => return var.t.a != DataAllocationType::Static,
+                Err(_) => return true,
             }
         }
-        _ => {}
-    }
-    if expressionOutput == ExpressionOutLocation::None && args.len() == 1 {
-        expressionOutput = args[0].clone();
+        // folds down to a literal
+        ExprNode::Unary(..) |
+        ExprNode::Binary(..) => return matches!(constantValue(node), Ok(Some(_))),
+        _ => return false,
     }
-    return Ok((expressionString, expressionOutput));
 }
 
-fn iterateOverLines(p: & mut EvaluationPackage<'_>, program: &Program<'_>, contents: &mut String, mut label_discriminant: usize) -> Result<(), CompilerError>{
-    for index in 0..p.lines.len() as usize{
-        match p.lines[index].t {
-            LineType::Expression => {
-                match p.expressions[p.lines[index].index].t {
-                    ExpressionType::Return => {
-                        if p.expressions[p.lines[index].index].tks.len() != 0 {
-                            contents.push_str(
-                                evaluateExpr(
-                                    p.expressions[p.lines[index].index].clone(),
-                                    program,
-                                    &p
-                                )?.0.as_str()
-                            );
-                        }
-                        if p.allocated_bytes != 0 {
-                            contents.push_str("DAL ");
-                            contents.push_str(&p.allocated_bytes.to_string());
-                            contents.push_str(";\n");
-                        }
-                        if program.procs[p.directory[0].index].label == "main" {
-                            contents.push_str("BRK;\n");
+// a call can use every register and the temps
+fn containsCall(node: &ExprNode<'_>) -> bool {
+    match node {
+        ExprNode::Call(..) => return true,
+        ExprNode::Unary(_, operand, _) |
+        ExprNode::Cast(_, operand, _) => return containsCall(operand),
+        ExprNode::Binary(_, lhs, rhs, _) |
+        ExprNode::Index(lhs, rhs, _) |
+        ExprNode::Assign(lhs, rhs, _) => return containsCall(lhs) || containsCall(rhs),
+        ExprNode::List(items, _) => return items.iter().any(containsCall),
+        _ => return false,
+    }
+}
+
+// what a call leaves behind, procs return in A (AX for a short)
+fn returnType(tk: &Token<'_>, span: Span, program: &Program) -> Result<DataValueType, CompilerError> {
+    match tk.tk_type {
+        TokenType::EmbeddedFunction if tk.tk_data == "sys" => return Ok(DataValueType::Uint),
+        TokenType::EmbeddedFunction => return Ok(DataValueType::Void),
+        _ => {
+            let called_proc = tk.tk_comp_data.proc().map(|i| &program.procs[i])
+                .ok_or(CompilerError::InvalidProcCall(tk.tk_data.to_string(), span))?;
+            return Ok(called_proc.retType.v);
+        }
+    }
+}
+
+// the variable indexed and the type of its elements, a buffer holds bytes
+fn indexedArray<'a>(
+    base: &ExprNode<'_>,
+    span: Span,
+    program: &Program<'a>,
+    current_pkg: &EvaluationPackage
+) -> Result<(Variable<'a>, DataValueType), CompilerError> {
+    let tk = match base {
+        ExprNode::Variable(tk) => tk,
+        _ => return Err(CompilerError::InvalidBufferIndexing(span)),
+    };
+    let var = grabVariableComp!(tk, program, current_pkg)?;
+    match var.t.v {
+        DataValueType::Array(i) => return Ok((var, program.arrays[i].v)),
+        DataValueType::Buffer => return Ok((var, DataValueType::Uint)),
+        _ => return Err(CompilerError::InvalidBufferIndexing(span)),
+    }
+}
+
+// location of array[index] when the index is a constant, None when it has to go through Y
+// static arrays are in the ROM so they are always read through Y
+fn indexLocation(
+    base: &ExprNode<'_>,
+    index: &ExprNode<'_>,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &CompilerState
+) -> Result<Option<ExpressionOutLocation>, CompilerError> {
+    let (var, element) = indexedArray(base, span, program, current_pkg)?;
+    let offset = match constantValue(index)? {
+        Some(c) => c.checked_mul(element.size().unwrap_or(1) as u16).ok_or(CompilerError::InvalidBufferIndexing(span))?,
+        None => return Ok(None),
+    };
+    match var.t.a {
+        DataAllocationType::Stack(addr) => {
+            match u8::try_from(offset).ok().and_then(|o| o.checked_add(addr)) {
+                Some(a) => return Ok(Some(state.stack(a))),
+                None => return Err(CompilerError::InvalidBufferIndexing(span)),
+            }
+        }
+        DataAllocationType::Heap(addr) => {
+            match addr.checked_add(offset) {
+                Some(a) => return Ok(Some(ExpressionOutLocation::Heap(a))),
+                None => return Err(CompilerError::InvalidBufferIndexing(span)),
+            }
+        }
+        _ => return Ok(None),
+    }
+}
+
+// puts the byte offset of array[index] in Y, clobbers A
+fn indexOffset(
+    index: &ExprNode<'_>,
+    element: DataValueType,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    let size = element.size().unwrap_or(1) as u16;
+    if let Some(c) = constantValue(index)? {
+        expressionString.push_str("LDYC ");
+        expressionString.push_str(&(c.wrapping_mul(size) & 0xFF).to_string());
+        expressionString.push_str(";\n");
+        return Ok(expressionString);
+    }
+    let exprpkg = evaluateNode(index, program, current_pkg, state)?;
+    expressionString.push_str(&exprpkg.0);
+    let expressionOutput = narrow(exprpkg.1);
+    if size == 2 {
+        if expressionOutput != ExpressionOutLocation::RegisterA {
+            expressionString.push_str(&moveOutTo(expressionOutput, ExpressionOutLocation::RegisterA)?);
+        }
+        expressionString.push_str("STA 0x00;\nCLC;\nADC 0x00;\nTAY;\n");
+    } else if expressionOutput != ExpressionOutLocation::RegisterY {
+        expressionString.push_str(&moveOutTo(expressionOutput, ExpressionOutLocation::RegisterY)?);
+    }
+    return Ok(expressionString);
+}
+
+// loads or stores A (AX for a short) at the array plus Y
+// a ROM word is read a byte at a time as a label can not be offset
+fn indexedAccess(var: &Variable<'_>, program: &Program, element: DataValueType, store: bool, span: Span, state: &CompilerState) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    let word = element.isWord();
+    match var.t.a {
+        DataAllocationType::Stack(addr) => {
+            let addr = match state.stack(addr) {
+                ExpressionOutLocation::Stack(a) => a,
+                _ => addr,
+            };
+            expressionString.push_str(if store {"STASY "} else {"LDASY "});
+            expressionString.push_str(&addr.to_string());
+            expressionString.push_str(";\n");
+            if word {
+                expressionString.push_str(if store {"STXSY "} else {"LDXSY "});
+                expressionString.push_str(&(addr+1).to_string());
+                expressionString.push_str(";\n");
+            }
+        }
+        DataAllocationType::Heap(addr) => {
+            expressionString.push_str(if store {"STAY "} else {"LDAY "});
+            expressionString.push_str(&addr.to_string());
+            expressionString.push_str(";\n");
+            if word {
+                expressionString.push_str(if store {"STXY "} else {"LDXY "});
+                expressionString.push_str(&(addr+1).to_string());
+                expressionString.push_str(";\n");
+            }
+        }
+        DataAllocationType::Static => {
+            if store {return Err(CompilerError::ReadOnlyStatic(var.label.to_string(), span));}
+            expressionString.push_str("LDARY ");
+            expressionString.push_str(&program.staticLabel(var.label, var.module));
+            expressionString.push_str(";\n");
+            if word {
+                expressionString.push_str("INY;\nLDXRY ");
+                expressionString.push_str(&program.staticLabel(var.label, var.module));
+                expressionString.push_str(";\n");
+            }
+        }
+        _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a, span)),
+    }
+    return Ok(expressionString);
+}
+
+// array[index] = value when the index is only known at runtime
+// a value that needs code is worked out first and parked on the stack while Y is set
+fn evaluateIndexedAssign(
+    base: &ExprNode<'_>,
+    index: &ExprNode<'_>,
+    value: &ExprNode<'_>,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let mut expressionString: String = Default::default();
+    let (var, element) = indexedArray(base, span, program, current_pkg)?;
+    if var.t.a == DataAllocationType::Static {return Err(CompilerError::ReadOnlyStatic(var.label.to_string(), span));}
+    let width = element.size().unwrap_or(1);
+    let dest = if width == 2 {ExpressionOutLocation::RegisterAX} else {ExpressionOutLocation::RegisterA};
+    let simple = isSimple(value, program, current_pkg);
+    if simple {
+        expressionString.push_str(&indexOffset(index, element, program, current_pkg, state)?);
+    }
+    let exprpkg = evaluateNode(value, program, current_pkg, state)?;
+    expressionString.push_str(&exprpkg.0);
+    if width == 2 {
+        let value_width = nodeWidth(value, program, current_pkg)?;
+        let signed = nodeSigned(value, program, current_pkg)?;
+        expressionString.push_str(&moveExtendedTo(exprpkg.1, dest.clone(), value_width, signed)?);
+    } else {
+        let expressionOutput = narrow(exprpkg.1);
+        if expressionOutput != dest {
+            expressionString.push_str(&moveOutTo(expressionOutput, dest.clone())?);
+        }
+    }
+    if !simple {
+        expressionString.push_str(&pushValue(width, state));
+        expressionString.push_str(&indexOffset(index, element, program, current_pkg, state)?);
+        expressionString.push_str("LDAS 1;\n");
+        if width == 2 {expressionString.push_str("LDXS 2;\n");}
+        expressionString.push_str(&popValue(width, state));
+    }
+    expressionString.push_str(&indexedAccess(&var, program, element, true, span, state)?);
+    return Ok((expressionString, dest));
+}
+
+// the variable a field is in, the offset of the field from its start and the type of the field
+// a plain variable is its own field at offset 0
+fn fieldOf<'a>(
+    node: &ExprNode<'_>,
+    program: &Program<'a>,
+    current_pkg: &EvaluationPackage
+) -> Result<(Variable<'a>, u8, DataValueType), CompilerError> {
+    match node {
+        ExprNode::Variable(tk) => {
+            let var = grabVariableComp!(tk, program, current_pkg)?;
+            return Ok((var, 0, var.t.v));
+        }
+        ExprNode::Field(base, name, _) => {
+            let (var, offset, v) = fieldOf(base, program, current_pkg)?;
+            let def = match v {
+                DataValueType::Struct(i) => &program.structs[i],
+                _ => return Err(CompilerError::NotAStruct(v, base.span())),
+            };
+            let field = match def.fields.iter().find(|f| f.label == name.tk_data) {
+                Some(f) => f,
+                None => return Err(CompilerError::UnknownField(def.label.to_string(), name.tk_data.to_string(), name.span)),
+            };
+            return Ok((var, offset + field.offset, field.v));
+        }
+        _ => return Err(CompilerError::InvalidFieldAccess(node.span())),
+    }
+}
+
+// fields are at a fixed offset from where their struct starts
+fn fieldLocation(
+    node: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &CompilerState
+) -> Result<ExpressionOutLocation, CompilerError> {
+    let (var, offset, _) = fieldOf(node, program, current_pkg)?;
+    match var.t.a {
+        DataAllocationType::Stack(addr) => return Ok(state.stack(addr + offset)),
+        DataAllocationType::Heap(addr) => return Ok(ExpressionOutLocation::Heap(addr + offset as u16)),
+        DataAllocationType::Static => return Ok(ExpressionOutLocation::Rom(program.staticLabel(var.label, var.module), offset)),
+        _ => return Err(CompilerError::InvalidFieldAccess(node.span())),
+    }
+}
+
+// byte n of a struct
+fn byteAt(loc: &ExpressionOutLocation, n: u8) -> ExpressionOutLocation {
+    match loc {
+        ExpressionOutLocation::Stack(addr) => return ExpressionOutLocation::Stack(addr + n),
+        ExpressionOutLocation::Heap(addr) => return ExpressionOutLocation::Heap(addr + n as u16),
+        ExpressionOutLocation::Rom(label, offset) => return ExpressionOutLocation::Rom(label.clone(), offset + n),
+        _ => return loc.clone(),
+    }
+}
+
+// the type of the value a node names, Void when it is not a variable or field
+fn structType(node: &ExprNode<'_>, program: &Program, current_pkg: &EvaluationPackage) -> Result<DataValueType, CompilerError> {
+    match node {
+        ExprNode::Variable(_) |
+        ExprNode::Field(..) => return Ok(fieldOf(node, program, current_pkg)?.2),
+        _ => return Ok(DataValueType::Void),
+    }
+}
+
+// the leaf fields of a struct in memory order, a nested struct adds its own fields
+fn fieldSizes(v: DataValueType, program: &Program, sizes: &mut Vec<u8>) {
+    match v {
+        DataValueType::Struct(i) => {
+            for field in &program.structs[i].fields {fieldSizes(field.v, program, sizes);}
+        }
+        _ => sizes.push(v.size().unwrap_or(1)),
+    }
+}
+
+// the type of the value a node gives, what is not a variable, field or pointer is taken as a uint
+fn valueType(node: &ExprNode<'_>, program: &Program, current_pkg: &EvaluationPackage) -> Result<DataValueType, CompilerError> {
+    if let Some((_, _, v)) = throughPointer(node, program, current_pkg)? {return Ok(v);}
+    match node {
+        ExprNode::Variable(_) |
+        ExprNode::Field(..) => return Ok(fieldOf(node, program, current_pkg)?.2),
+        ExprNode::Index(base, _, span) => return Ok(indexedArray(base, *span, program, current_pkg)?.1),
+        ExprNode::Cast(t, ..) => return Ok(*t),
+        ExprNode::Call(tk, _, span) => return returnType(tk, *span, program),
+        ExprNode::Assign(target, ..) => return valueType(target, program, current_pkg),
+        _ => return Ok(DataValueType::Uint),
+    }
+}
+
+// the type a node points at, None when it is not a pointer
+// &array points at its first element
+fn pointee(node: &ExprNode<'_>, program: &Program, current_pkg: &EvaluationPackage) -> Result<Option<DataValueType>, CompilerError> {
+    match node {
+        ExprNode::Unary(UnaryOp::AddressOf, operand, _) => {
+            match valueType(operand, program, current_pkg)? {
+                DataValueType::Array(i) => return Ok(Some(program.arrays[i].v)),
+                v => return Ok(Some(v)),
+            }
+        }
+        ExprNode::Binary(BinaryOp::Add, lhs, rhs, _) => {
+            return Ok(pointee(lhs, program, current_pkg)?.or(pointee(rhs, program, current_pkg)?));
+        }
+        ExprNode::Binary(BinaryOp::Subtract, lhs, _, _) => return pointee(lhs, program, current_pkg),
+        _ => return Ok(program.pointee(valueType(node, program, current_pkg)?)),
+    }
+}
+
+// *p and the fields of a struct behind a pointer
+// gives the pointer, the offset from where it points and the type found there
+fn throughPointer<'n, 'a>(
+    node: &'n ExprNode<'a>,
+    program: &Program,
+    current_pkg: &EvaluationPackage
+) -> Result<Option<(&'n ExprNode<'a>, u8, DataValueType)>, CompilerError> {
+    match node {
+        ExprNode::Unary(UnaryOp::Deref, ptr, span) => {
+            match pointee(ptr, program, current_pkg)? {
+                Some(v) => return Ok(Some((ptr, 0, v))),
+                None => return Err(CompilerError::NotAPointer(valueType(ptr, program, current_pkg)?, *span)),
+            }
+        }
+        ExprNode::Field(base, name, _) => {
+            let (ptr, offset, v) = match throughPointer(base, program, current_pkg)? {
+                Some(found) => found,
+                None => return Ok(None),
+            };
+            let def = match v {
+                DataValueType::Struct(i) => &program.structs[i],
+                _ => return Err(CompilerError::NotAStruct(v, base.span())),
+            };
+            let field = match def.fields.iter().find(|f| f.label == name.tk_data) {
+                Some(f) => f,
+                None => return Err(CompilerError::UnknownField(def.label.to_string(), name.tk_data.to_string(), name.span)),
+            };
+            return Ok(Some((ptr, offset + field.offset, field.v)));
+        }
+        _ => return Ok(None),
+    }
+}
+
+// puts the address a pointer holds on the zero page and the offset from it in Y
+fn loadPointer(
+    ptr: &ExprNode<'_>,
+    offset: u8,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<String, CompilerError> {
+    let width = nodeWidth(ptr, program, current_pkg)?;
+    let (mut expressionString, expressionOutput) = evaluateNode(ptr, program, current_pkg, state)?;
+    expressionString.push_str(&moveWordTo(expressionOutput, ExpressionOutLocation::RegisterAX, width)?);
+    expressionString.push_str("STA ");
+    expressionString.push_str(&POINTER_ADDR.to_string());
+    expressionString.push_str(";\nSTX ");
+    expressionString.push_str(&(POINTER_ADDR+1).to_string());
+    expressionString.push_str(";\nLDYC ");
+    expressionString.push_str(&offset.to_string());
+    expressionString.push_str(";\n");
+    return Ok(expressionString);
+}
+
+// loads or stores A (AX for a short) where the loaded pointer plus Y points
+fn pointerAccess(element: DataValueType, store: bool, span: Span) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    if let DataValueType::Struct(_) = element {return Err(CompilerError::StructThroughPointer(span));}
+    expressionString.push_str(if store {"STAIY "} else {"LDAIY "});
+    expressionString.push_str(&POINTER_ADDR.to_string());
+    expressionString.push_str(";\n");
+    if element.isWord() {
+        expressionString.push_str(if store {"INY;\nSTXIY "} else {"INY;\nLDXIY "});
+        expressionString.push_str(&POINTER_ADDR.to_string());
+        expressionString.push_str(";\n");
+    }
+    return Ok(expressionString);
+}
+
+// *p and fields read through a pointer, left in A (AX for a short)
+fn evaluatePointerRead(
+    ptr: &ExprNode<'_>,
+    offset: u8,
+    element: DataValueType,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let mut expressionString = loadPointer(ptr, offset, program, current_pkg, state)?;
+    expressionString.push_str(&pointerAccess(element, false, span)?);
+    if element.isWord() {return Ok((expressionString, ExpressionOutLocation::RegisterAX));}
+    return Ok((expressionString, ExpressionOutLocation::RegisterA));
+}
+
+// *p = value and fields written through a pointer, pointed is what throughPointer found
+// a value that needs code is worked out first and parked on the stack while the pointer is loaded
+fn evaluatePointerAssign(
+    pointed: (&ExprNode<'_>, u8, DataValueType),
+    value: &ExprNode<'_>,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let (ptr, offset, element) = pointed;
+    let mut expressionString: String = Default::default();
+    if let DataValueType::Struct(_) = element {return Err(CompilerError::StructThroughPointer(span));}
+    let width = element.size().unwrap_or(1);
+    let dest = if width == 2 {ExpressionOutLocation::RegisterAX} else {ExpressionOutLocation::RegisterA};
+    let simple = isSimple(value, program, current_pkg);
+    if simple {
+        expressionString.push_str(&loadPointer(ptr, offset, program, current_pkg, state)?);
+    }
+    let exprpkg = evaluateNode(value, program, current_pkg, state)?;
+    expressionString.push_str(&exprpkg.0);
+    if width == 2 {
+        let value_width = nodeWidth(value, program, current_pkg)?;
+        let signed = nodeSigned(value, program, current_pkg)?;
+        expressionString.push_str(&moveExtendedTo(exprpkg.1, dest.clone(), value_width, signed)?);
+    } else {
+        let expressionOutput = narrow(exprpkg.1);
+        if expressionOutput != dest {
+            expressionString.push_str(&moveOutTo(expressionOutput, dest.clone())?);
+        }
+    }
+    if !simple {
+        expressionString.push_str(&pushValue(width, state));
+        expressionString.push_str(&loadPointer(ptr, offset, program, current_pkg, state)?);
+        expressionString.push_str("LDAS 1;\n");
+        if width == 2 {expressionString.push_str("LDXS 2;\n");}
+        expressionString.push_str(&popValue(width, state));
+    }
+    expressionString.push_str(&pointerAccess(element, true, span)?);
+    return Ok((expressionString, dest));
+}
+
+// the address of a stack byte is S plus its offset, on page 1
+fn stackAddress(addr: u8) -> String {
+    let mut expressionString: String = Default::default();
+    expressionString.push_str("TSX;\nTXA;\nCLC;\nADCC ");
+    expressionString.push_str(&addr.to_string());
+    expressionString.push_str(";\nLDXC 1;\n");
+    return expressionString;
+}
+
+// &x, a heap address is known when compiling, a stack address is worked out from S
+// static data is in the ROM and has no address a pointer can use
+fn evaluateAddressOf(
+    target: &ExprNode<'_>,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let mut expressionString: String = Default::default();
+    // the pointer itself with the field offset added
+    if let Some((ptr, offset, _)) = throughPointer(target, program, current_pkg)? {
+        let width = nodeWidth(ptr, program, current_pkg)?;
+        let exprpkg = evaluateNode(ptr, program, current_pkg, state)?;
+        expressionString.push_str(&exprpkg.0);
+        expressionString.push_str(&moveWordTo(exprpkg.1, ExpressionOutLocation::RegisterAX, width)?);
+        if offset != 0 {
+            expressionString.push_str("CLC;\nADCC ");
+            expressionString.push_str(&offset.to_string());
+            expressionString.push_str(";\nSTA ");
+            expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+            expressionString.push_str(";\nTXA;\nADCC 0;\nTAX;\nLDA ");
+            expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+            expressionString.push_str(";\n");
+        }
+        return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+    }
+    let loc = match target {
+        ExprNode::Variable(_) |
+        ExprNode::Field(..) => {
+            let (var, offset, _) = fieldOf(target, program, current_pkg)?;
+            match var.t.a {
+                DataAllocationType::Stack(addr) => state.stack(addr + offset),
+                DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr + offset as u16),
+                _ => return Err(CompilerError::InvalidAddressOf(span)),
+            }
+        }
+        ExprNode::Index(base, index, index_span) => {
+            match indexLocation(base, index, *index_span, program, current_pkg, state)? {
+                Some(loc) => loc,
+                // the offset in Y is added to where the array starts
+                None => {
+                    let (var, element) = indexedArray(base, *index_span, program, current_pkg)?;
+                    expressionString.push_str(&indexOffset(index, element, program, current_pkg, state)?);
+                    match var.t.a {
+                        DataAllocationType::Heap(addr) => {
+                            expressionString.push_str("TYA;\nCLC;\nADCC ");
+                            expressionString.push_str(&(addr & 0xFF).to_string());
+                            expressionString.push_str(";\nSTA ");
+                            expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                            expressionString.push_str(";\nLDAC ");
+                            expressionString.push_str(&(addr >> 8).to_string());
+                            expressionString.push_str(";\nADCC 0;\nTAX;\nLDA ");
+                            expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                            expressionString.push_str(";\n");
+                        }
+                        DataAllocationType::Stack(addr) => {
+                            let addr = match state.stack(addr) {
+                                ExpressionOutLocation::Stack(a) => a,
+                                _ => addr,
+                            };
+                            expressionString.push_str("STY 0x00;\n");
+                            expressionString.push_str(&stackAddress(addr));
+                            expressionString.push_str("CLC;\nADC 0x00;\n");
+                        }
+                        _ => return Err(CompilerError::InvalidAddressOf(span)),
+                    }
+                    return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+                }
+            }
+        }
+        _ => return Err(CompilerError::InvalidAddressOf(span)),
+    };
+    match loc {
+        ExpressionOutLocation::Heap(addr) => return Ok((expressionString, ExpressionOutLocation::Literal(addr.to_string()))),
+        ExpressionOutLocation::Stack(addr) => {
+            expressionString.push_str(&stackAddress(addr));
+            return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+        }
+        _ => return Err(CompilerError::InvalidAddressOf(span)),
+    }
+}
+
+// emits an instruction with A as one side and the operand as the other, ex: ADC, SBC, CMP
+fn applyOperand(mnemonic: &str, operand: ExpressionOutLocation, span: Span) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    match operand {
+        ExpressionOutLocation::Literal(ref l) => {
+            expressionString.push_str(mnemonic);
+            expressionString.push_str("C ");
+            expressionString.push_str(l);
+            expressionString.push_str(";\n");
+        }
+        // already on the zero page
+        ExpressionOutLocation::Heap(addr) if addr < 0x0100 => {
+            expressionString.push_str(mnemonic);
+            expressionString.push(' ');
+            expressionString.push_str(&addr.to_string());
+            expressionString.push_str(";\n");
+        }
+        ExpressionOutLocation::Heap(_) |
+        ExpressionOutLocation::Stack(_) |
+        ExpressionOutLocation::RegisterX |
+        ExpressionOutLocation::RegisterY => {
+            expressionString.push_str(
+                &moveOutTo(operand.clone(), ExpressionOutLocation::Heap(SCRATCH_ADDR))?
+            );
+            expressionString.push_str(mnemonic);
+            expressionString.push_str(" 0x00;\n");
+        }
+        _ => return Err(CompilerError::UnimplementedArgumentType(operand, span))
+    }
+    return Ok(expressionString);
+}
+
+// bytes a node is worked out in, 2 when a short or a literal above 255 is involved
+fn nodeWidth(node: &ExprNode<'_>, program: &Program, current_pkg: &EvaluationPackage) -> Result<u8, CompilerError> {
+    match node {
+        ExprNode::Literal(tk) => {
+            match literalValue(tk) {
+                Some(v) if isNegative(tk) && v >= 0xFF80 => return Ok(1),
+                Some(v) if v > 0xFF => return Ok(2),
+                _ => return Ok(1),
+            }
+        }
+        ExprNode::Variable(tk) => {
+            let var = grabVariableComp!(tk, program, current_pkg)?;
+            if var.t.v.isWord() {return Ok(2);}
+            return Ok(1);
+        }
+        ExprNode::Unary(UnaryOp::Not, ..) => return Ok(1),
+        ExprNode::Unary(UnaryOp::AddressOf, ..) => return Ok(2),
+        ExprNode::Unary(UnaryOp::Deref, ..) |
+        ExprNode::Field(..) => {
+            if valueType(node, program, current_pkg)?.isWord() {return Ok(2);}
+            return Ok(1);
+        }
+        ExprNode::Unary(_, operand, _) => return nodeWidth(operand, program, current_pkg),
+        ExprNode::Cast(t, ..) if t.size() == Some(1) => return Ok(1),
+        ExprNode::Cast(..) => return Ok(2),
+        ExprNode::Index(base, _, span) => {
+            if indexedArray(base, *span, program, current_pkg)?.1.isWord() {return Ok(2);}
+            return Ok(1);
+        }
+        ExprNode::Binary(op, lhs, rhs, _) => {
+            if op.isComparison() || op.isLogical() {return Ok(1);}
+            if let Ok(Some(v)) = constantValue(node) {
+                if v >= 0xFF80 && nodeSigned(node, program, current_pkg)? {return Ok(1);}
+                if v > 0xFF {return Ok(2);}
+                return Ok(1);
+            }
+            return Ok(nodeWidth(lhs, program, current_pkg)?.max(nodeWidth(rhs, program, current_pkg)?));
+        }
+        ExprNode::Assign(target, ..) => return nodeWidth(target, program, current_pkg),
+        ExprNode::Call(tk, _, span) => {
+            if returnType(tk, *span, program)?.isWord() {return Ok(2);}
+            return Ok(1);
+        }
+        _ => return Ok(1),
+    }
+}
+
+// whether a node is worked out as a signed number, a literal takes the sign of the other side
+fn nodeSigned(node: &ExprNode<'_>, program: &Program, current_pkg: &EvaluationPackage) -> Result<bool, CompilerError> {
+    match node {
+        ExprNode::Literal(tk) => return Ok(isNegative(tk)),
+        ExprNode::Variable(tk) => {
+            let var = grabVariableComp!(tk, program, current_pkg)?;
+            return Ok(var.t.v.isSigned());
+        }
+        ExprNode::Unary(UnaryOp::Not, ..) |
+        ExprNode::Unary(UnaryOp::AddressOf, ..) => return Ok(false),
+        ExprNode::Unary(UnaryOp::Deref, ..) |
+        ExprNode::Field(..) => return Ok(valueType(node, program, current_pkg)?.isSigned()),
+        ExprNode::Unary(_, operand, _) => return nodeSigned(operand, program, current_pkg),
+        ExprNode::Cast(t, ..) => return Ok(t.isSigned()),
+        ExprNode::Index(base, _, span) => return Ok(indexedArray(base, *span, program, current_pkg)?.1.isSigned()),
+        ExprNode::Binary(op, lhs, rhs, _) => {
+            if op.isComparison() || op.isLogical() {return Ok(false);}
+            return Ok(nodeSigned(lhs, program, current_pkg)? || nodeSigned(rhs, program, current_pkg)?);
+        }
+        ExprNode::Assign(target, ..) => return nodeSigned(target, program, current_pkg),
+        ExprNode::Call(tk, _, span) => return Ok(returnType(tk, *span, program)?.isSigned()),
+        _ => return Ok(false),
+    }
+}
+
+// the low byte of a value used where only one byte fits
+fn narrow(loc: ExpressionOutLocation) -> ExpressionOutLocation {
+    match loc {
+        ExpressionOutLocation::RegisterAX => return ExpressionOutLocation::RegisterA,
+        ExpressionOutLocation::Literal(ref l) => {
+            match parseLiteral(l) {
+                Some(v) if v > 0xFF => return ExpressionOutLocation::Literal((v & 0xFF).to_string()),
+                _ => return loc,
+            }
+        }
+        _ => return loc,
+    }
+}
+
+// low and high byte of a value, a 1 byte value has a high byte of 0
+fn wordBytes(
+    loc: ExpressionOutLocation,
+    width: u8
+) -> Result<(ExpressionOutLocation, ExpressionOutLocation), CompilerError> {
+    if width == 1 {
+        return Ok((narrow(loc), ExpressionOutLocation::Literal("0".to_string())));
+    }
+    match loc {
+        ExpressionOutLocation::Literal(ref l) => {
+            let v = parseLiteral(l).ok_or(CompilerError::InvalidAddress(l.to_string(), Span::default()))?;
+            return Ok((
+                ExpressionOutLocation::Literal((v & 0xFF).to_string()),
+                ExpressionOutLocation::Literal((v >> 8).to_string())
+            ));
+        }
+        ExpressionOutLocation::Heap(addr) => return Ok((ExpressionOutLocation::Heap(addr), ExpressionOutLocation::Heap(addr+1))),
+        ExpressionOutLocation::Stack(addr) => return Ok((ExpressionOutLocation::Stack(addr), ExpressionOutLocation::Stack(addr+1))),
+        ExpressionOutLocation::Rom(ref label, offset) => return Ok((loc.clone(), ExpressionOutLocation::Rom(label.clone(), offset+1))),
+        ExpressionOutLocation::RegisterAX => return Ok((ExpressionOutLocation::RegisterA, ExpressionOutLocation::RegisterX)),
+        _ => return Err(CompilerError::InvalidMove(loc, ExpressionOutLocation::RegisterAX, Span::default())),
+    }
+}
+
+// moves a value into a 2 byte location, low byte first
+fn moveWordTo(
+    start_loc: ExpressionOutLocation,
+    dest: ExpressionOutLocation,
+    width: u8
+) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    if start_loc == dest && width == 2 {return Ok(expressionString);}
+    // STRC already writes the whole address
+    if let (ExpressionOutLocation::Static(_), ExpressionOutLocation::Heap(_)) = (&start_loc, &dest) {
+        return moveOutTo(start_loc, dest);
+    }
+
+    let (start_lo, start_hi) = wordBytes(start_loc, width)?;
+    let (dest_lo, dest_hi) = wordBytes(dest, 2)?;
+    if start_lo != dest_lo {expressionString.push_str(&moveOutTo(start_lo, dest_lo)?);}
+    if start_hi != dest_hi {expressionString.push_str(&moveOutTo(start_hi, dest_hi)?);}
+    return Ok(expressionString);
+}
+
+// moveWordTo for a value that may be signed, a signed byte gets a high byte of 255 when bit 7 is set
+fn moveExtendedTo(
+    start_loc: ExpressionOutLocation,
+    dest: ExpressionOutLocation,
+    width: u8,
+    signed: bool
+) -> Result<String, CompilerError> {
+    if width == 2 || !signed {return moveWordTo(start_loc, dest, width);}
+    if let ExpressionOutLocation::Literal(ref l) = start_loc {
+        let v = parseLiteral(l).ok_or(CompilerError::InvalidAddress(l.to_string(), Span::default()))?;
+        return moveWordTo(ExpressionOutLocation::Literal(extendByte(v).to_string()), dest, 2);
+    }
+    let mut expressionString: String = Default::default();
+    let start_loc = narrow(start_loc);
+    if start_loc != ExpressionOutLocation::RegisterA {
+        expressionString.push_str(&moveOutTo(start_loc, ExpressionOutLocation::RegisterA)?);
+    }
+    expressionString.push_str("LDXC 0;\nCMPC 128;\nBCC 1;\nDEX;\n");
+    expressionString.push_str(&moveWordTo(ExpressionOutLocation::RegisterAX, dest, 2)?);
+    return Ok(expressionString);
+}
+
+// applies the mnemonic to both bytes, the carry goes from the low byte into the high byte
+// the result is left in AX
+fn applyWordOperand(
+    mnemonic: &str,
+    carry: &str,
+    lhs: (ExpressionOutLocation, ExpressionOutLocation),
+    rhs: (ExpressionOutLocation, ExpressionOutLocation),
+    span: Span
+) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    if lhs.0 != ExpressionOutLocation::RegisterA {
+        expressionString.push_str(&moveOutTo(lhs.0, ExpressionOutLocation::RegisterA)?);
+    }
+    expressionString.push_str(carry);
+    expressionString.push_str(&applyOperand(mnemonic, rhs.0, span)?);
+    expressionString.push_str("STA ");
+    expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+    expressionString.push_str(";\n");
+    expressionString.push_str(&moveOutTo(lhs.1, ExpressionOutLocation::RegisterA)?);
+    expressionString.push_str(&applyOperand(mnemonic, rhs.1, span)?);
+    expressionString.push_str("TAX;\nLDA ");
+    expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+    expressionString.push_str(";\n");
+    return Ok(expressionString);
+}
+
+// where a side of a 16-bit operation is read from and how many bytes it has there
+type WordOperand = (ExpressionOutLocation, u8);
+
+// 16-bit version of evaluateOperands, gives where both sides can be read from
+// lhs is either left where it lives or in AX, a signed byte on either side is sign extended
+fn evaluateWordOperands(
+    lhs: &ExprNode<'_>,
+    rhs: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, WordOperand, WordOperand, u8), CompilerError> {
+    let mut expressionString: String = Default::default();
+    let mut parked = false;
+    let mut pushed: u8 = 0;
+    let mut rhs_width = nodeWidth(rhs, program, current_pkg)?;
+    let rhs_signed = nodeSigned(rhs, program, current_pkg)?;
+    let operand = if isSimple(rhs, program, current_pkg) && (rhs_width == 2 || !rhs_signed) {
+        evaluateNode(rhs, program, current_pkg, state)?.1
+    } else if let (true, Some(v)) = (isSimple(rhs, program, current_pkg), constantValue(rhs)?) {
+        rhs_width = 2;
+        ExpressionOutLocation::Literal(extendByte(v).to_string())
+    } else if containsCall(lhs) {
+        let exprpkg = evaluateNode(rhs, program, current_pkg, state)?;
+        expressionString.push_str(&exprpkg.0);
+        expressionString.push_str(&moveExtendedTo(exprpkg.1, ExpressionOutLocation::RegisterAX, rhs_width, rhs_signed)?);
+        expressionString.push_str(&pushValue(2, state));
+        rhs_width = 2;
+        pushed = 2;
+        ExpressionOutLocation::Stack(1)
+    } else {
+        let exprpkg = evaluateNode(rhs, program, current_pkg, state)?;
+        expressionString.push_str(&exprpkg.0);
+        let temp = state.pushTemp();
+        expressionString.push_str(&moveExtendedTo(exprpkg.1, temp.clone(), rhs_width, rhs_signed)?);
+        rhs_width = 2;
+        parked = true;
+        temp
+    };
+
+    let mut lhs_width = nodeWidth(lhs, program, current_pkg)?;
+    let exprpkg = evaluateNode(lhs, program, current_pkg, state)?;
+    expressionString.push_str(&exprpkg.0);
+    let mut lhs_loc = match exprpkg.1 {
+        ExpressionOutLocation::Heap(_) |
+        ExpressionOutLocation::Stack(_) |
+        ExpressionOutLocation::Literal(_) |
+        ExpressionOutLocation::RegisterA |
+        ExpressionOutLocation::RegisterAX => exprpkg.1,
+        _ => {
+            expressionString.push_str(&moveOutTo(exprpkg.1, ExpressionOutLocation::RegisterA)?);
+            ExpressionOutLocation::RegisterA
+        }
+    };
+    if lhs_width == 1 && nodeSigned(lhs, program, current_pkg)? {
+        expressionString.push_str(&moveExtendedTo(lhs_loc, ExpressionOutLocation::RegisterAX, 1, true)?);
+        lhs_loc = ExpressionOutLocation::RegisterAX;
+        lhs_width = 2;
+    }
+
+    if parked {state.popTemp();}
+    return Ok((expressionString, (lhs_loc, lhs_width), (operand, rhs_width), pushed));
+}
+
+// sets the flags for a 16-bit compare, the low bytes are only compared when the high bytes match
+// signed, the low bytes are unsigned, both get 128 added so N and V after the SBC give their order
+fn evaluateWordCompare(
+    lhs: &ExprNode<'_>,
+    rhs: &ExprNode<'_>,
+    signed: bool,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<String, CompilerError> {
+    let (mut expressionString, lhs, rhs, pushed) = evaluateWordOperands(lhs, rhs, program, current_pkg, state)?;
+    let (mut lhs_lo, lhs_hi) = wordBytes(lhs.0, lhs.1)?;
+    let (rhs_lo, rhs_hi) = wordBytes(rhs.0, rhs.1)?;
+    let low_label = state.label(&program.procLabel(current_pkg.directory[0].index), "CMP");
+
+    // A is needed for the high byte
+    if lhs_lo == ExpressionOutLocation::RegisterA {
+        expressionString.push_str("STA ");
+        expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+        expressionString.push_str(";\n");
+        lhs_lo = ExpressionOutLocation::Heap(LOW_BYTE_ADDR);
+    }
+    expressionString.push_str(&moveOutTo(lhs_hi, ExpressionOutLocation::RegisterA)?);
+    if signed {
+        expressionString.push_str("SEC;\n");
+        expressionString.push_str(&applyOperand("SBC", rhs_hi, span)?);
+    } else {
+        expressionString.push_str(&applyOperand("CMP", rhs_hi, span)?);
+    }
+    expressionString.push_str("BEQ 3;\nJMPA ");
+    expressionString.push_str(&low_label);
+    expressionString.push_str(";\n");
+    if signed {
+        expressionString.push_str(&moveOutTo(rhs_lo, ExpressionOutLocation::RegisterA)?);
+        expressionString.push_str("CLC;\nADCC 128;\n");
+        expressionString.push_str(&moveOutTo(ExpressionOutLocation::RegisterA, ExpressionOutLocation::Heap(SCRATCH_ADDR))?);
+        expressionString.push_str(&moveOutTo(lhs_lo, ExpressionOutLocation::RegisterA)?);
+        expressionString.push_str("CLC;\nADCC 128;\nSEC;\n");
+        expressionString.push_str(&applyOperand("SBC", ExpressionOutLocation::Heap(SCRATCH_ADDR), span)?);
+    } else {
+        expressionString.push_str(&moveOutTo(lhs_lo, ExpressionOutLocation::RegisterA)?);
+        expressionString.push_str(&applyOperand("CMP", rhs_lo, span)?);
+    }
+    expressionString.push_str("LABEL ");
+    expressionString.push_str(&low_label);
+    expressionString.push('\n');
+    expressionString.push_str(&popValue(pushed, state));
+    return Ok(expressionString);
+}
+
+// 16-bit arithmetic, the result is left in AX
+fn evaluateWordBinary(
+    op: BinaryOp,
+    lhs: &ExprNode<'_>,
+    rhs: &ExprNode<'_>,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let signed = nodeSigned(lhs, program, current_pkg)?;
+    let (mut expressionString, lhs, rhs, pushed) = evaluateWordOperands(lhs, rhs, program, current_pkg, state)?;
+    let (mnemonic, carry) = match op {
+        BinaryOp::Add => ("ADC", "CLC;\n"),
+        BinaryOp::Subtract => ("SBC", "SEC;\n"),
+        BinaryOp::BitAnd => ("AND", ""),
+        BinaryOp::BitOr => ("ORA", ""),
+        BinaryOp::BitXor => ("XOR", ""),
+        _ => ("", ""),
+    };
+    if !mnemonic.is_empty() {
+        expressionString.push_str(&applyWordOperand(mnemonic, carry, wordBytes(lhs.0, lhs.1)?, wordBytes(rhs.0, rhs.1)?, span)?);
+        expressionString.push_str(&popValue(pushed, state));
+        return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+    }
+
+    // the rest are runtime helpers
+    let helper = match op {
+        BinaryOp::Multiply => Helper::Mul16,
+        BinaryOp::Divide |
+        BinaryOp::Modulo => Helper::Div16,
+        BinaryOp::ShiftLeft => Helper::Shl16,
+        BinaryOp::ShiftRight if signed => Helper::Sar16,
+        BinaryOp::ShiftRight => Helper::Shr16,
+        _ => return Err(CompilerError::UnidentifiedError(span)),
+    };
+    expressionString.push_str(&moveWordTo(rhs.0, ExpressionOutLocation::Heap(HELPER_ARG), rhs.1)?);
+    expressionString.push_str(&popValue(pushed, state));
+    expressionString.push_str(&moveWordTo(lhs.0, ExpressionOutLocation::RegisterAX, lhs.1)?);
+    expressionString.push_str("JSR ");
+    expressionString.push_str(state.useHelper(helper));
+    expressionString.push_str(";\n");
+    if op == BinaryOp::Modulo {
+        expressionString.push_str(&moveWordTo(ExpressionOutLocation::Heap(DIV16_REMAINDER), ExpressionOutLocation::RegisterAX, 2)?);
+    }
+    return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+}
+
+// leaves lhs in A and gives where rhs can be read from
+// the rhs has to be used before anything else is evaluated
+// when the lhs makes a call the rhs waits on the stack, the bytes pushed have to be popped after it is used
+fn evaluateOperands(
+    lhs: &ExprNode<'_>,
+    rhs: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation, u8), CompilerError> {
+    let mut expressionString: String = Default::default();
+    let mut parked = false;
+    let mut pushed: u8 = 0;
+    let operand = if isSimple(rhs, program, current_pkg) {
+        narrow(evaluateNode(rhs, program, current_pkg, state)?.1)
+    } else if containsCall(lhs) {
+        // the callee can use the temps
+        let exprpkg = evaluateNode(rhs, program, current_pkg, state)?;
+        expressionString.push_str(&exprpkg.0);
+        let expressionOutput = narrow(exprpkg.1);
+        if expressionOutput != ExpressionOutLocation::RegisterA {
+            expressionString.push_str(&moveOutTo(expressionOutput, ExpressionOutLocation::RegisterA)?);
+        }
+        expressionString.push_str(&pushValue(1, state));
+        pushed = 1;
+        ExpressionOutLocation::Stack(1)
+    } else {
+        // work the rhs out first and park it while the lhs is evaluated
+        let exprpkg = evaluateNode(rhs, program, current_pkg, state)?;
+        expressionString.push_str(&exprpkg.0);
+        let temp = state.pushTemp();
+        expressionString.push_str(&moveOutTo(exprpkg.1, temp.clone())?);
+        parked = true;
+        temp
+    };
+
+    let exprpkg = evaluateNode(lhs, program, current_pkg, state)?;
+    expressionString.push_str(&exprpkg.0);
+    let lhs_loc = narrow(exprpkg.1);
+    if lhs_loc != ExpressionOutLocation::RegisterA {
+        expressionString.push_str(&moveOutTo(lhs_loc, ExpressionOutLocation::RegisterA)?);
+    }
+
+    if parked {state.popTemp();}
+    return Ok((expressionString, operand, pushed));
+}
+
+// sets the flags for a node used as a condition
+fn evaluateCondition(
+    node: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ConditionType), CompilerError> {
+    match node {
+        ExprNode::Binary(op, lhs, rhs, span) if op.isComparison() => {
+            let width = nodeWidth(lhs, program, current_pkg)?.max(nodeWidth(rhs, program, current_pkg)?);
+            let signed = nodeSigned(lhs, program, current_pkg)? || nodeSigned(rhs, program, current_pkg)?;
+            let expressionString = if width == 2 {
+                evaluateWordCompare(lhs, rhs, signed, *span, program, current_pkg, state)?
+            } else {
+                let (mut expressionString, operand, pushed) = evaluateOperands(lhs, rhs, program, current_pkg, state)?;
+                // CMP leaves V alone, a signed compare needs the overflow of the subtraction
+                if signed {
+                    expressionString.push_str("SEC;\n");
+                    expressionString.push_str(&applyOperand("SBC", operand, *span)?);
+                } else {
+                    expressionString.push_str(&applyOperand("CMP", operand, *span)?);
+                }
+                expressionString.push_str(&popValue(pushed, state));
+                expressionString
+            };
+            let conditionType = match (op, signed) {
+                (BinaryOp::Eq, _) => ConditionType::Eq,
+                (BinaryOp::NEq, _) => ConditionType::NEq,
+                (BinaryOp::Less, false) => ConditionType::Lesser,
+                (BinaryOp::Great, false) => ConditionType::Greater,
+                (BinaryOp::LessEq, false) => ConditionType::EqLesser,
+                (BinaryOp::GreatEq, false) => ConditionType::EqGreater,
+                (BinaryOp::Less, true) => ConditionType::SignedLesser,
+                (BinaryOp::Great, true) => ConditionType::SignedGreater,
+                (BinaryOp::LessEq, true) => ConditionType::SignedEqLesser,
+                (BinaryOp::GreatEq, true) => ConditionType::SignedEqGreater,
+                _ => return Err(CompilerError::UnidentifiedError(*span))
+            };
+            return Ok((expressionString, conditionType));
+        }
+        // anything else is true when not 0
+        _ => {
+            let (mut expressionString, expressionOutput) = evaluateNode(node, program, current_pkg, state)?;
+            if nodeWidth(node, program, current_pkg)? == 2 {
+                expressionString.push_str(&moveWordTo(expressionOutput, ExpressionOutLocation::RegisterAX, 2)?);
+                expressionString.push_str("STA ");
+                expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                expressionString.push_str(";\nTXA;\nORA ");
+                expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                expressionString.push_str(";\nCMPC 0;\n");
+                return Ok((expressionString, ConditionType::NEq));
+            }
+            let expressionOutput = narrow(expressionOutput);
+            if expressionOutput != ExpressionOutLocation::RegisterA {
+                expressionString.push_str(&moveOutTo(expressionOutput, ExpressionOutLocation::RegisterA)?);
+            }
+            expressionString.push_str("CMPC 0;\n");
+            return Ok((expressionString, ConditionType::NEq));
+        }
+    }
+}
+
+// jumps to the target when the condition is jump_when, falls through otherwise
+// && and || short circuit, the side that decides it jumps straight out
+fn evaluateBranch(
+    node: &ExprNode<'_>,
+    jump_when: bool,
+    target: &str,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    match node {
+        ExprNode::Unary(UnaryOp::Not, operand, _) => {
+            return evaluateBranch(operand, !jump_when, target, program, current_pkg, state);
+        }
+        ExprNode::Binary(op, lhs, rhs, _) if op.isLogical() => {
+            // the lhs alone decides it when it is false for && and true for ||
+            let decides = *op == BinaryOp::LogicalOr;
+            if decides == jump_when {
+                expressionString.push_str(&evaluateBranch(lhs, jump_when, target, program, current_pkg, state)?);
+                expressionString.push_str(&evaluateBranch(rhs, jump_when, target, program, current_pkg, state)?);
+                return Ok(expressionString);
+            }
+            // decided the other way, skip the rhs
+            let kind = if *op == BinaryOp::LogicalAnd {"AND"} else {"OR"};
+            let skip_label = state.label(&program.procLabel(current_pkg.directory[0].index), kind);
+            expressionString.push_str(&evaluateBranch(lhs, decides, &skip_label, program, current_pkg, state)?);
+            expressionString.push_str(&evaluateBranch(rhs, jump_when, target, program, current_pkg, state)?);
+            expressionString.push_str("LABEL ");
+            expressionString.push_str(&skip_label);
+            expressionString.push('\n');
+            return Ok(expressionString);
+        }
+        _ => {
+            let (code, conditionType) = evaluateCondition(node, program, current_pkg, state)?;
+            expressionString.push_str(&code);
+            if jump_when {
+                expressionString.push_str(skipWhenFalse(conditionType));
+            } else {
+                expressionString.push_str(skipWhenTrue(conditionType));
+            }
+            expressionString.push_str("JMPA ");
+            expressionString.push_str(target);
+            expressionString.push_str(";\n");
+            return Ok(expressionString);
+        }
+    }
+}
+
+// the condition of a block, a condition that failed to parse was already reported
+fn evaluateConditionJump(
+    expr: &Expression<'_>,
+    jump_when: bool,
+    target: &str,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<String, CompilerError> {
+    match expr.tree {
+        Some(ref node) => return evaluateBranch(node, jump_when, target, program, current_pkg, state),
+        None if !expr.tks.is_empty() => return Ok(Default::default()),
+        None => return Err(CompilerError::EncounteredBlankExpression(expr.span())),
+    }
+}
+
+// a condition used as a value, A gets 1 when it holds and 0 otherwise
+fn evaluateConditionValue(
+    node: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let false_label = state.label(&program.procLabel(current_pkg.directory[0].index), "EXPR");
+    let mut expressionString = evaluateBranch(node, false, &false_label, program, current_pkg, state)?;
+
+    expressionString.push_str("LDAC 1;\nJMPA ");
+    expressionString.push_str(&false_label);
+    expressionString.push_str("_END;\nLABEL ");
+    expressionString.push_str(&false_label);
+    expressionString.push_str("\nLDAC 0;\nLABEL ");
+    expressionString.push_str(&false_label);
+    expressionString.push_str("_END\n");
+    return Ok((expressionString, ExpressionOutLocation::RegisterA));
+}
+
+// only values passed in registers are supported, the types were matched by the checker
+fn checkArgument(
+    param: &Variable<'_>,
+    arg: &ExprNode<'_>,
+    proc_label: &str
+) -> Result<(), CompilerError> {
+    match param.t.v {
+        DataValueType::Uint |
+        DataValueType::Int |
+        DataValueType::Char |
+        DataValueType::Short |
+        DataValueType::SShort |
+        DataValueType::Pointer(_) |
+        DataValueType::Enum(..) => return Ok(()),
+        _ => return Err(CompilerError::UnsupportedParameter(proc_label.to_string(), param.t.v, arg.span())),
+    }
+}
+
+// leaves an argument in A, or AX when it is 2 bytes wide
+fn evaluateArgument(
+    arg: &ExprNode<'_>,
+    width: u8,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<String, CompilerError> {
+    let (mut expressionString, expressionOutput) = evaluateNode(arg, program, current_pkg, state)?;
+    if width == 2 {
+        let arg_width = nodeWidth(arg, program, current_pkg)?;
+        let signed = nodeSigned(arg, program, current_pkg)?;
+        expressionString.push_str(&moveExtendedTo(expressionOutput, ExpressionOutLocation::RegisterAX, arg_width, signed)?);
+        return Ok(expressionString);
+    }
+    let expressionOutput = narrow(expressionOutput);
+    if expressionOutput != ExpressionOutLocation::RegisterA {
+        expressionString.push_str(&moveOutTo(expressionOutput, ExpressionOutLocation::RegisterA)?);
+    }
+    return Ok(expressionString);
+}
+
+// takes values pushed with pushValue back off
+fn popValue(width: u8, state: &mut CompilerState) -> String {
+    let mut expressionString: String = Default::default();
+    if width == 0 {return expressionString;}
+    expressionString.push_str("DAL ");
+    expressionString.push_str(&width.to_string());
+    expressionString.push_str(";\n");
+    state.frame_offset -= width;
+    return expressionString;
+}
+
+// grows the stack by the width and stores A (and X) on top of it
+fn pushValue(width: u8, state: &mut CompilerState) -> String {
+    let mut expressionString: String = Default::default();
+    expressionString.push_str("SAL ");
+    expressionString.push_str(&width.to_string());
+    expressionString.push_str(";\nSTAS 1;\n");
+    if width == 2 {expressionString.push_str("STXS 2;\n");}
+    state.frame_offset += width;
+    return expressionString;
+}
+
+fn evaluateCall(
+    tk: &Token<'_>,
+    args: &[ExprNode<'_>],
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<String, CompilerError> {
+    let mut expressionString: String = Default::default();
+    match tk.tk_type {
+        TokenType::EmbeddedFunction => {
+            match tk.tk_data {
+                "store" => {
+                    if args.len() != 2 {
+                        return Err(CompilerError::InvalidArgCount(args.len(), tk.tk_data.to_string(), span));
+                    }
+                    let exprpkg = evaluateNode(&args[0], program, current_pkg, state)?;
+                    expressionString.push_str(&exprpkg.0);
+                    match &args[1] {
+                        ExprNode::Literal(l) => {
+                            expressionString.push_str(
+                                &moveOutTo(narrow(exprpkg.1), ExpressionOutLocation::Heap(parseAddress(l)?))?
+                            );
+                        }
+                        _ => {
+                            let addr = evaluateNode(&args[1], program, current_pkg, state)?.1;
+                            return Err(CompilerError::InvalidStore(exprpkg.1, addr, span));
+                        }
+                    }
+                }
+                "sys" => {
+                    // no returns
+                    if args.is_empty() {
+                        return Err(CompilerError::SysArgEnforce(args.len(), span));
+                    }
+                    let code = match &args[0] {
+                        ExprNode::Literal(l) if l.tk_type != TokenType::StringLiteral => literalText(l),
+                        _ => {
+                            let loc = evaluateNode(&args[0], program, current_pkg, state)?.1;
+                            return Err(CompilerError::SysArg1LiteralEnforce(loc, args[0].span()));
+                        }
+                    };
+                    // everything else goes through the IO buffer
+                    for arg in &args[1..] {
+                        let exprpkg = evaluateNode(arg, program, current_pkg, state)?;
+                        expressionString.push_str(&exprpkg.0);
+                        expressionString.push_str(
+                            &moveOutTo(narrow(exprpkg.1), ExpressionOutLocation::Heap(0xFFFE))?
+                        );
+                    }
+                    expressionString.push_str("SYS ");
+                    expressionString.push_str(&code);
+                    expressionString.push_str(";\n");
+                }
+                "exit" => {
+                    // no returns
+                    if args.len() != 1 {
+                        return Err(CompilerError::ExitArgEnforce(args.len(), span));
+                    }
+                    let exprpkg = evaluateNode(&args[0], program, current_pkg, state)?;
+                    expressionString.push_str(&exprpkg.0);
+                    if narrow(exprpkg.1.clone()) != ExpressionOutLocation::RegisterA {
+                        expressionString.push_str(&moveOutTo(narrow(exprpkg.1), ExpressionOutLocation::RegisterA)?);
+                    }
+                    expressionString.push_str("BRK;\n");
+                }
+                &_ => return Err(CompilerError::UnimplementedEmbeddedFunction(tk.tk_data.to_string(), tk.span))
+            }
+        }
+        TokenType::ProcedureCall => {
+            let called_index = tk.tk_comp_data.proc()
+                .ok_or(CompilerError::InvalidProcCall(tk.tk_data.to_string(), tk.span))?;
+            let called_proc = &program.procs[called_index];
+            for (arg, param) in args.iter().zip(&called_proc.arguments) {
+                checkArgument(param, arg, called_proc.label)?;
+            }
+
+            // stack arguments go first and in order, the last one ends up next to the return address
+            let mut pushed: u8 = 0;
+            for (i, arg) in args.iter().enumerate() {
+                let v = called_proc.arguments[i].t.v;
+                if argInRegister(i, v) {continue;}
+                let width = v.size().unwrap_or(1);
+                expressionString.push_str(&evaluateArgument(arg, width, program, current_pkg, state)?);
+                expressionString.push_str(&pushValue(width, state));
+                pushed += width;
+            }
+
+            // evaluating the first argument can use Y so the second waits on the stack
+            let y_arg = args.len() > 1 && argInRegister(1, called_proc.arguments[1].t.v);
+            if y_arg {
+                expressionString.push_str(&evaluateArgument(&args[1], 1, program, current_pkg, state)?);
+                expressionString.push_str(&pushValue(1, state));
+            }
+            if !args.is_empty() {
+                let width = called_proc.arguments[0].t.v.size().unwrap_or(1);
+                expressionString.push_str(&evaluateArgument(&args[0], width, program, current_pkg, state)?);
+            }
+            if y_arg {
+                expressionString.push_str("LDYS 1;\nDAL 1;\n");
+                state.frame_offset -= 1;
+            }
+
+            // push jsr
+            expressionString.push_str("JSR ");
+            expressionString.push_str(&program.procLabel(called_index));
+            expressionString.push_str(";\n");
+
+            // the caller takes its stack arguments back off
+            if pushed != 0 {
+                expressionString.push_str("DAL ");
+                expressionString.push_str(&pushed.to_string());
+                expressionString.push_str(";\n");
+                state.frame_offset -= pushed;
+            }
+        }
+        _ => return Err(CompilerError::InvalidProcCall(tk.tk_data.to_string(), span))
+    }
+    return Ok(expressionString);
+}
+
+// where the target of an assignment is written to
+fn assignTarget(
+    target: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &CompilerState
+) -> Result<ExpressionOutLocation, CompilerError> {
+    let dest = match target {
+        ExprNode::Variable(tk) => {
+            let var = grabVariableComp!(tk, program, current_pkg)?;
+            match var.t.a {
+                DataAllocationType::Stack(addr) => {
+                    // buffers and arrays are only assigned through an index
+                    if matches!(var.t.v, DataValueType::Buffer | DataValueType::Array(_)) {
+                        return Err(CompilerError::InvalidBufferIndexing(tk.span));
+                    }
+                    state.stack(addr)
+                }
+                DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr),
+                DataAllocationType::Static => return Err(CompilerError::ReadOnlyStatic(var.label.to_string(), tk.span)),
+                _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a, tk.span)),
+            }
+        }
+        ExprNode::Index(base, index, span) => {
+            indexLocation(base, index, *span, program, current_pkg, state)?
+                .ok_or(CompilerError::InvalidBufferIndexing(*span))?
+        }
+        ExprNode::Field(..) => {
+            match fieldLocation(target, program, current_pkg, state)? {
+                ExpressionOutLocation::Rom(label, _) => return Err(CompilerError::ReadOnlyStatic(label, target.span())),
+                loc => loc,
+            }
+        }
+        ExprNode::Register(tk) => {
+            ExpressionOutLocation::reg(tk.tk_data)
+                .ok_or(CompilerError::UnimplementedReg(tk.tk_data.to_string(), tk.span))?
+        }
+        _ => return Err(CompilerError::InvalidAssignmentTarget(target.span()))
+    };
+    return Ok(dest);
+}
+
+// both nodes name the same variable or register
+fn sameTarget(a: &ExprNode<'_>, b: &ExprNode<'_>) -> bool {
+    match (a, b) {
+        (ExprNode::Variable(x), ExprNode::Variable(y)) => return x.tk_comp_data == y.tk_comp_data,
+        (ExprNode::Register(x), ExprNode::Register(y)) => return x.tk_data == y.tk_data,
+        (ExprNode::Field(x, xf, _), ExprNode::Field(y, yf, _)) => return xf.tk_data == yf.tk_data && sameTarget(x, y),
+        _ => return false,
+    }
+}
+
+// an index whose element has no fixed location, it is written through Y
+fn runtimeIndex(
+    target: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &CompilerState
+) -> Result<bool, CompilerError> {
+    match target {
+        ExprNode::Index(base, index, span) => return Ok(indexLocation(base, index, *span, program, current_pkg, state)?.is_none()),
+        _ => return Ok(false),
+    }
+}
+
+// x = x + 1 and x = x - 1 (x += 1, x++ ...), gives if it steps up
+fn stepOf(target: &ExprNode<'_>, value: &ExprNode<'_>) -> Result<Option<bool>, CompilerError> {
+    match value {
+        ExprNode::Binary(BinaryOp::Add, a, b, _) if sameTarget(target, a) && constantValue(b)? == Some(1) => return Ok(Some(true)),
+        ExprNode::Binary(BinaryOp::Add, a, b, _) if sameTarget(target, b) && constantValue(a)? == Some(1) => return Ok(Some(true)),
+        ExprNode::Binary(BinaryOp::Subtract, a, b, _) if sameTarget(target, a) && constantValue(b)? == Some(1) => return Ok(Some(false)),
+        _ => return Ok(None),
+    }
+}
+
+// steps a 1 byte location by one where it lives
+// INC and DEC only have the 2 byte address form so the address is always written as 4 hex digits
+fn stepInPlace(dest: &ExpressionOutLocation, width: u8, up: bool) -> Option<String> {
+    if width != 1 {return None;}
+    let mut expressionString: String = Default::default();
+    match dest {
+        ExpressionOutLocation::RegisterX => expressionString.push_str(if up {"INX;\n"} else {"DEX;\n"}),
+        ExpressionOutLocation::RegisterY => expressionString.push_str(if up {"INY;\n"} else {"DEY;\n"}),
+        ExpressionOutLocation::RegisterA => expressionString.push_str(if up {"CLC;\nADCC 1;\n"} else {"SEC;\nSBCC 1;\n"}),
+        ExpressionOutLocation::Heap(addr) => {
+            expressionString.push_str(if up {"INC "} else {"DEC "});
+            expressionString.push_str(&format!("0x{:04x}", addr));
+            expressionString.push_str(";\n");
+        }
+        // no INC for the stack, Y is free to step it in
+        ExpressionOutLocation::Stack(addr) => {
+            expressionString.push_str("LDYS ");
+            expressionString.push_str(&addr.to_string());
+            expressionString.push_str(if up {";\nINY;\nSTYS "} else {";\nDEY;\nSTYS "});
+            expressionString.push_str(&addr.to_string());
+            expressionString.push_str(";\n");
+        }
+        _ => return None,
+    }
+    return Some(expressionString);
+}
+
+// x++ and x--, a short is worked out like x = x + 1
+fn evaluateStep(
+    target: &ExprNode<'_>,
+    up: bool,
+    span: Span,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let dest = match runtimeIndex(target, program, current_pkg, state)? || throughPointer(target, program, current_pkg)?.is_some() {
+        true => ExpressionOutLocation::None,
+        false => assignTarget(target, program, current_pkg, state)?,
+    };
+    if let Some(code) = stepInPlace(&dest, nodeWidth(target, program, current_pkg)?, up) {
+        return Ok((code, dest));
+    }
+    let one = Token {tk_data: "1", tk_type: TokenType::NumberLiteral, tk_comp_data: TokenCompData::None, span};
+    let op = if up {BinaryOp::Add} else {BinaryOp::Subtract};
+    let value = ExprNode::Binary(op, Box::new(target.clone()), Box::new(ExprNode::Literal(&one)), span);
+    return evaluateAssign(target, &value, program, current_pkg, state);
+}
+
+fn evaluateAssign(
+    target: &ExprNode<'_>,
+    value: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    if let (ExprNode::Index(base, index, span), true) = (target, runtimeIndex(target, program, current_pkg, state)?) {
+        return evaluateIndexedAssign(base, index, value, *span, program, current_pkg, state);
+    }
+    if let Some(pointed) = throughPointer(target, program, current_pkg)? {
+        return evaluatePointerAssign(pointed, value, target.span(), program, current_pkg, state);
+    }
+    let dest = assignTarget(target, program, current_pkg, state)?;
+    // a whole struct is copied a byte at a time through A
+    if let DataValueType::Struct(i) = structType(target, program, current_pkg)? {
+        let mut expressionString: String = Default::default();
+        if throughPointer(value, program, current_pkg)?.is_some() {return Err(CompilerError::StructThroughPointer(value.span()));}
+        let src = fieldLocation(value, program, current_pkg, state)?;
+        for n in 0..program.structs[i].size {
+            expressionString.push_str(&moveOutTo(byteAt(&src, n), ExpressionOutLocation::RegisterA)?);
+            expressionString.push_str(&moveOutTo(ExpressionOutLocation::RegisterA, byteAt(&dest, n))?);
+        }
+        return Ok((expressionString, dest));
+    }
+    if let Some(up) = stepOf(target, value)? {
+        if let Some(code) = stepInPlace(&dest, nodeWidth(target, program, current_pkg)?, up) {
+            return Ok((code, dest));
+        }
+    }
+
+    let (mut expressionString, expressionOutput) = evaluateNode(value, program, current_pkg, state)?;
+    if nodeWidth(target, program, current_pkg)? == 2 {
+        let width = nodeWidth(value, program, current_pkg)?;
+        let signed = nodeSigned(value, program, current_pkg)?;
+        expressionString.push_str(&moveExtendedTo(expressionOutput, dest.clone(), width, signed)?);
+        return Ok((expressionString, dest));
+    }
+    let expressionOutput = narrow(expressionOutput);
+    if expressionOutput != dest {
+        expressionString.push_str(&moveOutTo(expressionOutput, dest.clone())?);
+    }
+    return Ok((expressionString, dest));
+}
+
+// lowers a node, returning the code and where the result was left
+fn evaluateNode(
+    node: &ExprNode<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let mut expressionString: String = Default::default();
+    let expressionOutput: ExpressionOutLocation;
+    match node {
+        ExprNode::Literal(tk) => {
+            expressionOutput = literalLocation(tk);
+        }
+        ExprNode::Variable(tk) => {
+            let var = grabVariableComp!(tk, program, current_pkg)?;
+            expressionOutput = match var.t.a{
+                DataAllocationType::Stack(addr) => state.stack(addr),
+                DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr),
+                DataAllocationType::Static => ExpressionOutLocation::Static(program.staticLabel(var.label, var.module)),
+                // the parser puts the value in for the name, this is only reached when it has none
+                DataAllocationType::Const => ExpressionOutLocation::Literal((var.value.unwrap_or(0) as u16).to_string()),
+                _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a, tk.span)),
+            };
+        }
+        ExprNode::Register(tk) => {
+            expressionOutput = ExpressionOutLocation::reg(tk.tk_data)
+                .ok_or(CompilerError::UnimplementedReg(tk.tk_data.to_string(), tk.span))?;
+        }
+        // using a procedure as a label
+        ExprNode::Label(tk) => {
+            expressionOutput = ExpressionOutLocation::Static(tk.tk_data.to_string());
+        }
+        ExprNode::Index(base, index, span) => {
+            match indexLocation(base, index, *span, program, current_pkg, state)? {
+                Some(loc) => expressionOutput = loc,
+                None => {
+                    let (var, element) = indexedArray(base, *span, program, current_pkg)?;
+                    expressionString.push_str(&indexOffset(index, element, program, current_pkg, state)?);
+                    expressionString.push_str(&indexedAccess(&var, program, element, false, *span, state)?);
+                    if element.isWord() {return Ok((expressionString, ExpressionOutLocation::RegisterAX));}
+                    return Ok((expressionString, ExpressionOutLocation::RegisterA));
+                }
+            }
+        }
+        ExprNode::Field(..) => {
+            if let Some((ptr, offset, element)) = throughPointer(node, program, current_pkg)? {
+                return evaluatePointerRead(ptr, offset, element, node.span(), program, current_pkg, state);
+            }
+            expressionOutput = match fieldLocation(node, program, current_pkg, state)? {
+                // loaded right away, nothing else reads the ROM
+                loc @ ExpressionOutLocation::Rom(..) => {
+                    if nodeWidth(node, program, current_pkg)? == 2 {
+                        expressionString.push_str(&moveWordTo(loc, ExpressionOutLocation::RegisterAX, 2)?);
+                        ExpressionOutLocation::RegisterAX
+                    } else {
+                        expressionString.push_str(&moveOutTo(loc, ExpressionOutLocation::RegisterA)?);
+                        ExpressionOutLocation::RegisterA
+                    }
+                }
+                loc => loc,
+            };
+        }
+        ExprNode::Unary(op, operand, _) => {
+            match op {
+                UnaryOp::AddressOf => return evaluateAddressOf(operand, node.span(), program, current_pkg, state),
+                UnaryOp::Deref => {
+                    let (ptr, offset, element) = throughPointer(node, program, current_pkg)?
+                        .ok_or(CompilerError::UnidentifiedError(node.span()))?;
+                    return evaluatePointerRead(ptr, offset, element, node.span(), program, current_pkg, state);
+                }
+                UnaryOp::Negate => {
+                    if let Some(v) = constantValue(node)? {
+                        return Ok((expressionString, ExpressionOutLocation::Literal(v.to_string())));
+                    }
+                    let exprpkg = evaluateNode(operand, program, current_pkg, state)?;
+                    expressionString.push_str(&exprpkg.0);
+                    if nodeWidth(operand, program, current_pkg)? == 2 {
+                        expressionString.push_str(&moveWordTo(exprpkg.1, ExpressionOutLocation::RegisterAX, 2)?);
+                        // two's complement, the carry of the +1 goes into the high byte
+                        expressionString.push_str("XORC 255;\nCLC;\nADCC 1;\nSTA ");
+                        expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                        expressionString.push_str(";\nTXA;\nXORC 255;\nADCC 0;\nTAX;\nLDA ");
+                        expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                        expressionString.push_str(";\n");
+                        return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+                    }
+                    if exprpkg.1 != ExpressionOutLocation::RegisterA {
+                        expressionString.push_str(&moveOutTo(exprpkg.1, ExpressionOutLocation::RegisterA)?);
+                    }
+                    // two's complement
+                    expressionString.push_str("XORC 255;\nCLC;\nADCC 1;\n");
+                    expressionOutput = ExpressionOutLocation::RegisterA;
+                }
+                UnaryOp::Not => return evaluateConditionValue(node, program, current_pkg, state),
+                UnaryOp::Increment => return evaluateStep(operand, true, node.span(), program, current_pkg, state),
+                UnaryOp::Decrement => return evaluateStep(operand, false, node.span(), program, current_pkg, state),
+                // not folded, ~ of a literal depends on how wide it ends up
+                UnaryOp::BitNot => {
+                    let exprpkg = evaluateNode(operand, program, current_pkg, state)?;
+                    expressionString.push_str(&exprpkg.0);
+                    if nodeWidth(operand, program, current_pkg)? == 2 {
+                        expressionString.push_str(&moveWordTo(exprpkg.1, ExpressionOutLocation::RegisterAX, 2)?);
+                        expressionString.push_str("XORC 255;\nSTA ");
+                        expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                        expressionString.push_str(";\nTXA;\nXORC 255;\nTAX;\nLDA ");
+                        expressionString.push_str(&LOW_BYTE_ADDR.to_string());
+                        expressionString.push_str(";\n");
+                        return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+                    }
+                    if exprpkg.1 != ExpressionOutLocation::RegisterA {
+                        expressionString.push_str(&moveOutTo(exprpkg.1, ExpressionOutLocation::RegisterA)?);
+                    }
+                    expressionString.push_str("XORC 255;\n");
+                    expressionOutput = ExpressionOutLocation::RegisterA;
+                }
+            }
+        }
+        ExprNode::Binary(op, lhs, rhs, span) => {
+            if op.isComparison() || op.isLogical() {return evaluateConditionValue(node, program, current_pkg, state);}
+            if let Some(v) = constantValue(node)? {
+                return Ok((expressionString, ExpressionOutLocation::Literal(v.to_string())));
+            }
+            // p + n moves n elements along, n is scaled by the size of what p points at
+            if matches!(op, BinaryOp::Add | BinaryOp::Subtract) {
+                let (ptr_lhs, element) = match (pointee(lhs, program, current_pkg)?, pointee(rhs, program, current_pkg)?) {
+                    (Some(v), None) => (true, v),
+                    (None, Some(v)) if *op == BinaryOp::Add => (false, v),
+                    _ => (true, DataValueType::Uint),
+                };
+                let size = program.sizeOf(element).unwrap_or(1);
+                if size > 1 {
+                    let count = if ptr_lhs {rhs} else {lhs};
+                    let scaled_text = match constantValue(count)? {
+                        Some(c) => c.wrapping_mul(size as u16).to_string(),
+                        None => size.to_string(),
+                    };
+                    let scaled_tk = Token {tk_data: &scaled_text, tk_type: TokenType::NumberLiteral, tk_comp_data: TokenCompData::None, span: *span};
+                    let scaled = match constantValue(count)? {
+                        Some(_) => ExprNode::Literal(&scaled_tk),
+                        None => {
+                            let wide = ExprNode::Cast(DataValueType::Short, count.clone(), count.span());
+                            ExprNode::Binary(BinaryOp::Multiply, Box::new(wide), Box::new(ExprNode::Literal(&scaled_tk)), *span)
                         }
-                        else {
-                            contents.push_str("RTS;\n");
+                    };
+                    if ptr_lhs {return evaluateWordBinary(*op, lhs, &scaled, *span, program, current_pkg, state);}
+                    return evaluateWordBinary(*op, &scaled, rhs, *span, program, current_pkg, state);
+                }
+            }
+            if nodeWidth(node, program, current_pkg)? == 2 {
+                return evaluateWordBinary(*op, lhs, rhs, *span, program, current_pkg, state);
+            }
+
+            let (code, operand, pushed) = evaluateOperands(lhs, rhs, program, current_pkg, state)?;
+            expressionString.push_str(&code);
+            match op {
+                BinaryOp::Add => {
+                    expressionString.push_str("CLC;\n");
+                    expressionString.push_str(&applyOperand("ADC", operand, *span)?);
+                }
+                BinaryOp::Subtract => {
+                    expressionString.push_str("SEC;\n");
+                    expressionString.push_str(&applyOperand("SBC", operand, *span)?);
+                }
+                BinaryOp::BitAnd => expressionString.push_str(&applyOperand("AND", operand, *span)?),
+                BinaryOp::BitOr => expressionString.push_str(&applyOperand("ORA", operand, *span)?),
+                BinaryOp::BitXor => expressionString.push_str(&applyOperand("XOR", operand, *span)?),
+                // a known shift left is A added to itself that many times
+                BinaryOp::ShiftLeft if matches!(operand, ExpressionOutLocation::Literal(_)) => {
+                    let count = match constantValue(rhs)? {
+                        Some(v) => v,
+                        None => return Err(CompilerError::UnidentifiedError(*span)),
+                    };
+                    if count >= 8 {
+                        expressionString.push_str("LDAC 0;\n");
+                    } else {
+                        for _ in 0..count {
+                            expressionString.push_str("STA 0x00;\nCLC;\nADC 0x00;\n");
                         }
                     }
-                    ExpressionType::Unspecified |
-                    ExpressionType::Assignment => {
-                        contents.push_str(
-                            evaluateExpr(
-                                p.expressions[p.lines[index].index].clone(),
-                                program,
-                                &p
-                            )?.0.as_str()
-                        );
+                }
+                BinaryOp::ShiftLeft |
+                BinaryOp::ShiftRight |
+                BinaryOp::Multiply |
+                BinaryOp::Divide |
+                BinaryOp::Modulo => {
+                    // no opcode for these, call the runtime helper
+                    let helper = match op {
+                        BinaryOp::Multiply => Helper::Mul8,
+                        BinaryOp::ShiftLeft => Helper::Shl8,
+                        BinaryOp::ShiftRight if nodeSigned(lhs, program, current_pkg)? => Helper::Sar8,
+                        BinaryOp::ShiftRight => Helper::Shr8,
+                        _ => Helper::Div8,
+                    };
+                    expressionString.push_str(&moveOutTo(operand, ExpressionOutLocation::Heap(HELPER_ARG))?);
+                    expressionString.push_str("JSR ");
+                    expressionString.push_str(state.useHelper(helper));
+                    expressionString.push_str(";\n");
+                    if *op == BinaryOp::Modulo {
+                        expressionString.push_str(&moveOutTo(ExpressionOutLocation::Heap(DIV_REMAINDER), ExpressionOutLocation::RegisterA)?);
                     }
-                    _ => return Err(CompilerError::UnimplementedExprType(p.expressions[p.lines[index].index].t))
                 }
+                _ => return Err(CompilerError::UnidentifiedError(*span))
             }
-            LineType::Block => {
-                //grab info
-                let mut directory = p.directory.clone();
-                let index = p.lines[index].index;
-                directory.push(BlockParent {
-                    index: index,
-                    t: BlockParentType::Block
-                });
-                let block = program.getBlock(&directory);
-
-                //place condition and TODO: allocate var space & displace variable queries
-                let mut escape_label: String = Default::default();
-                match block.block_type {
-                    BlockType::Else => {
-                        p.else_block_dir = Some(directory);
-                        return Ok(());
-                    }
-                    BlockType::If => {
-                        escape_label.push_str(program.procs[directory[0].index].label);
-                        escape_label.push_str("_IF");
-                        escape_label.push_str(&label_discriminant.to_string());
+            expressionString.push_str(&popValue(pushed, state));
+            expressionOutput = ExpressionOutLocation::RegisterA;
+        }
+        // used as a value, calls on their own line go straight to evaluateCall
+        ExprNode::Call(tk, args, span) => {
+            let ret = returnType(tk, *span, program)?;
+            expressionString.push_str(&evaluateCall(tk, args, *span, program, current_pkg, state)?);
+            if ret.isWord() {
+                expressionOutput = ExpressionOutLocation::RegisterAX;
+            }
+            else {
+                expressionOutput = ExpressionOutLocation::RegisterA;
+            }
+        }
+        ExprNode::Assign(target, value, _) => return evaluateAssign(target, value, program, current_pkg, state),
+        ExprNode::List(_, span) => return Err(CompilerError::ListOutsideStatic(*span)),
+        // a byte is zero or sign extended to 2, going down to 1 byte keeps the low byte
+        ExprNode::Cast(t, operand, span) => {
+            let width = nodeWidth(operand, program, current_pkg)?;
+            let signed = nodeSigned(operand, program, current_pkg)?;
+            let exprpkg = evaluateNode(operand, program, current_pkg, state)?;
+            expressionString.push_str(&exprpkg.0);
+            if t.size() == Some(1) {
+                expressionOutput = narrow(exprpkg.1);
+            }
+            else if width == 2 {
+                expressionOutput = exprpkg.1;
+            }
+            else if let ExpressionOutLocation::Literal(ref l) = exprpkg.1 {
+                let v = parseLiteral(l).ok_or(CompilerError::InvalidAddress(l.to_string(), *span))?;
+                let v = if signed {extendByte(v)} else {v & 0xFF};
+                expressionOutput = ExpressionOutLocation::Literal(v.to_string());
+            }
+            else {
+                expressionString.push_str(&moveExtendedTo(exprpkg.1, ExpressionOutLocation::RegisterAX, 1, signed)?);
+                expressionOutput = ExpressionOutLocation::RegisterAX;
+            }
+        }
+    }
+    return Ok((expressionString, expressionOutput));
+}
 
-                        label_discriminant += 1;
-                        
-                        match block.con {
-                            Some(ref con) => {
+//implement for evaluation package
+fn evaluateExpr(
+    expr: &Expression<'_>,
+    program: &Program, 
+    current_pkg: &EvaluationPackage,
+    state: &mut CompilerState
+) -> Result<(String, ExpressionOutLocation), CompilerError> {
+    let node = match expr.tree {
+        Some(ref n) => n,
+        // failed to parse, already reported by the parser
+        None if !expr.tks.is_empty() => return Ok((Default::default(), ExpressionOutLocation::None)),
+        None => return Err(CompilerError::EncounteredBlankExpression(expr.span())),
+    };
+    match expr.t{
+        // store output in A reg on return, AX for 16-bit values
+        ExpressionType::Return => {
+            let called_proc = &program.procs[current_pkg.directory[0].index];
+            let ret = called_proc.retType.v;
+            let width = nodeWidth(node, program, current_pkg)?;
+            let (mut expressionString, expressionOutput) = evaluateNode(node, program, current_pkg, state)?;
+            if ret.isWord() {
+                let signed = nodeSigned(node, program, current_pkg)?;
+                expressionString.push_str(&moveExtendedTo(expressionOutput, ExpressionOutLocation::RegisterAX, width, signed)?);
+                return Ok((expressionString, ExpressionOutLocation::RegisterAX));
+            }
+            let expressionOutput = narrow(expressionOutput);
+            if expressionOutput != ExpressionOutLocation::RegisterA {
+                expressionString.push_str(&moveOutTo(expressionOutput, ExpressionOutLocation::RegisterA)?);
+            }
+            return Ok((expressionString, ExpressionOutLocation::RegisterA));
+        }
+        _ => {
+            // a call on its own does not need a value
+            if let ExprNode::Call(tk, args, span) = node {
+                return Ok((evaluateCall(tk, args, *span, program, current_pkg, state)?, ExpressionOutLocation::None));
+            }
+            return evaluateNode(node, program, current_pkg, state);
+        }
+    }
+}
+
+// a for stepping a uint by 1 up to a known bound is counted in X
+// gives where the counter lives, the bound and the branch over the jump back once it is reached
+fn countedLoop(
+    block: &Block<'_>,
+    program: &Program,
+    current_pkg: &EvaluationPackage,
+    state: &CompilerState
+) -> Result<Option<(ExpressionOutLocation, u16, &'static str)>, CompilerError> {
+    let (op, counter, bound) = match block.con.as_ref().and_then(|c| c.tree.as_ref()) {
+        Some(ExprNode::Binary(op, lhs, rhs, _)) => {
+            match (&**lhs, constantValue(rhs)?) {
+                (ExprNode::Variable(tk), Some(b)) => (*op, *tk, b),
+                _ => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+    let isCounter = |node: &ExprNode<'_>| -> bool {
+        match node {
+            ExprNode::Variable(tk) => return tk.tk_comp_data == counter.tk_comp_data,
+            _ => return false,
+        }
+    };
+
+    // i++, i += 1, i = i + 1 or i = 1 + i
+    let stepped = match block.step.as_ref().and_then(|s| s.tree.as_ref()) {
+        Some(ExprNode::Unary(UnaryOp::Increment, target, _)) => isCounter(target),
+        Some(ExprNode::Assign(target, value, _)) if isCounter(target) => stepOf(target, value)? == Some(true),
+        _ => false,
+    };
+    if !stepped {return Ok(None);}
+
+    let var = grabVariableComp!(counter, program, current_pkg)?;
+    let loc = match (var.t.a, var.t.v) {
+        (DataAllocationType::Stack(addr), DataValueType::Uint) => state.stack(addr),
+        (DataAllocationType::Heap(addr), DataValueType::Uint) => ExpressionOutLocation::Heap(addr),
+        _ => return Ok(None),
+    };
+    match op {
+        BinaryOp::Less if bound <= 0xFF => return Ok(Some((loc, bound, "BCS 3;\n"))),
+        BinaryOp::LessEq if bound < 0xFF => return Ok(Some((loc, bound+1, "BCS 3;\n"))),
+        BinaryOp::NEq if bound <= 0xFF => return Ok(Some((loc, bound, "BEQ 3;\n"))),
+        _ => return Ok(None),
+    }
+}
+
+// the words of a raw or asm block back into KASM, each on the line it was written on
+// a word written right after another, like the ; after a name, stays joined to it
+fn asmText<'a>(
+    words: &[&Token<'a>], 
+    mut operand: impl FnMut(&Token<'a>) -> Result<String, CompilerError>
+) -> Result<String, CompilerError> {
+    let mut text: String = Default::default();
+    for i in 0..words.len() {
+        if i != 0 {
+            let prev = words[i-1].span;
+            if words[i].span.line != prev.line {text.push('\n');}
+            else if words[i].span.offset != prev.offset + prev.len {text.push(' ');}
+        }
+        match words[i].tk_type {
+            TokenType::Variable => text.push_str(&operand(words[i])?),
+            // a const
+            TokenType::NumberLiteral => text.push_str(&literalText(words[i])),
+            _ => text.push_str(words[i].tk_data),
+        }
+    }
+    text.push('\n');
+    return Ok(text);
+}
+
+// a variable named in an asm block, the stack offset or heap address the KASM ops take
+fn asmOperand(tk: &Token<'_>, program: &Program, current_pkg: &EvaluationPackage) -> Result<String, CompilerError> {
+    let var = grabVariableComp!(tk, program, current_pkg)?;
+    match var.t.a {
+        DataAllocationType::Stack(addr) => return Ok(addr.to_string()),
+        DataAllocationType::Heap(addr) => return Ok(addr.to_string()),
+        DataAllocationType::Static => return Ok(program.staticLabel(var.label, var.module)),
+        _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a, tk.span)),
+    }
+}
+
+fn iterateOverLines(
+    p: & mut EvaluationPackage<'_>, 
+    program: &Program<'_>, 
+    contents: &mut String, 
+    state: &mut CompilerState, 
+    errors: &mut Vec<CompilerError>
+) -> Result<(), CompilerError>{
+    // variables declared in the block get their own frame
+    if p.t == EvaluationPackageType::Block && p.allocated_bytes != 0 {
+        contents.push_str("SAL ");
+        contents.push_str(&p.allocated_bytes.to_string());
+        contents.push_str(";\n");
+    }
+    for index in 0..p.lines.len(){
+        // a line that fails to compile is recorded and the rest of the lines still get compiled
+        let mut reachedElse = false;
+        state.temps = 0;
+        state.frame_offset = 0;
+        let result: Result<(), CompilerError> = (|| {
+            match p.lines[index].t {
+                LineType::Expression => {
+                    match p.expressions[p.lines[index].index].t {
+                        ExpressionType::Return => {
+                            let proc_ret = program.procs[p.directory[0].index].retType.v;
+                            let expr = &p.expressions[p.lines[index].index];
+                            // main is left to return nothing, it only halts
+                            if expr.tks.is_empty() && proc_ret != DataValueType::Void
+                            && program.procs[p.directory[0].index].label != "main" {
+                                return Err(CompilerError::MissingReturnValue(
+                                    program.procs[p.directory[0].index].label.to_string(), proc_ret, expr.span()
+                                ));
+                            }
+                            if p.expressions[p.lines[index].index].tree.is_some() {
                                 contents.push_str(
-                                    &evaluateExpr(con.clone(), program, &p)?.0
+                                    evaluateExpr(
+                                        &p.expressions[p.lines[index].index],
+                                        program,
+                                        p,
+                                        state
+                                    ).map_err(|e| e.at(p.expressions[p.lines[index].index].span()))?.0.as_str()
                                 );
                             }
-                            None => return Err(CompilerError::MissingCondition(block.block_type))
+                            // the whole frame goes, with the frames of the blocks it is in
+                            let frame = program.procs[p.directory[0].index].allocated_bytes + blockFrames(program, &p.directory, 1);
+                            if frame != 0 {
+                                contents.push_str("DAL ");
+                                contents.push_str(&frame.to_string());
+                                contents.push_str(";\n");
+                            }
+                            if program.procs[p.directory[0].index].label == "main" {
+                                contents.push_str("BRK;\n");
+                            }
+                            else {
+                                contents.push_str("RTS;\n");
+                            }
+                        }
+                        ExpressionType::Break |
+                        ExpressionType::Continue => {
+                            let expr = &p.expressions[p.lines[index].index];
+                            contents.push_str(&state.loopJump(expr.t, expr.span(), program, &p.directory)?);
+                        }
+                        ExpressionType::Asm => {
+                            let expr = &p.expressions[p.lines[index].index];
+                            contents.push_str(&asmText(&expr.tks, |tk| asmOperand(tk, program, p))?);
                         }
+                        ExpressionType::Unspecified |
+                        ExpressionType::Assignment => {
+                            contents.push_str(
+                                evaluateExpr(
+                                    &p.expressions[p.lines[index].index],
+                                    program,
+                                    p,
+                                    state
+                                ).map_err(|e| e.at(p.expressions[p.lines[index].index].span()))?.0.as_str()
+                            );
+                        }
+                        _ => return Err(CompilerError::UnimplementedExprType(p.expressions[p.lines[index].index].t, p.expressions[p.lines[index].index].span()))
+                    }
+                }
+                LineType::Block => {
+                    //grab info
+                    let mut directory = p.directory.clone();
+                    let index = p.lines[index].index;
+                    directory.push(BlockParent {
+                        index,
+                        t: BlockParentType::Block
+                    });
+                    let block = program.getBlock(&directory);
+
+                    //place condition and TODO: allocate var space & displace variable queries
+                    let escape_label: String;
+                    match block.block_type {
+                        // the rest of the chain is compiled by the if it hangs off
+                        BlockType::Elif |
+                        BlockType::Else => {
+                            p.else_block_dir = Some(directory);
+                            reachedElse = true;
+                            return Ok(());
+                        }
+                        BlockType::If => {
+                            escape_label = state.label(&program.procLabel(directory[0].index), "IF");
+
+                            //every taken link jumps past the rest of the chain
+                            let mut universal_escape: String = escape_label.clone();
+                            universal_escape.push_str("_UNI");
+                            let mut chained = false;
+
+                            let mut link = block;
+                            let mut link_dir = directory;
+                            let mut link_escape = escape_label;
+                            loop {
+                                match link.con {
+                                    Some(ref con) => {
+                                        contents.push_str(
+                                            &evaluateConditionJump(con, false, &link_escape, program, p, state).map_err(|e| e.at(con.span()))?
+                                        );
+                                    }
+                                    None => return Err(CompilerError::MissingCondition(link.block_type, Span::default()))
+                                }
+
+                                // build package
+                                let mut new_pkg = EvaluationPackage {
+                                    lines:&link.lines,
+                                    expressions:&link.expressions,
+                                    blocks:&link.blocks,
+                                    directory:link_dir,
+                                    allocated_bytes:link.allocated_bytes,
+                                    t:EvaluationPackageType::Block,
+                                    else_block_dir: None
+                                };
+
+                                //eval expressions
+                                match iterateOverLines(& mut new_pkg, program, contents, state, errors) {
+                                    Ok(_) => (),
+                                    Err(e) => return Err(e)
+                                };
 
-                        contents.push_str("JMPA ");
-                        contents.push_str(&escape_label);
-                        contents.push_str(";\n");
-
-                        // build package
-                        let mut new_pkg = EvaluationPackage {
-                            lines:&block.lines,
-                            expressions:&block.expressions,
-                            blocks:&block.blocks,
-                            directory:directory,
-                            allocated_bytes:block.allocated_bytes,
-                            t:EvaluationPackageType::Block,
-                            else_block_dir: None
-                        };
-
-                        //eval expressions
-                        match iterateOverLines(& mut new_pkg, program, contents, label_discriminant) {
-                            Ok(_) => (),
-                            Err(e) => return Err(e)
-                        };
-
-                        match new_pkg.else_block_dir {
-                            Some(dir) => {
-                                //only covers true else
-                                let mut universal_escape: String = escape_label.clone();
-                                universal_escape.push_str("_UNI");
-
-                                //place jump to universal (if was true)
+                                let dir = match new_pkg.else_block_dir {
+                                    Some(dir) => dir,
+                                    None => {
+                                        //place escape
+                                        contents.push_str("LABEL ");
+                                        contents.push_str(&link_escape);
+                                        contents.push('\n');
+                                        break;
+                                    }
+                                };
+                                chained = true;
+
+                                //place jump to universal (link was true)
                                 contents.push_str("JMPA ");
                                 contents.push_str(&universal_escape);
                                 contents.push_str(";\n");
 
                                 //place escape
                                 contents.push_str("LABEL ");
-                                contents.push_str(&escape_label);
-                                contents.push_str("\n");
-
-                                let else_block = program.getBlock(&dir);
+                                contents.push_str(&link_escape);
+                                contents.push('\n');
+
+                                let next_block = program.getBlock(&dir);
+                                if next_block.block_type == BlockType::Elif {
+                                    link = next_block;
+                                    link_dir = dir;
+                                    link_escape = state.label(&program.procLabel(link_dir[0].index), "ELIF");
+                                    continue;
+                                }
 
                                 //iterate else
                                 let mut else_pkg = EvaluationPackage {
-                                    lines:&else_block.lines,
-                                    expressions:&else_block.expressions,
-                                    blocks:&else_block.blocks,
+                                    lines:&next_block.lines,
+                                    expressions:&next_block.expressions,
+                                    blocks:&next_block.blocks,
                                     directory:dir.to_vec(),
-                                    allocated_bytes:else_block.allocated_bytes,
+                                    allocated_bytes:next_block.allocated_bytes,
                                     t:EvaluationPackageType::Block,
                                     else_block_dir: None
                                 };
 
                                 //eval expressions
-                                match iterateOverLines(& mut else_pkg, program, contents, label_discriminant) {
+                                match iterateOverLines(& mut else_pkg, program, contents, state, errors) {
                                     Ok(_) => (),
                                     Err(e) => return Err(e)
                                 };
+                                break;
+                            }
 
+                            if chained {
                                 //place universal
                                 contents.push_str("LABEL ");
                                 contents.push_str(&universal_escape);
-                                contents.push_str("\n");
+                                contents.push('\n');
                             }
-                            None => {
-                                //place escape
+                        }
+                        BlockType::While => {
+                            escape_label = state.label(&program.procLabel(directory[0].index), "WHILE");
+                        
+
+                            contents.push_str("JMPA ");
+                            contents.push_str(&escape_label);
+                            contents.push_str("_CON");
+                            contents.push_str(";\n");
+
+                            contents.push_str("LABEL ");
+                            contents.push_str(&escape_label);
+                            contents.push_str("_TOP");
+                            contents.push('\n');
+
+                            // build package
+                            let mut new_pkg = EvaluationPackage {
+                                lines:&block.lines,
+                                expressions:&block.expressions,
+                                blocks:&block.blocks,
+                                directory,
+                                allocated_bytes:block.allocated_bytes,
+                                t:EvaluationPackageType::Block,
+                                else_block_dir: None
+                            };
+
+                            //eval expressions
+                            state.loops.push(LoopLabels {
+                                label: escape_label.clone(),
+                                continue_to: "_CON",
+                                depth: new_pkg.directory.len()-1,
+                                broken: false
+                            });
+                            let body = iterateOverLines(&mut new_pkg, program, contents, state, errors);
+                            let broken = state.loops.pop().is_some_and(|l| l.broken);
+                            match body {
+                                Ok(_) => (),
+                                Err(e) => return Err(e)
+                            };
+
+                            //place escape
+                            contents.push_str("LABEL ");
+                            contents.push_str(&escape_label);
+                            contents.push_str("_CON");
+                            contents.push('\n');
+
+                            let mut top_label = escape_label.clone();
+                            top_label.push_str("_TOP");
+                            match block.con {
+                                Some(ref con) => {
+                                    contents.push_str(
+                                        &evaluateConditionJump(con, true, &top_label, program, p, state).map_err(|e| e.at(con.span()))?
+                                    );
+                                }
+                                None => return Err(CompilerError::MissingCondition(block.block_type, Span::default()))
+                            }
+
+                            // only there to be jumped to by break
+                            if broken {
                                 contents.push_str("LABEL ");
                                 contents.push_str(&escape_label);
-                                contents.push_str("\n");
+                                contents.push_str("_END");
+                                contents.push('\n');
+                            }
+                        }
+                        // init is compiled as the line before, continue goes to the step
+                        BlockType::For => {
+                            escape_label = state.label(&program.procLabel(directory[0].index), "FOR");
+                            let counted = countedLoop(block, program, p, state)?;
+                            if let Some((ref counter, ..)) = counted {
+                                contents.push_str(&moveOutTo(counter.clone(), ExpressionOutLocation::RegisterX)?);
                             }
-                        };
-                    }
-                    BlockType::While => {
-                        escape_label.push_str(program.procs[directory[0].index].label);
-                        escape_label.push_str("_WHILE");
-                        escape_label.push_str(&label_discriminant.to_string());
 
-                        label_discriminant += 1;
-                        
+                            contents.push_str("JMPA ");
+                            contents.push_str(&escape_label);
+                            contents.push_str("_CON");
+                            contents.push_str(";\n");
 
-                        contents.push_str("JMPA ");
-                        contents.push_str(&escape_label);
-                        contents.push_str("_CON");
-                        contents.push_str(";\n");
-
-                        contents.push_str("LABEL ");
-                        contents.push_str(&escape_label);
-                        contents.push_str("_TOP");
-                        contents.push_str("\n");
-
-                        // build package
-                        let mut new_pkg = EvaluationPackage {
-                            lines:&block.lines,
-                            expressions:&block.expressions,
-                            blocks:&block.blocks,
-                            directory:directory,
-                            allocated_bytes:block.allocated_bytes,
-                            t:EvaluationPackageType::Block,
-                            else_block_dir: None
-                        };
-
-                        //eval expressions
-                        match iterateOverLines(&mut new_pkg, program, contents, label_discriminant) {
-                            Ok(_) => (),
-                            Err(e) => return Err(e)
-                        };
-
-                        //place escape
-                        contents.push_str("LABEL ");
-                        contents.push_str(&escape_label);
-                        contents.push_str("_CON");
-                        contents.push_str("\n");
-
-                        match block.con {
-                            Some(ref con) => {
-                                contents.push_str(
-                                    &evaluateExpr(con.clone(), program, &p)?.0
-                                );
+                            contents.push_str("LABEL ");
+                            contents.push_str(&escape_label);
+                            contents.push_str("_TOP");
+                            contents.push('\n');
+
+                            // build package
+                            let mut new_pkg = EvaluationPackage {
+                                lines:&block.lines,
+                                expressions:&block.expressions,
+                                blocks:&block.blocks,
+                                directory,
+                                allocated_bytes:block.allocated_bytes,
+                                t:EvaluationPackageType::Block,
+                                else_block_dir: None
+                            };
+
+                            //eval expressions
+                            state.loops.push(LoopLabels {
+                                label: escape_label.clone(),
+                                continue_to: "_STEP",
+                                depth: new_pkg.directory.len()-1,
+                                broken: false
+                            });
+                            let body = iterateOverLines(&mut new_pkg, program, contents, state, errors);
+                            let broken = state.loops.pop().is_some_and(|l| l.broken);
+                            match body {
+                                Ok(_) => (),
+                                Err(e) => return Err(e)
+                            };
+
+                            contents.push_str("LABEL ");
+                            contents.push_str(&escape_label);
+                            contents.push_str("_STEP");
+                            contents.push('\n');
+
+                            let mut top_label = escape_label.clone();
+                            top_label.push_str("_TOP");
+                            match counted {
+                                Some((counter, bound, done)) => {
+                                    contents.push_str(&moveOutTo(counter.clone(), ExpressionOutLocation::RegisterX)?);
+                                    contents.push_str("INX;\n");
+                                    contents.push_str(&moveOutTo(ExpressionOutLocation::RegisterX, counter)?);
+
+                                    contents.push_str("LABEL ");
+                                    contents.push_str(&escape_label);
+                                    contents.push_str("_CON");
+                                    contents.push('\n');
+
+                                    contents.push_str("CPXC ");
+                                    contents.push_str(&bound.to_string());
+                                    contents.push_str(";\n");
+                                    contents.push_str(done);
+                                    contents.push_str("JMPA ");
+                                    contents.push_str(&top_label);
+                                    contents.push_str(";\n");
+                                }
+                                None => {
+                                    if let Some(ref step) = block.step {
+                                        state.temps = 0;
+                                        contents.push_str(
+                                            &evaluateExpr(step, program, p, state).map_err(|e| e.at(step.span()))?.0
+                                        );
+                                    }
+
+                                    contents.push_str("LABEL ");
+                                    contents.push_str(&escape_label);
+                                    contents.push_str("_CON");
+                                    contents.push('\n');
+
+                                    // no condition loops until a break
+                                    match block.con {
+                                        Some(ref con) => {
+                                            contents.push_str(
+                                                &evaluateConditionJump(con, true, &top_label, program, p, state).map_err(|e| e.at(con.span()))?
+                                            );
+                                        }
+                                        None => {
+                                            contents.push_str("JMPA ");
+                                            contents.push_str(&top_label);
+                                            contents.push_str(";\n");
+                                        }
+                                    }
+                                }
                             }
-                            None => return Err(CompilerError::MissingCondition(block.block_type))
-                        }
 
-                        contents.push_str("JMPA ");
-                        contents.push_str(&escape_label);
-                        contents.push_str("_TOP");
-                        contents.push_str(";\n");
+                            if broken {
+                                contents.push_str("LABEL ");
+                                contents.push_str(&escape_label);
+                                contents.push_str("_END");
+                                contents.push('\n');
+                            }
+                        }
+                        _ => return Err(CompilerError::UnimplementedBlockType(block.block_type, Span::default()))
                     }
-                    _ => return Err(CompilerError::UnimplementedBlockType(block.block_type))
                 }
             }
-            _ => return Err(CompilerError::UnimplementedLineType(p.lines[index].t))
-        }
+            return Ok(());
+        })();
+        if let Err(e) = result {errors.push(e);}
+        if reachedElse {break;}
+    }
+    if p.t == EvaluationPackageType::Block && p.allocated_bytes != 0 {
+        contents.push_str("DAL ");
+        contents.push_str(&p.allocated_bytes.to_string());
+        contents.push_str(";\n");
     }
     return Ok(());
 }
 
-pub fn runCompiler<'a>(program: Program<'a>, out_file: &'a str) -> Result<(), CompilerError>{
+// compiles the program into KASM, errors are collected into `errors` and
+// the returned program is only meaningful when none were pushed
+pub fn runCompiler<'a>(program: Program<'a>, errors: &mut Vec<CompilerError>) -> String{
     let mut procs: Vec<String> = vec![]; 
     let mut header: String = Default::default();
     let mut label_header: String = Default::default();
 
     let mut hasMain = false;
+    let mut state: CompilerState = Default::default();
     // println!("{program:#2?}");
 
-    if program.expressions.len() != 0 {
+    if !program.expressions.is_empty() {
         header.push_str("__START_HEADER__\n");
     }
 
+    for raw in &program.raw_labels {
+        label_header.push_str("LABEL ");
+        label_header.push_str(&program.staticLabel(raw.label, raw.module));
+        label_header.push_str("\nRAW\n");
+        match asmText(&raw.words, |tk| Err(CompilerError::UnidentifiedError(tk.span))) {
+            Ok(text) => label_header.push_str(&text),
+            Err(e) => errors.push(e),
+        }
+        label_header.push_str("END\n");
+    }
+
     for expr in &program.expressions {
-        match expr.t {
-            ExpressionType::Assignment => {
-                let expressionOutput = match expr.tks[2].tk_type {
-                    TokenType::CharLiteral |
-                    TokenType::NumberLiteral |
-                    TokenType::HexNumberLiteral => {
-                        ExpressionOutLocation::Literal(expr.tks[2].tk_data.to_string())
-                    }
-                    TokenType::StringLiteral => {
-                        ExpressionOutLocation::StringLiteral(expr.tks[2].tk_data.to_string())
-                    }
-                    TokenType::Symbol => {
-                        if expr.tks[2].tk_data == "[" {
-                            //array literal
-                            let var = grabVariableCompNP!(
-                                expr.tks[0].tk_comp_data.var().ok_or(
-                                    CompilerError::UnidentifiedError
-                                )?, 
-                                program
-                            )?;
-                            
-                            if var.t.a == DataAllocationType::Static && var.t.v == DataValueType::Buffer {
-                                label_header.push_str("LABEL ");
-                                label_header.push_str(expr.tks[0].tk_data);
-                                label_header.push_str("\nRAW\n");
-                                let mut i = 3;
-                                while i < expr.tks.len() && expr.tks[i].tk_data != "]" {
-                                    label_header.push_str(expr.tks[i].tk_data);
+        let result: Result<(), CompilerError> = (|| {
+            match expr.t {
+                ExpressionType::Assignment => {
+                    let (target, value) = match expr.tree {
+                        Some(ExprNode::Assign(ref target, ref value, _)) => (target, value),
+                        _ => return Err(CompilerError::UnimplementedExprType(expr.t, expr.span()))
+                    };
+                    let var_tk = match **target {
+                        ExprNode::Variable(tk) => tk,
+                        _ => return Err(CompilerError::InvalidAssignmentTarget(target.span()))
+                    };
+                    let var = grabVariableCompNP!(
+                        var_tk.tk_comp_data.var().ok_or(
+                            CompilerError::UnidentifiedError(var_tk.span)
+                        )?, 
+                        program
+                    )?;
+
+                    let expressionOutput = match **value {
+                        ExprNode::Literal(tk) => literalLocation(tk),
+                        // a static struct is its fields one after the other, 2 byte fields low byte first
+                        ExprNode::List(ref items, span) if matches!(var.t.v, DataValueType::Struct(_)) => {
+                            if var.t.a != DataAllocationType::Static {
+                                return Err(CompilerError::ListOutsideStatic(span));
+                            }
+                            let mut sizes: Vec<u8> = vec![];
+                            fieldSizes(var.t.v, &program, &mut sizes);
+                            if sizes.len() != items.len() {
+                                return Err(CompilerError::StructInitCount(var_tk.tk_data.to_string(), items.len(), sizes.len(), span));
+                            }
+                            label_header.push_str("LABEL ");
+                            label_header.push_str(&program.staticLabel(var.label, var.module));
+                            label_header.push_str("\nRAW\n");
+                            for (item, size) in items.iter().zip(sizes) {
+                                let v = constantValue(item)?.ok_or(CompilerError::NonLiteralInitialValue(item.span()))?;
+                                label_header.push_str(&(v & 0xFF).to_string());
+                                if size == 2 {
                                     label_header.push(' ');
-                                    i+=1;
+                                    label_header.push_str(&(v >> 8).to_string());
+                                }
+                                label_header.push(' ');
+                            }
+                            label_header.push_str("\nEND\n");
+                            return Ok(());
+                        }
+                        // the elements of a static array, missing ones are 0 and 2 byte ones low byte first
+                        ExprNode::List(ref items, span) if matches!(var.t.v, DataValueType::Array(_)) => {
+                            if var.t.a != DataAllocationType::Static {
+                                return Err(CompilerError::ListOutsideStatic(span));
+                            }
+                            let def = match var.t.v {
+                                DataValueType::Array(i) => program.arrays[i],
+                                _ => return Err(CompilerError::ListOutsideStatic(span)),
+                            };
+                            if items.len() > def.len as usize {
+                                return Err(CompilerError::ArrayInitCount(var_tk.tk_data.to_string(), items.len(), def.len, span));
+                            }
+                            label_header.push_str("LABEL ");
+                            label_header.push_str(&program.staticLabel(var.label, var.module));
+                            label_header.push_str("\nRAW\n");
+                            for n in 0..def.len as usize {
+                                let v = match items.get(n) {
+                                    Some(item) => constantValue(item)?.ok_or(CompilerError::NonLiteralInitialValue(item.span()))?,
+                                    None => 0,
+                                };
+                                label_header.push_str(&(v & 0xFF).to_string());
+                                if def.v.isWord() {
+                                    label_header.push(' ');
+                                    label_header.push_str(&(v >> 8).to_string());
+                                }
+                                label_header.push(' ');
+                            }
+                            label_header.push_str("\nEND\n");
+                            return Ok(());
+                        }
+                        ExprNode::List(ref items, span) => {
+                            //array literal
+                            if var.t.a != DataAllocationType::Static || var.t.v != DataValueType::Buffer {
+                                return Err(CompilerError::ListOutsideStatic(span));
+                            }
+                            label_header.push_str("LABEL ");
+                            label_header.push_str(&program.staticLabel(var.label, var.module));
+                            label_header.push_str("\nRAW\n");
+                            for item in items {
+                                match item {
+                                    ExprNode::Literal(tk) if tk.tk_type == TokenType::StringLiteral => label_header.push_str(tk.tk_data),
+                                    _ => {
+                                        let v = constantValue(item)?.ok_or(CompilerError::NonLiteralInitialValue(item.span()))?;
+                                        label_header.push_str(&(v & 0xFF).to_string());
+                                    }
                                 }
-                                label_header.push_str("\nEND\n");
-                            } else {
-                                return Err(CompilerError::InvalidStandAloneToken(expr.tks[2].tk_type, expr.tks[2].tk_data.to_string()));
+                                label_header.push(' ');
                             }
-                            continue;
-                        } else {
-                            return Err(CompilerError::InvalidStandAloneToken(expr.tks[2].tk_type, expr.tks[2].tk_data.to_string()));
+                            label_header.push_str("\nEND\n");
+                            return Ok(());
                         }
+                        // arithmetic on literals and consts is worked out here
+                        _ => match constantValue(value)? {
+                            Some(v) => ExpressionOutLocation::Literal(v.to_string()),
+                            None => return Err(CompilerError::NonLiteralInitialValue(value.span())),
+                        },
+                    };
+                    let o = match var.t.a{
+                        DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr),
+                        DataAllocationType::Static => ExpressionOutLocation::Static(program.staticLabel(var.label, var.module)),
+                        _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a, var_tk.span)),
+                    };
+                    match o {
+                        ExpressionOutLocation::Static(_) => label_header.push_str(
+                            &moveOutTo(expressionOutput.clone(), o).map_err(|e| e.at(expr.span()))?
+                        ),
+                        _ if var.t.v.isWord() => header.push_str(
+                            &moveWordTo(expressionOutput.clone(), o, 2).map_err(|e| e.at(expr.span()))?
+                        ),
+                        _ => header.push_str(
+                            &moveOutTo(narrow(expressionOutput.clone()), o).map_err(|e| e.at(expr.span()))?
+                        ),
                     }
-                    _ => return Err(CompilerError::InvalidStandAloneToken(expr.tks[2].tk_type, expr.tks[2].tk_data.to_string())),
-                };
-                let var = grabVariableCompNP!(
-                    expr.tks[0].tk_comp_data.var().ok_or(
-                        CompilerError::UnidentifiedError
-                    )?, 
-                    program
-                )?;
-                let o = match var.t.a{
-                    DataAllocationType::Heap(addr) => ExpressionOutLocation::Heap(addr),
-                    DataAllocationType::Static => ExpressionOutLocation::Static(var.label.to_string()),
-                    _ => return Err(CompilerError::UnimplementedDataAllocType(var.t.a)),
-                };
-                match o {
-                    ExpressionOutLocation::Static(_) => label_header.push_str(
-                        &moveOutTo(expressionOutput.clone(), o)?
-                    ),
-                    _ => header.push_str(
-                        &moveOutTo(expressionOutput.clone(), o)?
-                    ),
                 }
+                _ => return Err(CompilerError::UnimplementedExprType(expr.t, expr.span()))
             }
-            _ => return Err(CompilerError::UnimplementedExprType(expr.t))
-        }
+            return Ok(());
+        })();
+        if let Err(e) = result {errors.push(e);}
     }
 
-    if program.expressions.len() != 0 {
+    if !program.expressions.is_empty() {
         header.push_str("__END_HEADER__\n");
     }
 
@@ -1254,7 +3144,7 @@ pub fn runCompiler<'a>(program: Program<'a>, out_file: &'a str) -> Result<(), Co
         }
         else {
             contents.push_str("LABEL ");
-            contents.push_str(p.label);
+            contents.push_str(&program.procLabel(i));
             contents.push('\n');
         }
 
@@ -1263,7 +3153,25 @@ pub fn runCompiler<'a>(program: Program<'a>, out_file: &'a str) -> Result<(), Co
             contents.push_str(&p.allocated_bytes.to_string());
             contents.push_str(";\n");
         }
-        let mut label_discriminant = 0;
+        // register arguments are kept in the frame like locals
+        if p.label != "main" {
+            let stores = ["STAS ", "STYS "];
+            for (i, (arg, store)) in p.arguments.iter().zip(stores).enumerate() {
+                let addr = match arg.t.a {
+                    DataAllocationType::Stack(addr) if argInRegister(i, arg.t.v) => addr,
+                    _ => continue,
+                };
+                contents.push_str(store);
+                contents.push_str(&addr.to_string());
+                contents.push_str(";\n");
+                if arg.t.v.isWord() {
+                    contents.push_str("STXS ");
+                    contents.push_str(&(addr+1).to_string());
+                    contents.push_str(";\n");
+                }
+            }
+        }
+        state.label_discriminant = 0;
 
         let mut package = EvaluationPackage {
             lines:&p.lines,
@@ -1277,10 +3185,9 @@ pub fn runCompiler<'a>(program: Program<'a>, out_file: &'a str) -> Result<(), Co
             else_block_dir: None
         };
 
-        match iterateOverLines(&mut package, &program, &mut contents, label_discriminant) {
-            Ok(_) => (),
-            Err(e) => return Err(e)
-        };
+        if let Err(e) = iterateOverLines(&mut package, &program, &mut contents, &mut state, errors) {
+            errors.push(e);
+        }
 
         if p.label == "main" {
             procs.insert(0, contents.clone());
@@ -1291,31 +3198,30 @@ pub fn runCompiler<'a>(program: Program<'a>, out_file: &'a str) -> Result<(), Co
     }
 
     if !hasMain {
-        return Err(CompilerError::NoMainProc);
+        errors.push(CompilerError::NoMainProc);
+        return Default::default();
+    }
+
+    let mut kasm: String = header;
+    kasm.push_str(&label_header);
+    for proc in procs.iter().skip(1) {
+        kasm.push_str(proc);
     }
+    // only the helpers that got called
+    for helper in HELPERS {
+        if state.helpers.contains(helper) {kasm.push_str(helper.source());}
+    }
+    kasm.push_str(&procs[0]);
+    return kasm;
+}
 
+pub fn writeProgram(kasm: &str, out_file: &str) -> Result<(), CompilerError>{
     let mut out_file_path: File = match File::create(out_file) {
         Ok(f) => f,
         Err(_e) => return Err(CompilerError::UnableToOpenOutFile(out_file.to_string()))
     };
 
-    match write!(out_file_path,"{}",header) { 
-        Ok(_) => (),
-        Err(_e) => return Err(CompilerError::UnableToWriteOutFile(out_file.to_string()))
-    };
-
-    match write!(out_file_path,"{}",label_header) { 
-        Ok(_) => (),
-        Err(_e) => return Err(CompilerError::UnableToWriteOutFile(out_file.to_string()))
-    };
-
-    for i in 1..procs.len() {
-        match write!( out_file_path, "{}", procs[i]) {
-            Ok(_) => (),
-            Err(_e) => return Err(CompilerError::UnableToWriteOutFile(out_file.to_string()))
-        };
-    }
-    match write!( out_file_path, "{}", procs[0]) {
+    match write!(out_file_path,"{}",kasm) { 
         Ok(_) => (),
         Err(_e) => return Err(CompilerError::UnableToWriteOutFile(out_file.to_string()))
     };
@@ -0,0 +1,445 @@
+// KASM routines for operations the ISA has no opcode for,
+// the compiler JSRs into them and only the ones a program uses are put in the .kasm
+//
+// calling convention: lhs in A, rhs at HELPER_ARG, result back in A
+// the 16-bit ones take and give the high byte in X, the rhs low byte first
+// the 8-bit ones keep X, none touch Y, they use the zero page from 0x08 to 0x0E
+
+pub const HELPER_ARG: u16 = 0x0008;
+pub const DIV_REMAINDER: u16 = 0x000A; //left by __DIV8__, used for %
+pub const DIV16_REMAINDER: u16 = 0x000C; //left by __DIV16__
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Helper {
+    Mul8,
+    Div8,
+    Shl8,
+    Shr8,
+    Sar8,
+    Mul16,
+    Div16,
+    Shl16,
+    Shr16,
+    Sar16
+}
+
+pub static HELPERS: &[Helper] = &[
+    Helper::Mul8, Helper::Div8, Helper::Shl8, Helper::Shr8, Helper::Sar8,
+    Helper::Mul16, Helper::Div16, Helper::Shl16, Helper::Shr16, Helper::Sar16
+];
+
+impl Helper {
+    pub fn label(self) -> &'static str {
+        match self {
+            Helper::Mul8 => return "__MUL8__",
+            Helper::Div8 => return "__DIV8__",
+            Helper::Shl8 => return "__SHL8__",
+            Helper::Shr8 => return "__SHR8__",
+            Helper::Sar8 => return "__SAR8__",
+            Helper::Mul16 => return "__MUL16__",
+            Helper::Div16 => return "__DIV16__",
+            Helper::Shl16 => return "__SHL16__",
+            Helper::Shr16 => return "__SHR16__",
+            Helper::Sar16 => return "__SAR16__",
+        }
+    }
+
+    pub fn source(self) -> &'static str {
+        match self {
+            Helper::Mul8 => return MUL8,
+            Helper::Div8 => return DIV8,
+            Helper::Shl8 => return SHL8,
+            Helper::Shr8 => return SHR8,
+            Helper::Sar8 => return SAR8,
+            Helper::Mul16 => return MUL16,
+            Helper::Div16 => return DIV16,
+            Helper::Shl16 => return SHL16,
+            Helper::Shr16 => return SHR16,
+            Helper::Sar16 => return SAR16,
+        }
+    }
+
+    // another helper it jumps into, has to be put in the .kasm with it
+    pub fn needs(self) -> Option<Helper> {
+        match self {
+            Helper::Sar8 => return Some(Helper::Shr8),
+            Helper::Sar16 => return Some(Helper::Shr16),
+            _ => return None,
+        }
+    }
+}
+
+// shift and add, walks the multiplier from its top bit
+// 0x09 multiplicand, 0x0A result, 0x0B saved X
+static MUL8: &str = "LABEL __MUL8__
+#A*M[0x08]->A#
+STX 11;
+STA 9;
+LDAC 0;
+STA 10;
+LDXC 8;
+LABEL __MUL8_LOOP__
+LDA 10;
+CLC;
+ADC 10;
+STA 10;
+LDA 8;
+ANDC 128;
+CMPC 0;
+BNE 3;
+JMPA __MUL8_SKIP__;
+LDA 10;
+CLC;
+ADC 9;
+STA 10;
+LABEL __MUL8_SKIP__
+LDA 8;
+CLC;
+ADC 8;
+STA 8;
+DEX;
+CPXC 0;
+BEQ 3;
+JMPA __MUL8_LOOP__;
+LDA 10;
+LDX 11;
+RTS;
+";
+
+// shift and subtract, the quotient bits are shifted into the dividend as it empties
+// 0x09 dividend then quotient, 0x0A remainder, 0x0B saved X, 0x0C remainder overflowed
+// dividing by 0 gives 255 with the dividend as the remainder
+static DIV8: &str = "LABEL __DIV8__
+#A/M[0x08]->A,A%M[0x08]->M[0x0A]#
+STX 11;
+STA 9;
+LDAC 0;
+STA 10;
+LDXC 8;
+LABEL __DIV8_LOOP__
+LDA 10;
+ANDC 128;
+STA 12;
+LDA 10;
+CLC;
+ADC 10;
+STA 10;
+LDA 9;
+ANDC 128;
+CMPC 0;
+BNE 3;
+JMPA __DIV8_SHIFT__;
+LDA 10;
+ORAC 1;
+STA 10;
+LABEL __DIV8_SHIFT__
+LDA 9;
+CLC;
+ADC 9;
+STA 9;
+LDA 12;
+CMPC 0;
+BEQ 3;
+JMPA __DIV8_SUB__;
+LDA 10;
+CMP 8;
+BCS 3;
+JMPA __DIV8_NEXT__;
+LABEL __DIV8_SUB__
+LDA 10;
+SEC;
+SBC 8;
+STA 10;
+LDA 9;
+ORAC 1;
+STA 9;
+LABEL __DIV8_NEXT__
+DEX;
+CPXC 0;
+BEQ 3;
+JMPA __DIV8_LOOP__;
+LDA 9;
+LDX 11;
+RTS;
+";
+
+// A added to itself once per count
+// 0x09 copy of A, 0x0B saved X
+static SHL8: &str = "LABEL __SHL8__
+#A<<M[0x08]->A#
+STX 11;
+LDX 8;
+LABEL __SHL8_LOOP__
+CPXC 0;
+BNE 3;
+JMPA __SHL8_END__;
+STA 9;
+CLC;
+ADC 9;
+DEX;
+JMPA __SHL8_LOOP__;
+LABEL __SHL8_END__
+LDX 11;
+RTS;
+";
+
+// no shift right opcode, the top 8-count bits are shifted out of A into the result
+// 0x09 value, 0x0A result, 0x0B saved X
+static SHR8: &str = "LABEL __SHR8__
+#A>>M[0x08]->A#
+STX 11;
+STA 9;
+LDAC 0;
+STA 10;
+LDX 8;
+LABEL __SHR8_LOOP__
+CPXC 8;
+BCC 3;
+JMPA __SHR8_END__;
+LDA 10;
+CLC;
+ADC 10;
+STA 10;
+LDA 9;
+ANDC 128;
+CMPC 0;
+BNE 3;
+JMPA __SHR8_SHIFT__;
+LDA 10;
+ORAC 1;
+STA 10;
+LABEL __SHR8_SHIFT__
+LDA 9;
+CLC;
+ADC 9;
+STA 9;
+INX;
+JMPA __SHR8_LOOP__;
+LABEL __SHR8_END__
+LDA 10;
+LDX 11;
+RTS;
+";
+
+// >> of an int, a negative value is flipped, shifted and flipped back so the top fills with 1s
+static SAR8: &str = "LABEL __SAR8__
+#A>>M[0x08]->A, signed#
+CMPC 128;
+BCS 3;
+JMPA __SHR8__;
+XORC 255;
+JSR __SHR8__;
+XORC 255;
+RTS;
+";
+
+// same as __MUL8__ over two bytes, the carry takes bit 7 into the high byte
+// 0x0A multiplicand, 0x0C result
+static MUL16: &str = "LABEL __MUL16__
+#AX*M[0x08]->AX#
+STA 10;
+STX 11;
+LDAC 0;
+STA 12;
+STA 13;
+LDXC 16;
+LABEL __MUL16_LOOP__
+LDA 12;
+CLC;
+ADC 12;
+STA 12;
+LDA 13;
+ADC 13;
+STA 13;
+LDA 9;
+ANDC 128;
+CMPC 0;
+BNE 3;
+JMPA __MUL16_SKIP__;
+LDA 12;
+CLC;
+ADC 10;
+STA 12;
+LDA 13;
+ADC 11;
+STA 13;
+LABEL __MUL16_SKIP__
+LDA 8;
+CLC;
+ADC 8;
+STA 8;
+LDA 9;
+ADC 9;
+STA 9;
+DEX;
+CPXC 0;
+BEQ 3;
+JMPA __MUL16_LOOP__;
+LDA 12;
+LDX 13;
+RTS;
+";
+
+// same as __DIV8__ over two bytes, the high bytes are compared first
+// 0x0A dividend then quotient, 0x0C remainder, 0x0E remainder overflowed
+static DIV16: &str = "LABEL __DIV16__
+#AX/M[0x08]->AX,AX%M[0x08]->M[0x0C]#
+STA 10;
+STX 11;
+LDAC 0;
+STA 12;
+STA 13;
+LDXC 16;
+LABEL __DIV16_LOOP__
+LDA 13;
+ANDC 128;
+STA 14;
+LDA 12;
+CLC;
+ADC 12;
+STA 12;
+LDA 13;
+ADC 13;
+STA 13;
+LDA 11;
+ANDC 128;
+CMPC 0;
+BNE 3;
+JMPA __DIV16_SHIFT__;
+LDA 12;
+ORAC 1;
+STA 12;
+LABEL __DIV16_SHIFT__
+LDA 10;
+CLC;
+ADC 10;
+STA 10;
+LDA 11;
+ADC 11;
+STA 11;
+LDA 14;
+CMPC 0;
+BEQ 3;
+JMPA __DIV16_SUB__;
+LDA 13;
+CMP 9;
+BEQ 3;
+JMPA __DIV16_HIGH__;
+LDA 12;
+CMP 8;
+LABEL __DIV16_HIGH__
+BCS 3;
+JMPA __DIV16_NEXT__;
+LABEL __DIV16_SUB__
+LDA 12;
+SEC;
+SBC 8;
+STA 12;
+LDA 13;
+SBC 9;
+STA 13;
+LDA 10;
+ORAC 1;
+STA 10;
+LABEL __DIV16_NEXT__
+DEX;
+CPXC 0;
+BEQ 3;
+JMPA __DIV16_LOOP__;
+LDA 10;
+LDX 11;
+RTS;
+";
+
+// the count is only the low byte at HELPER_ARG
+// 0x0A value
+static SHL16: &str = "LABEL __SHL16__
+#AX<<M[0x08]->AX#
+STA 10;
+STX 11;
+LDX 8;
+LABEL __SHL16_LOOP__
+CPXC 0;
+BNE 3;
+JMPA __SHL16_END__;
+LDA 10;
+CLC;
+ADC 10;
+STA 10;
+LDA 11;
+ADC 11;
+STA 11;
+DEX;
+JMPA __SHL16_LOOP__;
+LABEL __SHL16_END__
+LDA 10;
+LDX 11;
+RTS;
+";
+
+// 0x0A value, 0x0C result
+static SHR16: &str = "LABEL __SHR16__
+#AX>>M[0x08]->AX#
+STA 10;
+STX 11;
+LDAC 0;
+STA 12;
+STA 13;
+LDX 8;
+LABEL __SHR16_LOOP__
+CPXC 16;
+BCC 3;
+JMPA __SHR16_END__;
+LDA 12;
+CLC;
+ADC 12;
+STA 12;
+LDA 13;
+ADC 13;
+STA 13;
+LDA 11;
+ANDC 128;
+CMPC 0;
+BNE 3;
+JMPA __SHR16_SHIFT__;
+LDA 12;
+ORAC 1;
+STA 12;
+LABEL __SHR16_SHIFT__
+LDA 10;
+CLC;
+ADC 10;
+STA 10;
+LDA 11;
+ADC 11;
+STA 11;
+INX;
+JMPA __SHR16_LOOP__;
+LABEL __SHR16_END__
+LDA 12;
+LDX 13;
+RTS;
+";
+
+// same as __SAR8__ over two bytes
+// 0x0E low byte while the high byte is flipped
+static SAR16: &str = "LABEL __SAR16__
+#AX>>M[0x08]->AX, signed#
+CPXC 128;
+BCS 3;
+JMPA __SHR16__;
+XORC 255;
+STA 14;
+TXA;
+XORC 255;
+TAX;
+LDA 14;
+JSR __SHR16__;
+XORC 255;
+STA 14;
+TXA;
+XORC 255;
+TAX;
+LDA 14;
+RTS;
+";
@@ -0,0 +1,133 @@
+use crate::lexer::Span;
+
+// a loaded .k file, Span::file indexes into the list of these
+#[derive(Default, Debug)]
+pub struct SourceFile {
+    pub name: String,
+    pub contents: String
+}
+
+#[derive(Default, Debug, PartialEq, Clone, Copy)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Error => return "error",
+            Severity::Warning => return "warning",
+        }
+    }
+}
+
+// renders a message rustc style:
+//
+// error: (ParserError) Invalid assignment to 'x'.
+//   --> ../main.k:12:5
+//    |
+// 12 |     x = 3;
+//    |     ^
+pub fn render(severity: Severity, message: &str, span: Option<Span>, sources: &[SourceFile]) -> String {
+    let mut out: String = Default::default();
+    out.push_str(severity.label());
+    out.push_str(": ");
+    out.push_str(message);
+    out.push('\n');
+
+    let span = match span {
+        Some(s) if s.line != 0 && s.file < sources.len() => s,
+        _ => return out,
+    };
+    let source = &sources[span.file];
+    let line_text = source.contents.lines().nth(span.line-1).unwrap_or("");
+    let gutter = " ".repeat(span.line.to_string().len());
+
+    out.push_str(&gutter);
+    out.push_str("--> ");
+    out.push_str(&source.name);
+    out.push(':');
+    out.push_str(&span.line.to_string());
+    out.push(':');
+    out.push_str(&span.col.to_string());
+    out.push('\n');
+
+    out.push_str(&gutter);
+    out.push_str(" |\n");
+
+    out.push_str(&span.line.to_string());
+    out.push_str(" | ");
+    out.push_str(line_text);
+    out.push('\n');
+
+    // keep tabs so the carets line up with the source line
+    out.push_str(&gutter);
+    out.push_str(" | ");
+    for c in line_text.chars().take(span.col-1) {
+        out.push(if c == '\t' {'\t'} else {' '});
+    }
+    // only underline up to the end of the line for spans crossing lines
+    let remaining = line_text.len().saturating_sub(span.col-1);
+    out.push_str(&"^".repeat(span.len.min(remaining).max(1)));
+    out.push('\n');
+    return out;
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>
+}
+
+// collects errors and warnings from every stage so they can be reported together
+#[derive(Default, Debug)]
+pub struct Diagnostics {
+    pub entries: Vec<Diagnostic>
+}
+
+impl Diagnostics {
+    pub fn error(&mut self, message: String, span: Option<Span>) {
+        self.entries.push(Diagnostic {severity: Severity::Error, message, span});
+    }
+
+    pub fn warning(&mut self, message: String, span: Option<Span>) {
+        self.entries.push(Diagnostic {severity: Severity::Warning, message, span});
+    }
+
+    pub fn errorCount(&self) -> usize {
+        return self.entries.iter().filter(|d| d.severity == Severity::Error).count();
+    }
+
+    pub fn warningCount(&self) -> usize {
+        return self.entries.iter().filter(|d| d.severity == Severity::Warning).count();
+    }
+
+    pub fn hasErrors(&self) -> bool {
+        return self.errorCount() != 0;
+    }
+
+    // prints every entry in source order, unlocated ones last, followed by a summary
+    pub fn report(&self, sources: &[SourceFile]) {
+        let mut ordered: Vec<&Diagnostic> = self.entries.iter().collect();
+        ordered.sort_by_key(|d| match d.span {
+            Some(s) if s.line != 0 => (0, s.file, s.offset),
+            _ => (1, 0, 0),
+        });
+        for d in ordered {
+            print!("{}", render(d.severity, &d.message, d.span, sources));
+        }
+
+        let errors = self.errorCount();
+        let warnings = self.warningCount();
+        if errors != 0 {
+            print!("error: aborting due to {errors} error(s)");
+            if warnings != 0 {print!("; {warnings} warning(s) emitted");}
+            println!();
+        } else if warnings != 0 {
+            println!("warning: {warnings} warning(s) emitted");
+        }
+    }
+}
@@ -12,74 +12,134 @@ use crate::parser;
 
 pub use crate::lexer::token::*;
 
-// look into if you can implement line and character numbers into the errors
 #[derive(Debug, Error)]
 pub enum LexerError {
     #[error("(LexerError) Unidentified error thrown. Reconsider life.")]
-    UnidentifiedError,
+    UnidentifiedError(Span),
 
     #[error("(LexerError) Incorrect hex value.")]
-    InvalidHexValue,
+    InvalidHexValue(Span),
     
     #[error("(LexerError) Length of char literal is invalid.")]
-    CharLengthInvalid,
+    CharLengthInvalid(Span),
 
     #[error("(LexerError) Size of a value is above 8 bits when it should only be 8 bits.")]
-    InvalidValueSize8b,
+    InvalidValueSize8b(Span),
 
     #[error("(LexerError) Size of a value is above 16 bits when it should only be 16 bits.")]
-    InvalidValueSize16b,
+    InvalidValueSize16b(Span),
     
     #[error("(LexerError) Attempting to use a register that does not exist.")]
-    InvalidRegister,
+    InvalidRegister(Span),
 
     #[error("(LexerError) String literal not terminated with a second \".")]
-    NonTerminatedString,
+    NonTerminatedString(Span),
 
     #[error("(LexerError) Comment not terminated with a second #.")]
-    NonTerminatedComment,
+    NonTerminatedComment(Span),
 
     #[error("(LexerError) File Not Read Error: {0}")]
     FileNotRead(#[from] io::Error)
 }
 
+impl LexerError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            LexerError::UnidentifiedError(s) |
+            LexerError::InvalidHexValue(s) |
+            LexerError::CharLengthInvalid(s) |
+            LexerError::InvalidValueSize8b(s) |
+            LexerError::InvalidValueSize16b(s) |
+            LexerError::InvalidRegister(s) |
+            LexerError::NonTerminatedString(s) |
+            LexerError::NonTerminatedComment(s) => Some(*s),
+            LexerError::FileNotRead(_) => None,
+        }
+    }
+
+    fn span_mut(&mut self) -> Option<&mut Span> {
+        match self {
+            LexerError::UnidentifiedError(s) |
+            LexerError::InvalidHexValue(s) |
+            LexerError::CharLengthInvalid(s) |
+            LexerError::InvalidValueSize8b(s) |
+            LexerError::InvalidValueSize16b(s) |
+            LexerError::InvalidRegister(s) |
+            LexerError::NonTerminatedString(s) |
+            LexerError::NonTerminatedComment(s) => Some(s),
+            LexerError::FileNotRead(_) => None,
+        }
+    }
+}
+
 fn tokenize<'a>(
-    file_data: &'a String,
+    file_data: &'a str,
+    file: usize,
     start: usize,
     index: &usize,
     tokenType: TokenType
 ) -> Token<'a> {
-    return Token { tk_type: tokenType, tk_data: &file_data[start..*index], tk_comp_data: TokenCompData::None};
+    return Token { 
+        tk_type: tokenType, 
+        tk_data: &file_data[start..*index], 
+        tk_comp_data: TokenCompData::None,
+        span: Span::new(file, start, *index-start)
+    };
 }
 
-fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Token<'a>, LexerError>{
-    while file_data.as_bytes()[*index].is_ascii_whitespace(){*index+=1;}
-    
+// runLexer has skipped the whitespace and comments before the token
+fn getNextToken<'a,'b>(file_data: &'a str, file: usize, index: &'b mut usize)->Result<Token<'a>, LexerError>{
     let mut outTk: Token<'a> = Default::default();
     let start: usize = *index;
     let mut c : char = file_data.as_bytes()[*index] as char;
 
     match c {
         // if starting with symbol
-        '+' | '-' =>{
+        '+' | '-' | '*' | '/' | '%' | '~' =>{
             *index+=1;
 
+            // ++ and -- or an op followed by =
+            if c != '~' && *index < file_data.len() {
+                let next = file_data.as_bytes()[*index] as char;
+                if next == '=' || ((c == '+' || c == '-') && next == c) {
+                    *index+=1;
+                    return Ok(tokenize(
+                        file_data,
+                        file,
+                        start,
+                        index,
+                        keywords::strToOp(&file_data[start..*index])
+                    ));
+                }
+            }
+
             return Ok(tokenize(
                 file_data,
+                file,
                 start,
                 index,
                 keywords::charToOp(c)
             ));
         }
         // if starting with symbol
-        '=' | '>' | '<' | '!' =>{
+        '=' | '>' | '<' | '!' | '&' | '|' | '^' =>{
             *index+=1;
 
-            if *index < file_data.len() 
+            // << >> && and ||, only the shifts take an =
+            let mut doubled = false;
+            if (c == '<' || c == '>' || c == '&' || c == '|') && *index < file_data.len() 
+            && c == (file_data.as_bytes()[*index] as char) {
+                *index+=1;
+                doubled = true;
+            }
+            if (!doubled || c == '<' || c == '>') && *index < file_data.len() 
             && '=' == (file_data.as_bytes()[*index] as char) {
                 *index+=1;
+            }
+            if *index-start > 1 {
                 return Ok(tokenize(
                     file_data,
+                    file,
                     start,
                     index,
                     keywords::strToOp(&file_data[start..*index])
@@ -87,17 +147,19 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
             } else {
                 return Ok(tokenize(
                     file_data,
+                    file,
                     start,
                     index,
                     keywords::charToOp(c)
                 ));
             }
         }
-        '(' | ')' | ';' | ',' | ']' | '[' =>{
+        '(' | ')' | ';' | ',' | ']' | '[' | '.' =>{
             *index+=1;
 
             return Ok(tokenize(
                 file_data,
+                file,
                 start,
                 index,
                 keywords::charToSymbol(c)
@@ -105,10 +167,18 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
         }
         '\'' =>{
             if (file_data.len() > *index+2) && file_data.as_bytes()[*index+2] == b'\''{ *index+=3; }
-            else { return Err(LexerError::CharLengthInvalid); }
+            else {
+                // cover up to the closing quote when it is on the same line
+                let len = match file_data[start+1..].find(['\'', '\n']) {
+                    Some(i) if file_data.as_bytes()[start+1+i] == b'\'' => i+2,
+                    _ => 1,
+                };
+                return Err(LexerError::CharLengthInvalid(Span::new(file, start, len)));
+            }
 
             return Ok(tokenize(
                 file_data,
+                file,
                 start,
                 index,
                 TokenType::CharLiteral
@@ -119,23 +189,22 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
 
             while (file_data.len() > *index) && (file_data.as_bytes()[*index]!=b'"') {*index+=1;}
             
-            if file_data.len() <= *index {return Err(LexerError::NonTerminatedString);}
+            if file_data.len() <= *index {return Err(LexerError::NonTerminatedString(Span::new(file, start, 1)));}
             
             *index+=1;
 
             return Ok(tokenize(
                 file_data,
+                file,
                 start,
                 index,
                 TokenType::StringLiteral
             ));
         }
+        // runLexer skips whole comments, one that gets here runs to the end of the file
         '#' =>{
-            *index+=1;
-            while (file_data.len() > *index) && (file_data.as_bytes()[*index]!=b'#') {*index+=1;}
-            if file_data.len() <= *index {return Err(LexerError::NonTerminatedString);}
-            *index+=1;
-            return getNextToken(&file_data, index);
+            *index = file_data.len();
+            return Err(LexerError::NonTerminatedComment(Span::new(file, start, 1)));
         }
         ':' =>{
             if (file_data.len() > *index+1) && file_data.as_bytes()[*index+1] == b':'{ *index+=1; }
@@ -143,6 +212,7 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
 
             return Ok(tokenize(
                 file_data,
+                file,
                 start,
                 index,
                 TokenType::Symbol
@@ -153,13 +223,14 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
             
             c = file_data.as_bytes()[*index] as char;
             if (file_data.len() <= *index+1) || (c != 'A' && c != 'X' && c != 'Y' && c != 'S') {
-                return Err(LexerError::InvalidRegister);
+                return Err(LexerError::InvalidRegister(Span::new(file, start, *index-start+1)));
             }
             
             *index+=1;
 
             return Ok(tokenize(
                 file_data,
+                file,
                 start,
                 index,
                 TokenType::Register
@@ -169,13 +240,14 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
             while (file_data.len() > *index) && (!file_data.as_bytes()[*index].is_ascii_whitespace()) {
                 c = file_data.as_bytes()[*index] as char;
                 match c {
-                    '+' | '-' | '=' | '>' | '<' | '(' | ')' | ';' | ',' | '\'' | '"' | '#' | ':' | '_' | ']' | '[' =>{ break; }
+                    '+' | '-' | '*' | '/' | '%' | '&' | '|' | '^' | '~' | '=' | '>' | '<' | '(' | ')' | ';' | ',' | '\'' | '"' | '#' | ':' | '_' | ']' | '[' | '.' =>{ break; }
                     _ =>{*index+=1;}
                 }
             }
         }
     }
     outTk.tk_data = &file_data[start..*index];
+    outTk.span = Span::new(file, start, *index-start);
 
     use crate::lexer::keywords::sliceToKeyword;
     
@@ -183,15 +255,15 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
         kw if keywords::isKeyword(kw) => sliceToKeyword(kw),
         kw if keywords::isEmbeddedFn(kw) => TokenType::EmbeddedFunction,
         kw if kw.parse::<u16>().is_ok() => TokenType::NumberLiteral,
-        kw if kw.parse::<f64>().is_ok() => return Err(LexerError::InvalidValueSize16b),
+        kw if kw.parse::<f64>().is_ok() => return Err(LexerError::InvalidValueSize16b(Span::new(file, start, kw.len()))),
         kw if kw.starts_with("0x") =>{
             let s = &kw[2..];
-            let number = match u64::from_str_radix(&s, 16) {
+            let number = match u64::from_str_radix(s, 16) {
                 Ok(n) => n,
-                Err(_e) => return Err(LexerError::InvalidHexValue),
+                Err(_e) => return Err(LexerError::InvalidHexValue(Span::new(file, start, kw.len()))),
             };
             if number > u16::MAX as u64 {
-                return Err(LexerError::InvalidValueSize16b);
+                return Err(LexerError::InvalidValueSize16b(Span::new(file, start, kw.len())));
             } 
             TokenType::HexNumberLiteral
         }
@@ -202,11 +274,159 @@ fn getNextToken<'a,'b>(file_data: &'a String, index: &'b mut usize)->Result<Toke
     return Ok(outTk);
 }
 
-pub fn runLexer<'a>(file_contents: &'a String, token_storage: &mut Vec<Token<'a>>)->Result<(), LexerError>{
+// byte offset of the start of every line, used to turn offsets into line & col
+fn lineStarts(file_data: &str) -> Vec<usize> {
+    let mut starts: Vec<usize> = vec![0];
+    for (i, b) in file_data.bytes().enumerate() {
+        if b == b'\n' {starts.push(i+1);}
+    }
+    return starts;
+}
+
+fn locateSpan(line_starts: &[usize], span: &mut Span) {
+    let line = line_starts.partition_point(|&s| s <= span.offset);
+    span.line = line;
+    span.col = span.offset - line_starts[line-1] + 1;
+}
+
+// whether a - after this token is a subtraction rather than the sign of a number
+fn endsValue(tk: Option<&Token<'_>>) -> bool {
+    match tk {
+        Some(tk) => match tk.tk_type {
+            TokenType::NumberLiteral |
+            TokenType::HexNumberLiteral |
+            TokenType::CharLiteral |
+            TokenType::StringLiteral |
+            TokenType::UnidentifiedLabel |
+            TokenType::Register |
+            TokenType::OpIncrement |
+            TokenType::OpDecrement => return true,
+            TokenType::Symbol => return tk.tk_data == ")" || tk.tk_data == "]",
+            _ => return false,
+        },
+        None => return false,
+    }
+}
+
+// -5 and -0x10 are one literal, the number has to be right after the -
+// the lowest a sshort holds is -32768
+fn mergeNegative<'a>(file_contents: &'a str, token_storage: &mut Vec<Token<'a>>, tk: &mut Token<'a>) -> Result<(), LexerError> {
+    if tk.tk_type != TokenType::NumberLiteral && tk.tk_type != TokenType::HexNumberLiteral {return Ok(());}
+    let minus = match token_storage.last() {
+        Some(m) if m.tk_type == TokenType::OpSubtract && m.span.offset+1 == tk.span.offset => *m,
+        _ => return Ok(()),
+    };
+    if endsValue(token_storage.len().checked_sub(2).map(|i| &token_storage[i])) {return Ok(());}
+
+    token_storage.pop();
+    tk.tk_data = &file_contents[minus.span.offset..tk.span.offset+tk.span.len];
+    tk.span = Span {len: tk.span.len+1, ..minus.span};
+    let magnitude = match tk.tk_data[1..].strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).unwrap_or(u32::MAX),
+        None => tk.tk_data[1..].parse::<u32>().unwrap_or(u32::MAX),
+    };
+    if magnitude > 0x8000 {
+        tk.tk_type = TokenType::Invalid;
+        return Err(LexerError::InvalidValueSize16b(tk.span));
+    }
+    return Ok(());
+}
+
+// the body of a raw or asm block is KASM, every word up to the end is kept as written
+// a ; is split off so a name before it can be swapped for its operand, "strings" stay one word
+fn lexAsmBody<'a>(file_contents: &'a str, file: usize, line_starts: &[usize], index: &mut usize, token_storage: &mut Vec<Token<'a>>, errors: &mut Vec<LexerError>) {
+    let bytes = file_contents.as_bytes();
+    loop {
+        while *index < bytes.len() && bytes[*index].is_ascii_whitespace() {*index+=1;}
+        if *index >= bytes.len() {return;}
+        let start = *index;
+        let mut tk = match bytes[start] {
+            b'#' => {
+                *index+=1;
+                while *index < bytes.len() && bytes[*index] != b'#' {*index+=1;}
+                if *index >= bytes.len() {
+                    let mut span = Span::new(file, start, 1);
+                    locateSpan(line_starts, &mut span);
+                    errors.push(LexerError::NonTerminatedComment(span));
+                    return;
+                }
+                *index+=1;
+                continue;
+            }
+            b'"' => {
+                *index+=1;
+                while *index < bytes.len() && bytes[*index] != b'"' {*index+=1;}
+                if *index >= bytes.len() {
+                    let mut span = Span::new(file, start, 1);
+                    locateSpan(line_starts, &mut span);
+                    errors.push(LexerError::NonTerminatedString(span));
+                    return;
+                }
+                *index+=1;
+                tokenize(file_contents, file, start, index, TokenType::AsmText)
+            }
+            _ => {
+                while *index < bytes.len() && !bytes[*index].is_ascii_whitespace() && bytes[*index] != b'#' {*index+=1;}
+                // the end is lexed like any other keyword
+                if &file_contents[start..*index] == "end" {
+                    *index = start;
+                    return;
+                }
+                if *index-start > 1 && bytes[*index-1] == b';' {*index-=1;}
+                tokenize(file_contents, file, start, index, TokenType::AsmText)
+            }
+        };
+        locateSpan(line_starts, &mut tk.span);
+        token_storage.push(tk);
+    }
+}
+
+// whitespace and # comments # before the next token, a comment with no closing # is left for getNextToken to report
+fn skipBlank(bytes: &[u8], index: &mut usize) {
+    loop {
+        while *index < bytes.len() && bytes[*index].is_ascii_whitespace() {*index+=1;}
+        if *index >= bytes.len() || bytes[*index] != b'#' {return;}
+        match bytes[*index+1..].iter().position(|b| *b == b'#') {
+            Some(len) => *index += len + 2,
+            None => return,
+        }
+    }
+}
+
+// errors are collected into `errors`, the failed lexeme is kept as an Invalid token
+// so the parser can drop the statement it was in
+pub fn runLexer<'a>(file_contents: &'a str, file: usize, token_storage: &mut Vec<Token<'a>>, errors: &mut Vec<LexerError>){
+    let line_starts = lineStarts(file_contents);
     let mut index: usize = 0;
     while file_contents.len() > index {
-        let tk: Token = getNextToken(&file_contents, &mut index)?;
+        // trailing whitespace and comments would otherwise run getNextToken off the end
+        skipBlank(file_contents.as_bytes(), &mut index);
+        if index >= file_contents.len() {break;}
+        let start = index;
+        let mut tk: Token = match getNextToken(file_contents, file, &mut index) {
+            Ok(tk) => tk,
+            Err(mut e) => {
+                let mut span = e.span().unwrap_or(Span::new(file, start, 1));
+                if let Some(s) = e.span_mut() {
+                    locateSpan(&line_starts, s);
+                    span = *s;
+                }
+                errors.push(e);
+
+                // always make progress past the bad lexeme
+                index = index.max(span.offset + span.len).max(start + 1).min(file_contents.len());
+                Token {
+                    tk_data: &file_contents[span.offset.min(index)..index],
+                    tk_type: TokenType::Invalid,
+                    tk_comp_data: TokenCompData::None,
+                    span
+                }
+            }
+        };
+        locateSpan(&line_starts, &mut tk.span);
+        if let Err(e) = mergeNegative(file_contents, token_storage, &mut tk) {errors.push(e);}
+        let body = tk.tk_type == TokenType::KeywordRaw || tk.tk_type == TokenType::KeywordAsm;
         token_storage.push(tk);
+        if body {lexAsmBody(file_contents, file, &line_starts, &mut index, token_storage, errors);}
     }
-    return Ok(());
 }
\ No newline at end of file
@@ -1,7 +1,8 @@
 pub static KEYWORDS: &[&str] = &[
     "static", "string", "const", "short", "buffer", "heap", 
     "LABEL", "raw", "end", "proc", "ret",
-    "while", "if", "else", "void", "uint"
+    "while", "for", "if", "elif", "else", "break", "continue", "void", "uint", "int", "sshort", "char", "struct", "ptr",
+    "import", "pub", "asm", "enum"
 ];
 
 pub static EMBEDDED_FNS: &[&str] = &[
@@ -9,7 +10,7 @@ pub static EMBEDDED_FNS: &[&str] = &[
 ];
 
 pub static SYMBOLS: &[&str] = &[
-    ";", ":", "(", ")", ",", "[", "]"
+    ";", ":", "(", ")", ",", "[", "]", "."
 ];
 
 use crate::lexer::TokenType;
@@ -24,14 +25,27 @@ pub fn sliceToKeyword(s: &str) -> TokenType {
         "heap" => return TokenType::KeywordHeap,
         "LABEL" => return TokenType::KeywordLABEL,
         "raw" => return TokenType::KeywordRaw,
+        "asm" => return TokenType::KeywordAsm,
         "end" => return TokenType::KeywordEnd,
         "proc" => return TokenType::KeywordProc,
         "ret" => return TokenType::KeywordRet,
         "while" => return TokenType::KeywordWhile,
+        "for" => return TokenType::KeywordFor,
         "if" => return TokenType::KeywordIf,
+        "elif" => return TokenType::KeywordElif,
         "else" => return TokenType::KeywordElse,
+        "break" => return TokenType::KeywordBreak,
+        "continue" => return TokenType::KeywordContinue,
         "void" => return TokenType::KeywordVoid,
         "uint" => return TokenType::KeywordUint,
+        "int" => return TokenType::KeywordInt,
+        "sshort" => return TokenType::KeywordSShort,
+        "char" => return TokenType::KeywordChar,
+        "struct" => return TokenType::KeywordStruct,
+        "enum" => return TokenType::KeywordEnum,
+        "ptr" => return TokenType::KeywordPtr,
+        "import" => return TokenType::KeywordImport,
+        "pub" => return TokenType::KeywordPub,
         &_ => todo!(),
     }
 }
@@ -61,6 +75,13 @@ pub fn charToOp(c: char) -> TokenType {
     match c{
         '+' => return TokenType::OpAdd,
         '-' => return TokenType::OpSubtract,
+        '*' => return TokenType::OpMultiply,
+        '/' => return TokenType::OpDivide,
+        '%' => return TokenType::OpModulo,
+        '&' => return TokenType::OpAnd,
+        '|' => return TokenType::OpOr,
+        '^' => return TokenType::OpXor,
+        '~' => return TokenType::OpBitNot,
         '=' => return TokenType::OpAssign,
         '!' => return TokenType::OpNot,
         '<' => return TokenType::OpLess,
@@ -75,6 +96,22 @@ pub fn strToOp(s: &str) -> TokenType {
         ">=" => return TokenType::OpGreatEq,
         "<=" => return TokenType::OpLessEq,
         "!=" => return TokenType::OpNEq,
+        "<<" => return TokenType::OpShiftLeft,
+        ">>" => return TokenType::OpShiftRight,
+        "&&" => return TokenType::OpLogicalAnd,
+        "||" => return TokenType::OpLogicalOr,
+        "++" => return TokenType::OpIncrement,
+        "--" => return TokenType::OpDecrement,
+        "+=" => return TokenType::OpAddAssign,
+        "-=" => return TokenType::OpSubtractAssign,
+        "*=" => return TokenType::OpMultiplyAssign,
+        "/=" => return TokenType::OpDivideAssign,
+        "%=" => return TokenType::OpModuloAssign,
+        "&=" => return TokenType::OpAndAssign,
+        "|=" => return TokenType::OpOrAssign,
+        "^=" => return TokenType::OpXorAssign,
+        "<<=" => return TokenType::OpShiftLeftAssign,
+        ">>=" => return TokenType::OpShiftRightAssign,
         &_ => return TokenType::Op,
     }
 }
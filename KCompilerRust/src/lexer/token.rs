@@ -1,11 +1,16 @@
+use crate::parser::parserTree::DataValueType;
+
 //Token type enum
 #[derive(Default, Debug, PartialEq, Clone, Copy)]
 pub enum TokenType{
     #[default]
     None,
+    Invalid, //lexeme the lexer failed on, already reported
     Register,
     UnidentifiedLabel,
     Variable,
+    Field, //name after a ., resolved against the struct by the compiler
+    AsmText, //word of a raw or asm block, kept as it is written
     ProcedureCall,
     CharLiteral,
     StringLiteral,
@@ -17,7 +22,30 @@ pub enum TokenType{
     OpAssign,
     OpAdd,
     OpSubtract,
+    OpMultiply,
+    OpDivide,
+    OpModulo,
+    OpAnd,
+    OpOr,
+    OpXor,
+    OpBitNot,
+    OpShiftLeft,
+    OpShiftRight,
+    OpIncrement,
+    OpDecrement,
+    OpAddAssign,
+    OpSubtractAssign,
+    OpMultiplyAssign,
+    OpDivideAssign,
+    OpModuloAssign,
+    OpAndAssign,
+    OpOrAssign,
+    OpXorAssign,
+    OpShiftLeftAssign,
+    OpShiftRightAssign,
     OpNot,
+    OpLogicalAnd,
+    OpLogicalOr,
     OpEq,
     OpGreatEq,
     OpLessEq,
@@ -27,19 +55,32 @@ pub enum TokenType{
     KeywordString,
     KeywordUint,
     KeywordShort,
+    KeywordInt,
+    KeywordSShort,
+    KeywordChar,
+    KeywordStruct,
+    KeywordEnum,
+    KeywordPtr,
     KeywordBuffer,
     KeywordStatic,
     KeywordHeap,
     KeywordConst,
     KeywordLABEL,
     KeywordRaw,
+    KeywordAsm,
     KeywordEnd,
     KeywordProc,
     KeywordRet,
     KeywordWhile,
+    KeywordFor,
     KeywordIf,
+    KeywordElif,
     KeywordElse,
+    KeywordBreak,
+    KeywordContinue,
     KeywordVoid,
+    KeywordImport,
+    KeywordPub,
     EmbeddedFunction
 }
 
@@ -66,7 +107,11 @@ pub enum VarDest {
 pub enum TokenCompData{
     #[default]
     None,
-    Var(usize,VarDest)
+    Var(usize,VarDest),
+    Type(DataValueType), //type of a ptr<...> or enum cast, filled in by the parser
+    Const(i32), //value of the const the token names, the token is used as a number literal
+    Member(usize, i32), //Dir::UP, the enum in Program::enums and the value of the member
+    Proc(usize) //the proc a call names in Program::procs, names are only unique per file
 }
 
 impl TokenCompData {
@@ -76,6 +121,47 @@ impl TokenCompData {
             _ => None,
         }
     }
+
+    pub fn proc(self) -> Option<usize> {
+        match self {
+            TokenCompData::Proc(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    // the value put in for a const or an enum member
+    pub fn constant(self) -> Option<i32> {
+        match self {
+            TokenCompData::Const(c) |
+            TokenCompData::Member(_, c) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+// where a token (or anything built from tokens) sits in its source file
+// line and col start at 1, a line of 0 means the span was never located
+#[derive(Default, Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub file: usize, //index into the loaded source files
+    pub offset: usize, //byte offset from the start of the file
+    pub len: usize,
+    pub line: usize,
+    pub col: usize
+}
+
+impl Span {
+    pub fn new(file: usize, offset: usize, len: usize) -> Span {
+        return Span { file, offset, len, line: 0, col: 0 };
+    }
+
+    // span covering both self and other, assumes both are in the same file
+    pub fn to(self, other: Span) -> Span {
+        if other.offset < self.offset {return other.to(self);}
+        let mut s = self;
+        s.len = (other.offset + other.len).max(self.offset + self.len) - self.offset;
+        return s;
+    }
 }
 
 #[derive(Default, Debug, PartialEq, Copy, Clone)]
@@ -83,4 +169,5 @@ pub struct Token<'a>{
     pub tk_data: &'a str,
     pub tk_type: TokenType,
     pub tk_comp_data: TokenCompData,
+    pub span: Span,
 }
\ No newline at end of file
@@ -2,19 +2,24 @@
 #![allow(
     non_snake_case,
     unused_imports,
-    dead_code
+    dead_code,
+    clippy::needless_return //every function ends in an explicit return
 )]
 
 pub mod lexer;
 pub mod parser;
 pub mod compiler;
+pub mod checker;
+pub mod diagnostics;
+pub mod modules;
 
 use crate::lexer::LexerError;
+use crate::diagnostics::{SourceFile, Diagnostics};
 use std::fs::*;
 use std::io::Read;
 use std::env;
 
-fn loadFile<'a>(file_name: &'a str)->Result<String, LexerError>{
+fn loadFile(file_name: &str)->Result<String, LexerError>{
     let mut file_bundle = File::open(file_name)?;
     let mut file_data = String::new();
     file_bundle.read_to_string(&mut file_data)?;
@@ -41,26 +46,61 @@ fn main() -> Result<(), Box<dyn Error>> {
             return Err(Box::new(e));
         }
     };
+    let mut sources: Vec<SourceFile> = vec![SourceFile {name: file_name.to_string(), contents: file_contents}];
+
+    let mut diagnostics: Diagnostics = Default::default();
+
+    // imported files are lexed before the files that import them, so their procs are declared first
+    let mut moduleErrors : Vec<modules::ModuleError> = vec![];
+    let order = modules::loadImports(&mut sources, & mut moduleErrors);
+    for e in moduleErrors {
+        diagnostics.error(e.to_string(), Some(e.span()));
+    }
+
     let mut token_storage : Vec<lexer::Token<'_>> = Default::default();
-    let _lexerOutput = match lexer::runLexer(&file_contents, & mut token_storage) {
-        Ok(output) => output,
-        Err(e) => {println!("Lexer failed: {e}"); return Err(Box::new(e));}
-    };
+    let mut lexerErrors : Vec<LexerError> = vec![];
+    for file in order {
+        lexer::runLexer(&sources[file].contents, file, & mut token_storage, & mut lexerErrors);
+    }
+    for e in lexerErrors {
+        diagnostics.error(e.to_string(), e.span());
+    }
     
     let mut parserWarnings : Vec<parser::ParserWarning> = vec![];
-    let mut program : crate::parser::Program<'_> = Default::default();
-    let _parserOutput = match parser::runParser(&mut token_storage, program, & mut parserWarnings) {
-        Ok(output) => program = output,
-        Err(e) => {println!("Parser failed: {e}"); return Err(Box::new(e));}
-    };
+    let mut parserErrors : Vec<parser::ParserError> = vec![];
+    let program = parser::runParser(&mut token_storage, Default::default(), & mut parserWarnings, & mut parserErrors);
+    for e in parserErrors {
+        diagnostics.error(e.to_string(), Some(e.span()));
+    }
     for w in parserWarnings {
-        println!("Parser Threw Warning: {w}");
+        diagnostics.warning(w.to_string(), Some(w.span()));
     }
     
-    match compiler::runCompiler(program, &out_file_name) {
-        Ok(output) => output,
-        Err(e) => {println!("Compiler failed: {e}"); return Err(Box::new(e));}
+    let mut checkerErrors : Vec<checker::CheckerError> = vec![];
+    checker::runChecker(&program, & mut checkerErrors);
+    for e in checkerErrors {
+        diagnostics.error(e.to_string(), Some(e.span()));
+    }
+
+    // codegen expects a program that parsed and checked cleanly
+    if !diagnostics.hasErrors() {
+        let mut compilerErrors : Vec<compiler::CompilerError> = vec![];
+        let kasm = compiler::runCompiler(program, & mut compilerErrors);
+        for e in compilerErrors {
+            diagnostics.error(e.to_string(), e.span());
+        }
+
+        if !diagnostics.hasErrors() {
+            if let Err(e) = compiler::writeProgram(&kasm, out_file_name) {
+                diagnostics.error(e.to_string(), e.span());
+            }
+        }
+    }
+
+    diagnostics.report(&sources);
+    if diagnostics.hasErrors() {
+        std::process::exit(1);
     }
 
     return Ok(());
-}
\ No newline at end of file
+}
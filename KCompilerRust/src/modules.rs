@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+//Custom Error Implementation
+use thiserror::Error;
+
+use crate::lexer::{self, Span, Token, TokenType};
+use crate::diagnostics::SourceFile;
+
+#[derive(Debug, Error)]
+pub enum ModuleError {
+    #[error("(ModuleError) Unable to read imported file '{0}'.")]
+    UnableToRead(String, Span),
+
+    #[error("(ModuleError) Import cycle, {0} imports itself again through {1}.")]
+    ImportCycle(String, String, Span),
+}
+
+impl ModuleError {
+    pub fn span(&self) -> Span {
+        match self {
+            ModuleError::UnableToRead(.., s) |
+            ModuleError::ImportCycle(.., s) => *s,
+        }
+    }
+}
+
+// the import "file.k"; lines of a file, path without the quotes
+fn imports(source: &SourceFile, file: usize) -> Vec<(String, Span)> {
+    let mut tokens: Vec<Token<'_>> = vec![];
+    // lex errors show up when the file is lexed for real
+    lexer::runLexer(&source.contents, file, &mut tokens, &mut vec![]);
+    let mut found: Vec<(String, Span)> = vec![];
+    for i in 1..tokens.len() {
+        if tokens[i-1].tk_type == TokenType::KeywordImport && tokens[i].tk_type == TokenType::StringLiteral {
+            let data = tokens[i].tk_data;
+            found.push((data[1..data.len()-1].to_string(), tokens[i].span));
+        }
+    }
+    return found;
+}
+
+fn loadFile(sources: &mut Vec<SourceFile>, file: usize, paths: &mut Vec<Option<PathBuf>>, loading: &mut Vec<usize>, order: &mut Vec<usize>, errors: &mut Vec<ModuleError>) {
+    loading.push(file);
+    let dir = Path::new(&sources[file].name).parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    for (path, span) in imports(&sources[file], file) {
+        let name = dir.join(&path).to_string_lossy().to_string();
+        let canonical = fs::canonicalize(&name).ok();
+        // the same file through another path is still the same file
+        let known = (0..sources.len()).find(|&i| {
+            sources[i].name == name || (canonical.is_some() && paths[i] == canonical)
+        });
+        match known {
+            Some(i) if loading.contains(&i) => {
+                let chain: Vec<&str> = loading[loading.iter().position(|&l| l == i).unwrap()..].iter()
+                    .map(|&l| sources[l].name.as_str()).collect();
+                errors.push(ModuleError::ImportCycle(sources[i].name.clone(), chain.join(" -> "), span));
+            }
+            // already loaded, its tokens are in front
+            Some(_) => (),
+            None => match fs::read_to_string(&name) {
+                Ok(contents) => {
+                    sources.push(SourceFile {name, contents});
+                    paths.push(canonical);
+                    loadFile(sources, sources.len()-1, paths, loading, order, errors);
+                }
+                Err(_) => errors.push(ModuleError::UnableToRead(name, span)),
+            }
+        }
+    }
+    loading.pop();
+    order.push(file);
+}
+
+// loads every file the first source imports, the returned order has each file after the files it imports
+pub fn loadImports(sources: &mut Vec<SourceFile>, errors: &mut Vec<ModuleError>) -> Vec<usize> {
+    let mut paths: Vec<Option<PathBuf>> = sources.iter().map(|s| fs::canonicalize(&s.name).ok()).collect();
+    let mut order: Vec<usize> = vec![];
+    loadFile(sources, 0, &mut paths, &mut vec![], &mut order, errors);
+    return order;
+}
@@ -4,125 +4,438 @@ use thiserror::Error;
 
 pub mod parserTree;
 pub use crate::parser::parserTree::*;
+pub mod exprParser;
+use crate::parser::exprParser::buildExpressionTree;
 
 use crate::lexer::LexerError;
 use crate::lexer::VarDest;
 use crate::lexer::TokenCompData;
+use crate::lexer::Span;
+use std::iter::Peekable;
+use std::slice::IterMut;
 
 #[derive(Debug, Error, Clone)]
 pub enum ParserError {
     #[error("(ParserError) Unidentified error thrown. Reconsider life.")]
-    UnidentifiedError,
+    UnidentifiedError(Span),
 
     #[error("(ParserError) Unidentified token '{0}'.")]
-    UnidentifiedToken(String),
+    UnidentifiedToken(String, Span),
     
     #[error("(ParserError) Type '{0}' does not exist.")]
-    UnidentifiedType(String),
+    UnidentifiedType(String, Span),
 
     #[error("(ParserError) Missing para in procedure definition.")]
-    NoParaProcDef,
+    NoParaProcDef(Span),
 
     #[error("(ParserError) Missing para in conditional block definition.")]
-    NoParaConBlockDef,
+    NoParaConBlockDef(Span),
 
     #[error("(ParserError) Para not terminated.")]
-    NoParaNotTerminated,
+    NoParaNotTerminated(Span),
 
     #[error("(ParserError) Ran into an excessive end statement.")]
-    ExcessiveEndStatement,
+    ExcessiveEndStatement(Span),
 
     #[error("(ParserError) Missing end statement.")]
-    MissingEndStatement,
+    MissingEndStatement(Span),
 
     #[error("(ParserError) Missing ret statement in proc '{0}'.")]
-    MissingReturn(String),
+    MissingReturn(String, Span),
 
     #[error("(ParserError) Missing terminating square bracket.")]
-    MissingClosingSqBracket,
+    MissingClosingSqBracket(Span),
 
     #[error("(ParserError) Missing size in buffer declaration, this is done by '[SIZE]'.")]
-    MissingSizeForBuffer,
+    MissingSizeForBuffer(Span),
 
     #[error("(ParserError) Size for buffer declaration is not a number, note hexidecimal is does not count.")]
-    MissingSizeForBufferNotNumber,
+    MissingSizeForBufferNotNumber(Span),
+
+    #[error("(ParserError) Array size '{0}' is not a number or the array is over 255 bytes.")]
+    InvalidArraySize(String, Span),
 
     #[error("(ParserError) Symbol, keyword, or value incorrectly placed in expression.")]
-    SymbolIncorrectlyInExpression,
+    SymbolIncorrectlyInExpression(Span),
 
     #[error("(ParserError) Unnecessary semicolon.")]
-    UnnecessarySemicolon,
+    UnnecessarySemicolon(Span),
 
     #[error("(ParserError) Attempted to put an expression outside of a procedure.")]
-    AttemptedExpressionInProgram,
+    AttemptedExpressionInProgram(Span),
 
     #[error("(ParserError) Attempted to put a(n) '{0:?}' block outside of a procedure.")]
-    AttemptedBlockInProgram(BlockType),
+    AttemptedBlockInProgram(BlockType, Span),
 
     #[error("(ParserError) Attempted to put the variable '{0}' outside of a procedure.")]
-    AttemptedVariableInProgram(String),
+    AttemptedVariableInProgram(String, Span),
 
     #[error("(ParserError) Invalid assignment to '{0}'.")]
-    InvalidAssignment(String),
+    InvalidAssignment(String, Span),
 
     #[error("(ParserError) Label '{0}' could not be identified.")]
-    UnidentifiedLabel(String),
+    UnidentifiedLabel(String, Span),
 
     #[error("(ParserError) The type '{0:?}' was placed in the middle of an expression.")]
-    TypeInExpression(DataValueType),
+    TypeInExpression(DataValueType, Span),
 
     #[error("(ParserError) Value '{0}' found outside of expression.")]
-    StrayValue(String),
+    StrayValue(String, Span),
 
     #[error("(ParserError) Assignment to nothing.")]
-    StrayAssignment,
+    StrayAssignment(Span),
 
     #[error("(ParserError) Operator '{0}' not within expression or missing oprehands.")]
-    StrayOperator(String),
+    StrayOperator(String, Span),
 
     #[error("(ParserError) Else not attacthed to if or elif block.")]
-    HangingElse,
+    HangingElse(Span),
+
+    #[error("(ParserError) Elif not attached to if or elif block.")]
+    HangingElif(Span),
 
     #[error("(ParserError) Variable '{0}' is redefined.")]
-    RedefinitionOfVariable(String),
+    RedefinitionOfVariable(String, Span),
 
     #[error("(ParserError) Const variable '{0}' lacks an initial value (required).")]
-    ConstNoInitial(String),
+    ConstNoInitial(String, Span),
 
     #[error("(ParserError) Unimplemented DataAllocationType {0:?}, couldn't allocate the space.")]
-    UnimplementedDataAllocType(DataAllocationType),
+    UnimplementedDataAllocType(DataAllocationType, Span),
 
     #[error("(ParserError) Unimplemented VarDest {0:?}, couldn't query variable.")]
-    UnimplementedVarDestType(VarDest),
+    UnimplementedVarDestType(VarDest, Span),
 
     #[error("(ParserError) Unimplemented DataValueType {0:?}, couldn't find size.")]
-    UnimplementedDataValueType(DataValueType),
+    UnimplementedDataValueType(DataValueType, Span),
 
     #[error("(ParserError) Unimplemented BlockType {0:?}, couldn't create block.")]
-    UnimplementedBlockType(BlockType),
+    UnimplementedBlockType(BlockType, Span),
+
+    #[error("(ParserError) Missing para in call to '{0}'.")]
+    NoParaProcCall(String, Span),
+
+    #[error("(ParserError) Expected a value but found '{0}'.")]
+    ExpectedValue(String, Span),
+
+    #[error("(ParserError) Unexpected '{0}' in expression, missing an operator?")]
+    UnexpectedInExpression(String, Span),
+
+    #[error("(ParserError) Expression ended while still expecting a value.")]
+    UnexpectedEndOfExpression(Span),
+
+    #[error("(ParserError) '{0}' used outside of a loop.")]
+    LoopControlOutsideLoop(String, Span),
+
+    #[error("(ParserError) '{0}' has to be a statement of its own, it can not be used as a value.")]
+    StepAsValue(String, Span),
+
+    #[error("(ParserError) A struct can only be declared outside of a procedure.")]
+    StructInProc(Span),
+
+    #[error("(ParserError) Only fields can be declared in a struct, found '{0}'.")]
+    UnexpectedInStruct(String, Span),
+
+    #[error("(ParserError) Expected a field name after '.', found '{0}'.")]
+    ExpectedField(String, Span),
+
+    #[error("(ParserError) Expected a type between < and > after ptr, found '{0}'.")]
+    ExpectedPointerType(String, Span),
+
+    #[error("(ParserError) An import is written as import \"file.k\"; outside of any procedure.")]
+    InvalidImport(Span),
+
+    #[error("(ParserError) The value of const '{0}' has to be known at compile time, a literal, another const or arithmetic on them.")]
+    ConstNotConstant(String, Span),
+
+    #[error("(ParserError) The const value {0} does not fit in a {1:?}.")]
+    ConstOutOfRange(i32, DataValueType, Span),
+
+    #[error("(ParserError) An enum is written as enum Name: uint A = 0 B C end outside of any procedure, a value has to be a number or a const.")]
+    InvalidEnum(Span),
+
+    #[error("(ParserError) Enum '{0}' has no member '{1}'.")]
+    UnknownEnumMember(String, String, Span),
+
+    #[error("(ParserError) Raw data is written as LABEL name raw ... end outside of any procedure.")]
+    InvalidRawLabel(Span),
+
+    #[error("(ParserError) asm ... end can only be a statement inside of a procedure.")]
+    InvalidAsm(Span),
+
+    #[error("(ParserError) pub can only be put before a proc, struct, enum, heap, static, const or LABEL outside of a procedure.")]
+    MisplacedPub(Span),
+
+    #[error("(ParserError) '{0}' is not pub in the file it is declared in.")]
+    NotPublic(String, Span),
+
+    #[error("(ParserError) Procedure '{0}' is redefined.")]
+    RedefinitionOfProc(String, Span),
 
     #[error("{0}")]
-    LexError(String)
+    LexError(String, Span)
+}
+
+impl ParserError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParserError::UnidentifiedError(.., s) |
+            ParserError::UnidentifiedToken(.., s) |
+            ParserError::UnidentifiedType(.., s) |
+            ParserError::NoParaProcDef(.., s) |
+            ParserError::NoParaConBlockDef(.., s) |
+            ParserError::NoParaNotTerminated(.., s) |
+            ParserError::ExcessiveEndStatement(.., s) |
+            ParserError::MissingEndStatement(.., s) |
+            ParserError::MissingReturn(.., s) |
+            ParserError::MissingClosingSqBracket(.., s) |
+            ParserError::MissingSizeForBuffer(.., s) |
+            ParserError::MissingSizeForBufferNotNumber(.., s) |
+            ParserError::InvalidArraySize(.., s) |
+            ParserError::SymbolIncorrectlyInExpression(.., s) |
+            ParserError::UnnecessarySemicolon(.., s) |
+            ParserError::AttemptedExpressionInProgram(.., s) |
+            ParserError::AttemptedBlockInProgram(.., s) |
+            ParserError::AttemptedVariableInProgram(.., s) |
+            ParserError::InvalidAssignment(.., s) |
+            ParserError::UnidentifiedLabel(.., s) |
+            ParserError::TypeInExpression(.., s) |
+            ParserError::StrayValue(.., s) |
+            ParserError::StrayAssignment(.., s) |
+            ParserError::StrayOperator(.., s) |
+            ParserError::HangingElse(.., s) |
+            ParserError::HangingElif(.., s) |
+            ParserError::RedefinitionOfVariable(.., s) |
+            ParserError::ConstNoInitial(.., s) |
+            ParserError::UnimplementedDataAllocType(.., s) |
+            ParserError::UnimplementedVarDestType(.., s) |
+            ParserError::UnimplementedDataValueType(.., s) |
+            ParserError::UnimplementedBlockType(.., s) |
+            ParserError::NoParaProcCall(.., s) |
+            ParserError::ExpectedValue(.., s) |
+            ParserError::UnexpectedInExpression(.., s) |
+            ParserError::UnexpectedEndOfExpression(.., s) |
+            ParserError::LoopControlOutsideLoop(.., s) |
+            ParserError::StepAsValue(.., s) |
+            ParserError::StructInProc(.., s) |
+            ParserError::UnexpectedInStruct(.., s) |
+            ParserError::ExpectedField(.., s) |
+            ParserError::ExpectedPointerType(.., s) |
+            ParserError::InvalidImport(.., s) |
+            ParserError::ConstNotConstant(.., s) |
+            ParserError::ConstOutOfRange(.., s) |
+            ParserError::InvalidEnum(.., s) |
+            ParserError::UnknownEnumMember(.., s) |
+            ParserError::InvalidRawLabel(.., s) |
+            ParserError::InvalidAsm(.., s) |
+            ParserError::MisplacedPub(.., s) |
+            ParserError::NotPublic(.., s) |
+            ParserError::RedefinitionOfProc(.., s) |
+            ParserError::LexError(.., s) => *s,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum ParserWarning {
     #[error("(ParserWarning) Unidentified warning.")]
-    WarningUnidentified,
+    WarningUnidentified(Span),
 
     #[error("(ParserWarning) Possible stray value of '{0}'.")]
-    WarningPossibleStrayValue(String),
+    WarningPossibleStrayValue(String, Span),
 
     #[error("(ParserWarning) Variable '{0}' is defined without initial value being assigned.")]
-    WarningNoInitialValue(String),
+    WarningNoInitialValue(String, Span),
+}
+
+impl ParserWarning {
+    pub fn span(&self) -> Span {
+        match self {
+            ParserWarning::WarningUnidentified(.., s) |
+            ParserWarning::WarningPossibleStrayValue(.., s) |
+            ParserWarning::WarningNoInitialValue(.., s) => *s,
+        }
+    }
+}
+
+// part of a for header being read, for (init; con; step)
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ForPart {
+    None,
+    Init,
+    Con,
+    Step
+}
+
+// the block an if chain hangs off, None when it is the proc
+fn chainParent(program: &Program<'_>, directory: &[BlockParent]) -> Option<Vec<BlockParent>> {
+    let mut dir = directory.to_vec();
+    while dir.len() > 1 && program.getBlock(&dir).block_type == BlockType::Elif {dir.pop();}
+    if dir.len() > 1 && program.getBlock(&dir).block_type == BlockType::If {dir.pop();}
+    if dir.len() > 1 {return Some(dir);}
+    return None;
+}
+
+// a type between ( and ) inside an expression is a cast, not a declaration
+fn isCast(expr: &Option<Expression<'_>>, next: Option<&Token<'_>>) -> bool {
+    let opened = match expr {
+        Some(exp) => exp.tks.last().is_some_and(|t| t.tk_type == TokenType::Symbol && t.tk_data == "("),
+        None => false,
+    };
+    return opened && next.is_some_and(|t| t.tk_type == TokenType::Symbol && t.tk_data == ")");
+}
+
+// what another file declares can only be used when it is pub
+fn checkVisible(module: usize, public: bool, tk: &Token<'_>) -> Result<(), ParserError> {
+    if public || module == tk.span.file {return Ok(());}
+    return Err(ParserError::NotPublic(tk.tk_data.to_string(), tk.span));
+}
+
+// the struct the token names
+fn findStruct(program: &Program<'_>, tk: &Token<'_>) -> Result<Option<usize>, ParserError> {
+    match findSymbol(&program.structs, tk.tk_data, tk.span.file) {
+        Some(i) => {
+            checkVisible(program.structs[i].module, program.structs[i].public, tk)?;
+            return Ok(Some(i));
+        }
+        None => return Ok(None),
+    }
+}
+
+// the enum the token names
+fn findEnum(program: &Program<'_>, tk: &Token<'_>) -> Result<Option<usize>, ParserError> {
+    match findSymbol(&program.enums, tk.tk_data, tk.span.file) {
+        Some(i) => {
+            checkVisible(program.enums[i].module, program.enums[i].public, tk)?;
+            return Ok(Some(i));
+        }
+        None => return Ok(None),
+    }
+}
+
+// a type written by its name, a number type or an enum
+fn namedType(program: &Program<'_>, tk: &Token<'_>) -> Result<DataValueType, ParserError> {
+    match findEnum(program, tk)? {
+        Some(i) => return Ok(program.enumType(i)),
+        None => return toValueType(tk.tk_data, tk.span),
+    }
+}
+
+// a const is worked out when it is declared, a value that can not be used is reported and left as 0
+fn constValue(exp: &Expression<'_>, v: DataValueType, errors: &mut Vec<ParserError>) -> i32 {
+    let value = match exp.tree {
+        Some(ExprNode::Assign(_, ref value, _)) => value,
+        _ => return 0,
+    };
+    match value.constant() {
+        Some(c) if v.fits(c) => return c,
+        Some(c) => {
+            errors.push(ParserError::ConstOutOfRange(c, v, value.span()));
+            return c;
+        }
+        None => {
+            errors.push(ParserError::ConstNotConstant(exp.tks[0].tk_data.to_string(), value.span()));
+            return 0;
+        }
+    }
 }
 
-pub fn runParser<'a>(token_storage: &'a mut [Token<'a>], mut program: Program<'a>, warnings: & mut Vec<ParserWarning>)->Result<Program<'a>, ParserError>{
+// a size in [], a number or the name of a const
+fn constNumber(program: &Program<'_>, tk: &Token<'_>) -> Result<Option<i32>, ParserError> {
+    if let Some(c) = literalNumber(tk) {return Ok(Some(c));}
+    match findSymbol(&program.const_variables, tk.tk_data, tk.span.file).map(|i| &program.const_variables[i]) {
+        Some(v) => {
+            checkVisible(v.module, v.public, tk)?;
+            return Ok(v.value);
+        }
+        None => return Ok(None),
+    }
+}
+
+// drops the words of a raw or asm block in the wrong place, so its end does not close anything else
+fn skipAsmBody<'a>(tk_iter: &mut Peekable<IterMut<'a, Token<'a>>>) {
+    while tk_iter.next_if(|t| t.tk_type == TokenType::AsmText).is_some() {}
+    tk_iter.next_if(|t| t.tk_type == TokenType::KeywordEnd);
+}
+
+// the next token, the file ending after the token at span leaves its statement without an end
+fn nextToken<'a>(tk_iter: &mut Peekable<IterMut<'a, Token<'a>>>, span: Span) -> Result<&'a mut Token<'a>, ParserError> {
+    return tk_iter.next().ok_or(ParserError::MissingEndStatement(span));
+}
+
+fn peekToken<'i, 'a>(tk_iter: &'i mut Peekable<IterMut<'a, Token<'a>>>, span: Span) -> Result<&'i &'a mut Token<'a>, ParserError> {
+    return tk_iter.peek().ok_or(ParserError::MissingEndStatement(span));
+}
+
+// drops the rest of an enum that can not be declared, up to and with its end
+fn skipEnumBody<'a>(tk_iter: &mut Peekable<IterMut<'a, Token<'a>>>) {
+    for t in tk_iter.by_ref() {
+        if t.tk_type == TokenType::KeywordEnd {break;}
+    }
+}
+
+// the type of ptr<...>, the ptr token has already been taken
+// the >> ending ptr<ptr<uint>> is split, the second > is left for the outer pointer
+fn pointerType<'a>(
+    span: Span,
+    tk_iter: &mut Peekable<IterMut<'a, Token<'a>>>,
+    program: &mut Program<'a>
+) -> Result<DataValueType, ParserError> {
+    match tk_iter.next() {
+        Some(tk) if tk.tk_type == TokenType::OpLess => (),
+        Some(tk) => return Err(ParserError::ExpectedPointerType(tk.tk_data.to_string(), tk.span)),
+        None => return Err(ParserError::ExpectedPointerType(String::new(), span)),
+    }
+    let tk = match tk_iter.next() {
+        Some(tk) => tk,
+        None => return Err(ParserError::ExpectedPointerType(String::new(), span)),
+    };
+    let v = match tk.tk_type {
+        TokenType::KeywordPtr => pointerType(tk.span, tk_iter, program)?,
+        // a struct can point at itself, ex: ptr<Node> next
+        _ => match findStruct(program, tk)? {
+            Some(i) => DataValueType::Struct(i),
+            None => namedType(program, tk)?,
+        }
+    };
+    let close = match tk_iter.peek_mut() {
+        Some(close) => close,
+        None => return Err(ParserError::ExpectedPointerType(tk.tk_data.to_string(), tk.span)),
+    };
+    match close.tk_type {
+        TokenType::OpGreat => {tk_iter.next();}
+        TokenType::OpShiftRight => {
+            close.tk_type = TokenType::OpGreat;
+            close.tk_data = ">";
+            close.span.offset += 1;
+            close.span.col += 1;
+            close.span.len = 1;
+        }
+        _ => return Err(ParserError::ExpectedPointerType(close.tk_data.to_string(), close.span)),
+    }
+    return Ok(program.pointerTo(v));
+}
+
+pub fn runParser<'a>(
+    token_storage: &'a mut [Token<'a>], 
+    mut program: Program<'a>, 
+    warnings: & mut Vec<ParserWarning>,
+    errors: & mut Vec<ParserError>
+)->Program<'a>{
     
-    #[macro_export]
     macro_rules! grabVariable {
-        ($label: expr, $program: expr, $current_proc: expr) => ({
-            let mut variable = match $current_proc{
+        ($label: expr, $file: expr, $program: expr, $current_proc: expr, $current_block: expr) => ({
+            // the blocks first so their variables shadow the outer ones
+            let mut variable = None;
+            if let Some(ref dir) = $current_block {
+                for depth in $program.liveScopes(dir) {
+                    for v in &$program.getBlock(&dir[..depth+1]).variables {
+                        if v.label == $label {variable = Some(v); break;}
+                    }
+                    if variable.is_some() {break;}
+                }
+            }
+            if variable.is_none() {variable = match $current_proc{
                 Some(p) => {
                     let mut fv = Default::default();
                     for v in &$program.procs[p].arguments{
@@ -136,87 +449,33 @@ pub fn runParser<'a>(token_storage: &'a mut [Token<'a>], mut program: Program<'a
                     fv
                 }
                 None => None
-            };
+            };}
 
             if variable.is_none() {
-                for v in &$program.heap_variables {
-                    if v.label == $label {variable = Some(v); break;}
-                }
-                for v in &$program.const_variables{
-                    if v.label == $label {variable = Some(v); break;}
-                }
-                for v in &$program.static_variables{
-                    if v.label == $label {variable = Some(v); break;}
-                }
+                variable = $program.programVariable($label, $file).map(|(i, dest)| $program.programVariableAt(i, dest));
             }
             variable
         })
     }
 
-    #[macro_export]
-    macro_rules! grabVariable_mut {
-        ($label: expr, $program: expr, $current_proc: expr) => ({
-            let mut variable = match $current_proc{
-                Some(p) => {
-                    let proc = & mut $program.procs[p];
-                    let mut fv = Default::default();
-                    let mut len = $program.procs[p].arguments.len();
-                    for i in 0..len{
-                        let v = proc.arguments[i];
-                        if v.label == $label {
-                            fv = Some(v); 
+    macro_rules! grabVariableSetToken {
+        ($tk: expr, $program: expr, $current_proc: expr, $current_block: expr) => ({
+            // the blocks first so their variables shadow the outer ones
+            let mut variable = None;
+            if let Some(ref dir) = $current_block {
+                for depth in $program.liveScopes(dir) {
+                    let block = $program.getBlock(&dir[..depth+1]);
+                    for i in 0..block.variables.len() {
+                        if block.variables[i].label == $tk.tk_data {
+                            variable = Some(&block.variables[i]);
+                            $tk.tk_comp_data = TokenCompData::Var(i, VarDest::Block(depth as u8));
                             break;
                         }
                     }
-                    if fv == Default::default() {
-                        len = $program.procs[p].variables.len();
-                        for i in 0..len{
-                            let v = proc.variables[i];
-                            if v.label == $label {
-                                fv = Some(v); 
-                                break;
-                            }
-                        }
-                    }
-                    fv
-                }
-                None => None
-            };
-
-            if variable.is_none() {
-                let mut len = $program.heap_variables.len();
-                for i in 0..len{
-                    let v = & mut $program.heap_variables[i];
-                    if v.label == $label {
-                        variable = Some(v); 
-                        break;
-                    }
-                }
-                len = $program.const_variables.len();
-                for i in 0..len{
-                    let v = & mut $program.const_variables[i];
-                    if v.label == $label {
-                        variable = Some(v); 
-                        break;
-                    }
-                }
-                len = $program.static_variables.len();
-                for i in 0..len{
-                    let v = & mut $program.static_variables[i];
-                    if v.label == $label {
-                        variable = Some(v); 
-                        break;
-                    }
+                    if variable.is_some() {break;}
                 }
             }
-            variable
-        })
-    }
-
-    #[macro_export]
-    macro_rules! grabVariableSetToken {
-        ($tk: expr, $program: expr, $current_proc: expr) => ({
-            let mut variable = match $current_proc{
+            if variable.is_none() {variable = match $current_proc{
                 Some(p) => {
                     let mut fv = Default::default();
                     let mut len = $program.procs[p].arguments.len();
@@ -242,174 +501,215 @@ pub fn runParser<'a>(token_storage: &'a mut [Token<'a>], mut program: Program<'a
                     fv
                 }
                 None => None
-            };
+            };}
 
             if variable.is_none() {
-                let mut len = $program.heap_variables.len();
-                for i in 0..len{
-                    let v = &$program.heap_variables[i];
-                    if v.label == $tk.tk_data {
-                        variable = Some(v); 
-                        $tk.tk_comp_data = TokenCompData::Var(i, VarDest::Heap);
-                        break;
-                    }
-                }
-                len = $program.const_variables.len();
-                for i in 0..len{
-                    let v = &$program.const_variables[i];
-                    if v.label == $tk.tk_data {
-                        variable = Some(v); 
-                        $tk.tk_comp_data = TokenCompData::Var(i, VarDest::ProgramConst);
-                        break;
-                    }
-                }
-                len = $program.static_variables.len();
-                for i in 0..len{
-                    let v = &$program.static_variables[i];
-                    if v.label == $tk.tk_data {
-                        variable = Some(v); 
-                        $tk.tk_comp_data = TokenCompData::Var(i, VarDest::ProgramStatic);
-                        break;
-                    }
+                if let Some((i, dest)) = $program.programVariable($tk.tk_data, $tk.span.file) {
+                    variable = Some($program.programVariableAt(i, dest));
+                    $tk.tk_comp_data = TokenCompData::Var(i, dest);
                 }
             }
             variable
         })
     }
 
-    #[macro_export]
+    let mut nextPub: bool = false; //the next proc, struct or program variable is pub
+
     macro_rules! declareVariable {
-        ($current_var_def: expr, $dat: expr, $dvt: expr, $tk_iter: expr, $expr: expr, $program: expr, $current_proc: expr) => ({
+        ($current_var_def: expr, $dat: expr, $dvt: expr, $span: expr, $tk_iter: expr, $expr: expr, $program: expr, $current_proc: expr, $current_block: expr) => ({
             // check if expr open
             match $expr {
-                Some(ref mut _exp) => return Err(ParserError::TypeInExpression($dvt)),
+                Some(ref mut _exp) => return Err(ParserError::TypeInExpression($dvt, $span)),
                 None => {
                     // change tk
-                    let mut vtk = $tk_iter.peek().unwrap();
+                    let mut vtk = peekToken(&mut $tk_iter, $span)?;
+                    let mut dvt = $dvt;
+                    // uint[N], short[N] and char[N] are arrays of N numbers
+                    if vtk.tk_data == "[" && matches!(dvt, DataValueType::Uint | DataValueType::Short | DataValueType::Int | DataValueType::SShort | DataValueType::Char | DataValueType::Enum(..)) {
+                        $tk_iter.next();
+                        let size_tk = nextToken(&mut $tk_iter, $span)?;
+                        let len = match constNumber(&$program, size_tk)? {
+                            Some(s) if s > 0 && s * (dvt.size().unwrap() as i32) <= 0xFF => s as u8,
+                            _ => return Err(ParserError::InvalidArraySize(size_tk.tk_data.to_string(), size_tk.span))
+                        };
+                        let close_tk = nextToken(&mut $tk_iter, size_tk.span)?;
+                        if close_tk.tk_data != "]" {
+                            return Err(ParserError::MissingClosingSqBracket(close_tk.span));
+                        }
+                        dvt = $program.arrayOf(dvt, len);
+                        vtk = peekToken(&mut $tk_iter, $span)?;
+                    }
+                    let dvt_size = $program.sizeOf(dvt);
 
                     let mut var_size: i16 = -1;
-                    if ($dvt==DataValueType::Buffer) {
+                    if (dvt==DataValueType::Buffer) {
                         if vtk.tk_data == "[" {
                             $tk_iter.next();
-                            var_size = match $tk_iter.next().unwrap().tk_data.parse::<i16>() {
-                                Ok(s) => s,
-                                Err(_e) => return Err(ParserError::MissingSizeForBufferNotNumber)
+                            let size_tk = nextToken(&mut $tk_iter, $span)?;
+                            var_size = match constNumber(&$program, size_tk)? {
+                                Some(s) => s as i16,
+                                None => return Err(ParserError::MissingSizeForBufferNotNumber(size_tk.span))
                             };
-                            if $tk_iter.next().unwrap().tk_data != "]" {
-                                return Err(ParserError::MissingClosingSqBracket);
+                            let close_tk = nextToken(&mut $tk_iter, size_tk.span)?;
+                            if close_tk.tk_data != "]" {
+                                return Err(ParserError::MissingClosingSqBracket(close_tk.span));
                             }
-                            vtk = $tk_iter.peek().unwrap();
+                            vtk = peekToken(&mut $tk_iter, $span)?;
                         }
                         else {
-                            if $dat != DataAllocationType::Static {return Err(ParserError::MissingClosingSqBracket);}
+                            if $dat != DataAllocationType::Static {return Err(ParserError::MissingClosingSqBracket(vtk.span));}
                         }
                     }
                     
-                    // identify, if already exists, throw error 
-                    match grabVariable!(vtk.tk_data, $program, $current_proc){
-                        Some(_v) => return Err(ParserError::RedefinitionOfVariable(vtk.tk_data.to_string())),
-                        // let case for other label types and if none of them are matched throw error
-                        None => (),
-                    }
-
-                    //declare var
-                    match $current_proc {
-                        Some(p) => {
-                            //allocate bytes
-                            let newDat = match $dat {
-                                DataAllocationType::Stack(_) => {
-                                    let s = program.procs[p].allocated_bytes;
-                                    if var_size == -1 {
-                                        program.procs[p].allocated_bytes += $dvt.size().ok_or(ParserError::UnimplementedDataValueType($dvt))?;
-                                    } else {
-                                        program.procs[p].allocated_bytes += var_size as u8;
-                                    }
-                                    DataAllocationType::Stack(s+1)
-                                }
-                                DataAllocationType::Const => DataAllocationType::Const,
-                                _ => return Err(ParserError::UnimplementedDataAllocType($dat))
-                            };
-
-                            //declare var
-                            $program.procs[p].variables.push(Variable {
+                    // stack variables declared in a block live in the block's frame
+                    let block_dir = match $current_block {
+                        Some(ref dir) if $current_proc.is_some() && matches!($dat, DataAllocationType::Stack(_)) => Some(dir.clone()),
+                        _ => None
+                    };
+                    match block_dir {
+                        Some(dir) => {
+                            // only the block itself can clash, outer names are shadowed
+                            let block = $program.getBlock_mut(&dir);
+                            if block.variables.iter().any(|v| v.label == vtk.tk_data) {
+                                return Err(ParserError::RedefinitionOfVariable(vtk.tk_data.to_string(), vtk.span));
+                            }
+                            let s = block.allocated_bytes;
+                            if var_size == -1 {
+                                block.allocated_bytes += dvt_size.ok_or(ParserError::UnimplementedDataValueType(dvt, vtk.span))?;
+                            } else {
+                                block.allocated_bytes += var_size as u8;
+                            }
+                            block.variables.push(Variable {
                                 t: DataType {
-                                    a: newDat,
-                                    v: $dvt
+                                    a: DataAllocationType::Stack(s+1),
+                                    v: dvt
                                 },
                                 value: None,
-                                label: vtk.tk_data
+                                label: vtk.tk_data,
+                                module: vtk.span.file,
+                                public: false
                             });
-                            $current_var_def = Some(($program.procs[p].variables.len()-1, VarDest::CurrentProc));
+                            $current_var_def = Some((block.variables.len()-1, VarDest::Block((dir.len()-1) as u8)));
                         }
                         None => {
-                            //allocate bytes
-                            let newDat = match $dat {
-                                DataAllocationType::Heap(_) => {
-                                    let s = program.allocated_bytes;
-                                    program.allocated_bytes += $dvt.size().ok_or(ParserError::UnimplementedDataValueType($dvt))? as u16;
-                                    DataAllocationType::Heap(s)
-                                }
-                                DataAllocationType::Const => {
-                                    DataAllocationType::Const
-                                }
-                                DataAllocationType::Static => {
-                                    DataAllocationType::Static
-                                }
-                                _ => return Err(ParserError::AttemptedVariableInProgram(vtk.tk_data.to_string()))
+                            // identify, if already exists, throw error 
+                            // a local only clashes with the proc's own names, it shadows heap, const and static ones
+                            let taken = match $current_proc {
+                                Some(p) => $program.procs[p].arguments.iter().chain($program.procs[p].variables.iter()).any(|v| v.label == vtk.tk_data),
+                                // a private name of another file is not in the way
+                                None => grabVariable!(vtk.tk_data, vtk.span.file, $program, None::<usize>, None::<Vec<BlockParent>>)
+                                    .is_some_and(|v| v.module == vtk.span.file || (nextPub && v.public)),
                             };
+                            if taken {return Err(ParserError::RedefinitionOfVariable(vtk.tk_data.to_string(), vtk.span));}
 
                             //declare var
-                            match newDat{
-                                DataAllocationType::Const => {
-                                    $program.const_variables.push(Variable {
+                            match $current_proc {
+                                Some(p) => {
+                                    //allocate bytes
+                                    let newDat = match $dat {
+                                        DataAllocationType::Stack(_) => {
+                                            let s = program.procs[p].allocated_bytes;
+                                            if var_size == -1 {
+                                                program.procs[p].allocated_bytes += dvt_size.ok_or(ParserError::UnimplementedDataValueType(dvt, vtk.span))?;
+                                            } else {
+                                                program.procs[p].allocated_bytes += var_size as u8;
+                                            }
+                                            DataAllocationType::Stack(s+1)
+                                        }
+                                        DataAllocationType::Const => DataAllocationType::Const,
+                                        _ => return Err(ParserError::UnimplementedDataAllocType($dat, vtk.span))
+                                    };
+
+                                    //declare var
+                                    $program.procs[p].variables.push(Variable {
                                         t: DataType {
                                             a: newDat,
-                                            v: $dvt
+                                            v: dvt
                                         },
                                         value: None,
-                                        label: vtk.tk_data
+                                        label: vtk.tk_data,
+                                        module: vtk.span.file,
+                                        public: false
                                     });
-                                    $current_var_def = Some(($program.const_variables.len()-1, VarDest::ProgramConst));
+                                    $current_var_def = Some(($program.procs[p].variables.len()-1, VarDest::CurrentProc));
                                 }
-                                DataAllocationType::Static => {
-                                    $program.static_variables.push(Variable {
-                                        t: DataType {
-                                            a: newDat,
-                                            v: $dvt
-                                        },
-                                        value: None,
-                                        label: vtk.tk_data
-                                    });
-                                    $current_var_def = Some(($program.static_variables.len()-1, VarDest::ProgramStatic));
+                                None => {
+                                    //allocate bytes
+                                    let newDat = match $dat {
+                                        DataAllocationType::Heap(_) => {
+                                            let s = program.allocated_bytes;
+                                            if var_size == -1 {
+                                                program.allocated_bytes += dvt_size.ok_or(ParserError::UnimplementedDataValueType(dvt, vtk.span))? as u16;
+                                            } else {
+                                                program.allocated_bytes += var_size as u16;
+                                            }
+                                            DataAllocationType::Heap(s)
+                                        }
+                                        DataAllocationType::Const => {
+                                            DataAllocationType::Const
+                                        }
+                                        DataAllocationType::Static => {
+                                            DataAllocationType::Static
+                                        }
+                                        _ => return Err(ParserError::AttemptedVariableInProgram(vtk.tk_data.to_string(), vtk.span))
+                                    };
+
+                                    //declare var
+                                    match newDat{
+                                        DataAllocationType::Const => {
+                                            $program.const_variables.push(Variable {
+                                                t: DataType {
+                                                    a: newDat,
+                                                    v: dvt
+                                                },
+                                                value: None,
+                                                label: vtk.tk_data,
+                                                module: vtk.span.file,
+                                                public: nextPub
+                                            });
+                                            $current_var_def = Some(($program.const_variables.len()-1, VarDest::ProgramConst));
+                                        }
+                                        DataAllocationType::Static => {
+                                            $program.static_variables.push(Variable {
+                                                t: DataType {
+                                                    a: newDat,
+                                                    v: dvt
+                                                },
+                                                value: None,
+                                                label: vtk.tk_data,
+                                                module: vtk.span.file,
+                                                public: nextPub
+                                            });
+                                            $current_var_def = Some(($program.static_variables.len()-1, VarDest::ProgramStatic));
+                                        }
+                                        DataAllocationType::Heap(_) => {
+                                            $program.heap_variables.push(Variable {
+                                                t: DataType {
+                                                    a: newDat,
+                                                    v: dvt
+                                                },
+                                                value: None,
+                                                label: vtk.tk_data,
+                                                module: vtk.span.file,
+                                                public: nextPub
+                                            });
+                                            $current_var_def = Some(($program.heap_variables.len()-1, VarDest::Heap));
+                                        } 
+                                        _ => return Err(ParserError::AttemptedVariableInProgram(vtk.tk_data.to_string(), vtk.span))
+                                    }
+                                    nextPub = false;
                                 }
-                                DataAllocationType::Heap(_) => {
-                                    $program.heap_variables.push(Variable {
-                                        t: DataType {
-                                            a: newDat,
-                                            v: $dvt
-                                        },
-                                        value: None,
-                                        label: vtk.tk_data
-                                    });
-                                    $current_var_def = Some(($program.heap_variables.len()-1, VarDest::Heap));
-                                } 
-                                _ => return Err(ParserError::AttemptedVariableInProgram(vtk.tk_data.to_string()))
-                            }
+                            };
                         }
-                    };
+                    }
                 }
             };
         })
     }
 
-    #[macro_export]
     macro_rules! grabProcedure {
-        ($label: expr, $program: expr) => ({
-            let mut proc : Option<&Procedure<'_>> = None;
-            for i in 0..$program.procs.len(){
-                if $program.procs[i].label == $label {proc = Some(&$program.procs[i]); break;}
-            }
-            proc
+        ($label: expr, $file: expr, $program: expr) => ({
+            findSymbol(&$program.procs, $label, $file)
         })
     }
 
@@ -418,7 +718,6 @@ pub fn runParser<'a>(token_storage: &'a mut [Token<'a>], mut program: Program<'a
     let tks_len = token_storage.len();
 
     let mut current_proc: Option<usize> = None;
-    let mut tk: & mut Token<'_>;
     let mut index: usize = 0;
     let mut tk_iter = token_storage.iter_mut().peekable();
     let mut expr: Option<Expression> = None;
@@ -428,476 +727,1086 @@ pub fn runParser<'a>(token_storage: &'a mut [Token<'a>], mut program: Program<'a
     let mut creatingBlock: BlockType = BlockType::None;
     let mut current_block: Option<Vec<BlockParent>> = None;
     let mut hasRet: bool = false;
+    let mut proc_span: Span = Default::default();
+    let mut paren_depth: usize = 0; //parens opened inside the current expression
+    let mut forPart: ForPart = ForPart::None;
+    let mut forCon: Option<Expression<'a>> = None; //kept until the for block is opened at its )
+    let mut forScope: usize = 0;
+    let mut current_struct: Option<usize> = None; //struct whose fields are being declared
 
     while tk_iter.len() != 0{
-        tk=tk_iter.nth(0).unwrap();
-        match tk.tk_type {
-            TokenType::None => return Err(ParserError::UnidentifiedToken(tk.tk_data.to_string())),
-            TokenType::KeywordProc => {
-                let mut new_proc: Procedure<'a> = Default::default();
-
-                // gather out type
-                tk=tk_iter.next().unwrap(); //next token
-                if tk.tk_data == ":" {
-                    tk=tk_iter.next().unwrap(); //next token
-                    let vt = match toValueType(&tk.tk_data) {
-                        Ok(t) => t,
-                        Err(e) => return Err(e)
-                    };
-                    new_proc.retType = DataType {a: DataAllocationType::None, v: vt};
-                } else {
-                    new_proc.retType = DataType {a: DataAllocationType::None, v: DataValueType::Void};
+        // a statement that fails is recorded and skipped, parsing resumes at the next ; or end
+        let mut resync = false;
+        let mut inHeader = false;
+        let result: Result<(), ParserError> = (|| {
+            let mut tk: & mut Token<'_> = tk_iter.next().unwrap();
+            resync = tk.tk_type != TokenType::SymbolSemicolon && tk.tk_type != TokenType::KeywordEnd;
+            // inside an if, elif, while or for header and not the ) that closes it
+            inHeader = (creatingBlock != BlockType::None || forPart != ForPart::None)
+                && !(paren_depth == 0 && tk.tk_type == TokenType::Symbol && tk.tk_data == ")");
+            // only fields are declared between struct and its end
+            if let Some(s) = current_struct {
+                let v = match tk.tk_type {
+                    TokenType::KeywordEnd => {
+                        current_struct = None;
+                        return Ok(());
+                    }
+                    TokenType::SymbolSemicolon => return Ok(()),
+                    TokenType::KeywordUint => DataValueType::Uint,
+                    TokenType::KeywordShort => DataValueType::Short,
+                    TokenType::KeywordInt => DataValueType::Int,
+                    TokenType::KeywordSShort => DataValueType::SShort,
+                    TokenType::KeywordChar => DataValueType::Char,
+                    TokenType::KeywordPtr => pointerType(tk.span, &mut tk_iter, &mut program)?,
+                    // structs declared before this one can be nested
+                    _ => match (findStruct(&program, tk)?, findEnum(&program, tk)?) {
+                        (Some(i), _) if i != s => DataValueType::Struct(i),
+                        (None, Some(e)) => program.enumType(e),
+                        _ => return Err(ParserError::UnexpectedInStruct(tk.tk_data.to_string(), tk.span)),
+                    }
+                };
+                let name = match tk_iter.next() {
+                    Some(n) => n,
+                    None => return Err(ParserError::MissingEndStatement(tk.span)),
+                };
+                let size = program.sizeOf(v).ok_or(ParserError::UnimplementedDataValueType(v, tk.span))?;
+                let def = &mut program.structs[s];
+                if def.fields.iter().any(|f| f.label == name.tk_data) {
+                    return Err(ParserError::RedefinitionOfVariable(name.tk_data.to_string(), name.span));
                 }
-                tk=tk_iter.next().unwrap(); //next token
+                def.fields.push(StructField {label: name.tk_data, v, offset: def.size});
+                def.size += size;
+                return Ok(());
+            }
+            match tk.tk_type {
+                TokenType::None => return Err(ParserError::UnidentifiedToken(tk.tk_data.to_string(), tk.span)),
+                // already reported by the lexer, just drop the statement
+                TokenType::Invalid => {
+                    expr = None;
+                    paren_depth = 0;
+                    creatingBlock = BlockType::None;
+                    return Ok(());
+                }
+                TokenType::KeywordProc => {
+                    let mut new_proc: Procedure<'a> = Default::default();
+                    let keyword_span = tk.span;
+
+                    // gather out type
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                    if tk.tk_data == ":" {
+                        tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                        let vt = match tk.tk_type {
+                            TokenType::KeywordPtr => pointerType(tk.span, &mut tk_iter, &mut program)?,
+                            _ => namedType(&program, tk)?
+                        };
+                        new_proc.retType = DataType {a: DataAllocationType::None, v: vt};
+                    } else {
+                        new_proc.retType = DataType {a: DataAllocationType::None, v: DataValueType::Void};
+                    }
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
                 
-                //set label
-                new_proc.label = tk.tk_data;
+                    //set label
+                    new_proc.label = tk.tk_data;
+                    new_proc.module = keyword_span.file;
+                    new_proc.public = nextPub;
+                    nextPub = false;
+                    // a private proc only clashes in its own file, it gets its own label in the .kasm
+                    if clashes(&program.procs, new_proc.label, new_proc.module, new_proc.public) {
+                        errors.push(ParserError::RedefinitionOfProc(tk.tk_data.to_string(), tk.span));
+                    }
                 
-                //get arguments
-                tk=tk_iter.next().unwrap(); //next token
-                if tk.tk_data != "(" {return Err(ParserError::NoParaProcDef);}
-                tk=tk_iter.next().unwrap(); //next token
-                while tk.tk_data != ")" && index < tks_len {
-                    // make a var
-                    let mut var: Variable<'a> = Default::default();
-                    let vt = match toValueType(&tk.tk_data) {
-                        Ok(t) => t,
-                        Err(e) => return Err(e)
-                    };
-                    var.t = DataType { a: DataAllocationType::Stack(0), v: vt};
-                    tk=tk_iter.next().unwrap(); //next token
-                    var.label = tk.tk_data;
-                    new_proc.arguments.push(var);
+                    //get arguments
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                    if tk.tk_data != "(" {return Err(ParserError::NoParaProcDef(tk.span));}
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                    while tk.tk_data != ")" && index < tks_len {
+                        // make a var
+                        let mut var: Variable<'a> = Default::default();
+                        let vt = match tk.tk_type {
+                            TokenType::KeywordPtr => pointerType(tk.span, &mut tk_iter, &mut program)?,
+                            _ => namedType(&program, tk)?
+                        };
+                        // register arguments are stored into the frame by the callee,
+                        // stack ones are placed when the proc is closed
+                        let mut dat = DataAllocationType::Stack(0);
+                        if argInRegister(new_proc.arguments.len(), vt) {
+                            dat = DataAllocationType::Stack(new_proc.allocated_bytes+1);
+                            new_proc.allocated_bytes += vt.size().ok_or(ParserError::UnimplementedDataValueType(vt, tk.span))?;
+                        }
+                        var.t = DataType { a: dat, v: vt};
+                        tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                        var.label = tk.tk_data;
+                        var.module = tk.span.file;
+                        new_proc.arguments.push(var);
+
+                        //inc
+                        tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                        if tk.tk_data == "," {tk=nextToken(&mut tk_iter, tk.span)?;}
+                    }
+                    if index >= tks_len-1 {return Err(ParserError::NoParaNotTerminated(tk.span));}
 
-                    //inc
-                    tk=tk_iter.next().unwrap(); //next token
+                    //set current proc, closing an unterminated one so the new one still parses
+                    if let Some(p) = current_proc {
+                        program.procs[p].placeStackArguments();
+                        errors.push(ParserError::MissingEndStatement(proc_span));
+                        current_block = None;
+                        hasRet = false;
+                    }
+                    program.procs.push(new_proc);
+                    current_proc = Some(program.procs.len()-1);
+                    proc_span = keyword_span;
                 }
-                if index >= tks_len-1 {return Err(ParserError::NoParaNotTerminated);}
-
-                //set current proc
-                if current_proc.is_some() {return Err(ParserError::MissingEndStatement);}
-                program.procs.push(new_proc);
-                current_proc = Some(program.procs.len()-1);
-            }
-            TokenType::KeywordEnd => {
-                let proc = match current_proc {
-                    Some(p) => p,
-                    None => return Err(ParserError::ExcessiveEndStatement)
-                };
-                match current_block {
-                    Some(ref mut directory) => {
-                        match directory.last(){
-                            Some(ref bp) => {
+                TokenType::KeywordEnd => {
+                    let proc = match current_proc {
+                        Some(p) => p,
+                        None => return Err(ParserError::ExcessiveEndStatement(tk.span))
+                    };
+                    match current_block {
+                        Some(ref mut directory) => {
+                            if let Some(bp) = directory.last() {
+                                // an elif or else closes the whole chain back to its if
+                                let block_type = program.getBlock(directory).block_type;
+                                // the loop variables go out of scope, their bytes stay in the proc frame
+                                if block_type == BlockType::For {
+                                    let scope_start = program.getBlock(directory).scope_start;
+                                    let parent = &directory[..directory.len()-1];
+                                    let variables = match parent.len() {
+                                        1 => &mut program.procs[proc].variables,
+                                        _ => &mut program.getBlock_mut(parent).variables
+                                    };
+                                    for v in &mut variables[scope_start..] {v.label = "";}
+                                }
                                 if bp.t == BlockParentType::Block 
-                                    && program.getBlock(directory).block_type == BlockType::Else {
+                                    && (block_type == BlockType::Else || block_type == BlockType::Elif) {
                                     while program.getBlock(directory).block_type != BlockType::If {directory.pop();}
                                     directory.pop();
                                 } 
                                 else {directory.pop();}
                             }
-                            None => ()
-                        }
 
-                        if directory.len() == 1{
-                            current_block = None;
+                            if directory.len() == 1{
+                                current_block = None;
+                            }
+                        }
+                        None => {
+                            if !hasRet {errors.push(ParserError::MissingReturn(program.procs[proc].label.to_string(), tk.span));}
+                            program.procs[proc].placeStackArguments();
+                            hasRet = false;
+                            current_proc = None
                         }
-                    }
-                    None => {
-                        if !hasRet {return Err(ParserError::MissingReturn(program.procs[proc].label.to_string()));}
-                        hasRet = false;
-                        current_proc = None
                     }
                 }
-            }
-            TokenType::KeywordRet => {
-                //start expression
-                if expr.is_some() {return Err(ParserError::SymbolIncorrectlyInExpression);}
-                let mut built_expr: Expression = Default::default();
-                built_expr.t = ExpressionType::Return;
+                TokenType::KeywordRet => {
+                    //start expression
+                    if expr.is_some() {return Err(ParserError::SymbolIncorrectlyInExpression(tk.span));}
+                    let built_expr: Expression = Expression {t: ExpressionType::Return, ..Default::default()};
 
-                hasRet = true;
+                    hasRet = true;
                 
-                expr = Some(built_expr);
-            }
-            TokenType::SymbolSemicolon => {
-                let mut pushExpr: bool = true; 
-                let unpkg_expr = match expr {
-                    Some(ref exp) => exp,
-                    None => return Err(ParserError::UnnecessarySemicolon)
-                };
-                match current_proc {
-                    Some(p) => {
-                        let exp = unpkg_expr.clone();
-                        if exp.tks.len() == 1 {
-                            match exp.t {
-                                ExpressionType::Unspecified =>{
+                    expr = Some(built_expr);
+                }
+                TokenType::KeywordBreak |
+                TokenType::KeywordContinue => {
+                    if expr.is_some() {return Err(ParserError::SymbolIncorrectlyInExpression(tk.span));}
+                    // any block between it and the loop is fine, ex: an if in the loop
+                    let in_loop = match current_block {
+                        Some(ref dir) => (2..dir.len()+1).any(|i| matches!(program.getBlock(&dir[..i]).block_type, BlockType::While | BlockType::For)),
+                        None => false
+                    };
+                    if !in_loop {return Err(ParserError::LoopControlOutsideLoop(tk.tk_data.to_string(), tk.span));}
+
+                    let t = match tk.tk_type {
+                        TokenType::KeywordBreak => ExpressionType::Break,
+                        _ => ExpressionType::Continue
+                    };
+                    expr = Some(Expression {t, ..Default::default()});
+                }
+                TokenType::SymbolSemicolon if forPart == ForPart::Con => {
+                    // the condition waits for the step and the )
+                    forCon = match expr.take() {
+                        Some(mut exp) => {
+                            exp.t = ExpressionType::ConditionalWhile;
+                            exp.tree = Some(buildExpressionTree(&exp.tks)?);
+                            Some(exp)
+                        }
+                        None => None
+                    };
+                    paren_depth = 0;
+                    forPart = ForPart::Step;
+                    creatingBlock = BlockType::For;
+                }
+                // for (; ...) has no init
+                TokenType::SymbolSemicolon if forPart == ForPart::Init && expr.is_none() => {
+                    forPart = ForPart::Con;
+                }
+                TokenType::SymbolSemicolon => {
+                    let mut pushExpr: bool = true; 
+                    let unpkg_expr = match expr {
+                        Some(ref mut exp) => exp,
+                        None => return Err(ParserError::UnnecessarySemicolon(tk.span))
+                    };
+                    paren_depth = 0;
+                    if !unpkg_expr.tks.is_empty() {
+                        unpkg_expr.tree = Some(buildExpressionTree(&unpkg_expr.tks)?);
+                    }
+                    match current_proc {
+                        Some(p) => {
+                            let exp = unpkg_expr.clone();
+                            if exp.tks.len() == 1 {
+                                if exp.t == ExpressionType::Unspecified {
                                     match exp.tks[0].tk_type{
                                         TokenType::Variable => {
-                                            warnings.push(ParserWarning::WarningNoInitialValue(exp.tks[0].tk_data.to_string()));
+                                            // an array, struct or buffer is filled in later, it has no one initial value
+                                            let aggregate = grabVariable!(exp.tks[0].tk_data, exp.tks[0].span.file, program, current_proc, current_block)
+                                                .is_some_and(|v| matches!(v.t.v, DataValueType::Array(_) | DataValueType::Struct(_) | DataValueType::Buffer));
+                                            if !aggregate {
+                                                warnings.push(ParserWarning::WarningNoInitialValue(exp.tks[0].tk_data.to_string(), exp.tks[0].span));
+                                            }
                                             pushExpr = false;
                                         },
-                                        _ => warnings.push(ParserWarning::WarningUnidentified)
+                                        _ => warnings.push(ParserWarning::WarningUnidentified(exp.tks[0].span))
+                                    }
+                                }
+                            }
+                            else if exp.t == ExpressionType::Assignment && exp.tks[0].tk_type == TokenType::Variable {
+                                match current_var_def {
+                                    Some(i) => {
+                                        let unpkged_var: &mut parserTree::Variable<'_> = match i.1 {
+                                            VarDest::CurrentProc => {
+                                                & mut program.procs[{match current_proc {
+                                                    Some(p) => p,
+                                                    None => return Err(ParserError::AttemptedVariableInProgram(exp.tks[0].tk_data.to_string(), exp.tks[0].span))
+                                                }}].variables[i.0]
+                                            }
+                                            VarDest::Block(depth) => {
+                                                match current_block {
+                                                    Some(ref dir) => & mut program.getBlock_mut(&dir[..depth as usize+1]).variables[i.0],
+                                                    None => return Err(ParserError::AttemptedVariableInProgram(exp.tks[0].tk_data.to_string(), exp.tks[0].span))
+                                                }
+                                            }
+                                            VarDest::Heap => & mut program.heap_variables[i.0],
+                                            VarDest::ProgramConst => & mut program.const_variables[i.0],
+                                            _ => return Err(ParserError::AttemptedVariableInProgram(exp.tks[0].tk_data.to_string(), exp.tks[0].span))
+                                        };
+                                        if unpkged_var.t.a == DataAllocationType::Const {
+                                            unpkged_var.value = Some(constValue(&exp, unpkged_var.t.v, errors));
+                                            // ignores other resolvables, add a macro called "resolve!(e)"
+                                            resolvableErrors.pop();
+                                            pushExpr = false;
+                                        }
+                                        current_var_def = None;
+                                    },
+                                    None => {
+                                        match grabVariable!(exp.tks[0].tk_data, exp.tks[0].span.file, program, current_proc, current_block){
+                                            Some(_) => (),
+                                            None => return Err(ParserError::InvalidAssignment(exp.tks[0].tk_data.to_string(), exp.tks[0].span))
+                                        };
+                                    }
+                                };
+                            }
+                            if pushExpr{
+                                match current_block {
+                                    Some(ref directory) => {
+                                        // if in block, get block
+                                        let block = program.getBlock_mut(directory);
+                                        let len = block.expressions.len();
+                                        block.lines.push(Line {index: len, t: LineType::Expression});
+                                        block.expressions.push(unpkg_expr.clone());
+
+                                    }
+                                    None => {
+                                        // if in base proc
+                                        let len = program.procs[p].expressions.len();
+                                        program.procs[p].lines.push(Line {index: len, t: LineType::Expression});
+                                        program.procs[p].expressions.push(unpkg_expr.clone());
                                     }
                                 }
-                                _ => ()
                             }
                         }
-                        else {
+                        None => {
+                            let exp = unpkg_expr.clone();
                             match exp.t {
-                                ExpressionType::Assignment =>{
+                                ExpressionType::Assignment => {
                                     match exp.tks[0].tk_type{
                                         TokenType::Variable => {
-                                            match current_var_def {
+                                            let unpkged_var = match current_var_def {
                                                 Some(i) => {
-                                                    let unpkged_var: &mut parserTree::Variable<'_> = match i.1 {
-                                                        VarDest::CurrentProc => {
-                                                            & mut program.procs[{match current_proc {
-                                                                Some(p) => p,
-                                                                None => return Err(ParserError::AttemptedVariableInProgram(exp.tks[0].tk_data.to_string()))
-                                                            }}].variables[i.0]
-                                                        }
+                                                    match i.1 {
                                                         VarDest::Heap => & mut program.heap_variables[i.0],
                                                         VarDest::ProgramConst => & mut program.const_variables[i.0],
-                                                        _ => return Err(ParserError::AttemptedVariableInProgram(exp.tks[0].tk_data.to_string()))
-                                                    };
-                                                    // only supoorts `const TYPE = VALUE;` 
-                                                    if unpkged_var.t.a == DataAllocationType::Const {
-                                                        unpkged_var.value = Some(exp.tks[2]);
-                                                        // ignores other resolvables, add a macro called "resolve!(e)"
-                                                        resolvableErrors.pop();
-                                                        pushExpr = false;
+                                                        VarDest::ProgramStatic => & mut program.static_variables[i.0],
+                                                        _ => return Err(ParserError::AttemptedVariableInProgram(exp.tks[0].tk_data.to_string(), exp.tks[0].span))
                                                     }
-                                                    current_var_def = None;
-                                                },
-                                                None => {
-                                                    match grabVariable!(exp.tks[0].tk_data, program, current_proc){
-                                                        Some(_) => (),
-                                                        None => return Err(ParserError::InvalidAssignment(exp.tks[0].tk_data.to_string()))
-                                                    };
                                                 }
+                                                None => return Err(ParserError::UnidentifiedError(exp.tks[0].span))
                                             };
+                                            if unpkged_var.t.a == DataAllocationType::Const {
+                                                unpkged_var.value = Some(constValue(&exp, unpkged_var.t.v, errors));
+                                                // ignores other resolvables, add a macro called "resolve!(e)"
+                                                resolvableErrors.pop();
+                                                pushExpr = false;
+                                            }
                                         }
-                                        _ => ()
+                                        _ => return Err(ParserError::AttemptedExpressionInProgram(exp.tks[0].span))
                                     }
                                 }
-                                _ => ()
+                                // declared without a value, ex: heap Player p;
+                                ExpressionType::Unspecified if exp.tks.len() == 1 && exp.tks[0].tk_type == TokenType::Variable => {
+                                    pushExpr = false;
+                                }
+                                _ => return Err(ParserError::AttemptedExpressionInProgram(tk.span))
                             }
+                            if pushExpr{
+                                program.expressions.push(unpkg_expr.clone());
+                            }
+                            current_var_def = None;
                         }
-                        if pushExpr{
+                    };
+                    expr = None;
+                    // anything still unresolved at the end of the statement is a real error
+                    if !resolvableErrors.is_empty() {
+                        errors.append(&mut resolvableErrors);
+                    }
+                    // the init of a for is compiled before the loop like any other statement
+                    if forPart == ForPart::Init {forPart = ForPart::Con;}
+                }
+                TokenType::KeywordIf => {
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                    if tk.tk_data != "(" {return Err(ParserError::NoParaConBlockDef(tk.span));}
+                    creatingBlock = BlockType::If;
+                }
+                TokenType::KeywordElif => {
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                    if tk.tk_data != "(" {return Err(ParserError::NoParaConBlockDef(tk.span));}
+                    creatingBlock = BlockType::Elif;
+                }
+                TokenType::KeywordElse => {
+                    // else if on one line is read as elif, an if on the next line opens a block in the else
+                    let line = tk.span.line;
+                    if tk_iter.peek().is_some_and(|next| next.tk_type == TokenType::KeywordIf && next.span.line == line) {
+                        tk_iter.next();
+                        tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                        if tk.tk_data != "(" {return Err(ParserError::NoParaConBlockDef(tk.span));}
+                        creatingBlock = BlockType::Elif;
+                        return Ok(());
+                    }
+                    match current_proc { 
+                        Some(_p) => {
+                            let mut new_block: Block<'a> = Block {block_type: BlockType::Else, ..Default::default()};
+
+                            //load up parentDirectory, if of block copy partent and add parent index
                             match current_block {
-                                Some(ref directory) => {
-                                    // if in block, get block
-                                    let mut block = program.getBlock_mut(&directory);
-                                    let len = block.expressions.len();
-                                    block.lines.push(Line {index: len, t: LineType::Expression});
-                                    block.expressions.push(unpkg_expr.clone());
+                                Some(ref dir) =>{ 
+                                    new_block.parentDirectory = dir.clone();
+                                    let cblock = program.getBlock_mut(dir);
+                                    new_block.distance = cblock.distance + 1;
+
+                                    if cblock.block_type != BlockType::If && cblock.block_type != BlockType::Elif {
+                                        return Err(ParserError::HangingElse(tk.span));
+                                    }
 
+                                    //push block to parent
+                                    let len = cblock.blocks.len();
+
+                                    //set cblock and reset expr
+                                    let mut cdir = new_block.parentDirectory.clone();
+                                    cdir.push(BlockParent{
+                                        t: BlockParentType::Block, 
+                                        index:len
+                                    });
+                                    current_block = Some(cdir);
+
+                                    //push block to parent
+                                    cblock.lines.push(Line {index: len, t: LineType::Block});
+                                    cblock.blocks.push(new_block);
                                 }
-                                None => {
-                                    // if in base proc
-                                    let len = program.procs[p].expressions.len();
-                                    program.procs[p].lines.push(Line {index: len, t: LineType::Expression});
-                                    program.procs[p].expressions.push(unpkg_expr.clone());
+                                None => { 
+                                    return Err(ParserError::HangingElse(tk.span));
                                 }
                             }
                         }
+                        None => { 
+                            return Err(ParserError::HangingElse(tk.span));
+                        }
                     }
-                    None => {
-                        let exp = unpkg_expr.clone();
-                        match exp.t {
-                            ExpressionType::Assignment => {
-                                match exp.tks[0].tk_type{
-                                    TokenType::Variable => {
-                                        let unpkged_var = match current_var_def {
-                                            Some(i) => {
-                                                match i.1 {
-                                                    VarDest::Heap => & mut program.heap_variables[i.0],
-                                                    VarDest::ProgramConst => & mut program.const_variables[i.0],
-                                                    VarDest::ProgramStatic => & mut program.static_variables[i.0],
-                                                    _ => return Err(ParserError::AttemptedVariableInProgram(exp.tks[0].tk_data.to_string()))
+                }
+                TokenType::KeywordFor => {
+                    if expr.is_some() {return Err(ParserError::SymbolIncorrectlyInExpression(tk.span));}
+                    let p = match current_proc {
+                        Some(p) => p,
+                        None => return Err(ParserError::AttemptedBlockInProgram(BlockType::For, tk.span))
+                    };
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                    if tk.tk_data != "(" {return Err(ParserError::NoParaConBlockDef(tk.span));}
+                    forPart = ForPart::Init;
+                    forScope = match current_block {
+                        Some(ref dir) => program.getBlock(dir).variables.len(),
+                        None => program.procs[p].variables.len()
+                    };
+                }
+                TokenType::KeywordWhile => {
+                    tk=nextToken(&mut tk_iter, tk.span)?; //next token
+                    if tk.tk_data != "(" {return Err(ParserError::NoParaConBlockDef(tk.span));}
+                    creatingBlock = BlockType::While;
+                }
+                TokenType::Symbol => {
+                    match tk.tk_data {
+                        "(" => {
+                            paren_depth += 1;
+                            match expr {
+                                Some(ref mut exp) => exp.tks.push(tk),
+                                None => {
+                                    let mut built_expr: Expression = Expression {t: ExpressionType::Unspecified, ..Default::default()};
+                                    built_expr.tks.push(tk);
+                                
+                                    expr = Some(built_expr);
+                                }
+                            };
+                        }
+                        // closes a paren from inside the expression, not the condition
+                        ")" if paren_depth != 0 => {
+                            paren_depth -= 1;
+                            match expr {
+                                Some(ref mut exp) => exp.tks.push(tk),
+                                None => return Err(ParserError::StrayValue(tk.tk_data.to_string(), tk.span))
+                            };
+                        }
+                        ")" => {
+                            match creatingBlock{
+                                BlockType::While |
+                                BlockType::For |
+                                BlockType::Elif |
+                                BlockType::If => {
+                                    match current_proc { 
+                                        Some(p) => {
+                                            let mut new_block: Block<'a> = Default::default();
+                                            if creatingBlock == BlockType::For {
+                                                // the step is what is left in the header, it can be empty
+                                                new_block.con = forCon.take();
+                                                new_block.step = match expr.take() {
+                                                    Some(mut exp) => {
+                                                        match buildExpressionTree(&exp.tks) {
+                                                            Ok(tree) => exp.tree = Some(tree),
+                                                            Err(e) => errors.push(e)
+                                                        }
+                                                        Some(exp)
+                                                    }
+                                                    None => None
+                                                };
+                                                new_block.scope_start = forScope;
+                                                forPart = ForPart::None;
+                                            }
+                                            else {
+                                                let unpkg_expr = match expr {
+                                                    Some(ref mut exp) => exp,
+                                                    None => return Err(ParserError::UnnecessarySemicolon(tk.span))
+                                                };
+                                                unpkg_expr.t = match creatingBlock {
+                                                    BlockType::If |
+                                                    BlockType::Elif => ExpressionType::ConditionalIf,
+                                                    BlockType::While => ExpressionType::ConditionalWhile,
+                                                    _ => return Err(ParserError::UnidentifiedError(tk.span))
+                                                };
+                                                // still open the block on a bad condition so its end lines up,
+                                                // a header that already failed is left empty and has no tree
+                                                if !unpkg_expr.tks.is_empty() {
+                                                    match buildExpressionTree(&unpkg_expr.tks) {
+                                                        Ok(tree) => unpkg_expr.tree = Some(tree),
+                                                        Err(e) => errors.push(e)
+                                                    }
                                                 }
+                                                new_block.con = Some(unpkg_expr.clone());
                                             }
-                                            None => return Err(ParserError::UnidentifiedError)
-                                        };
-                                        // only supoorts `const TYPE = VALUE;` 
-                                        if unpkged_var.t.a == DataAllocationType::Const {
-                                            unpkged_var.value = Some(exp.tks[2]);
-                                            // ignores other resolvables, add a macro called "resolve!(e)"
-                                            resolvableErrors.pop();
-                                            pushExpr = false;
+                                            new_block.block_type = creatingBlock;
+
+                                            //load up parentDirectory, if of block copy partent and add parent index
+                                            match current_block {
+                                                Some(ref dir) =>{ 
+                                                    new_block.parentDirectory = dir.clone();
+                                                    let cblock = program.getBlock_mut(dir);
+                                                    new_block.distance = cblock.distance + 1;
+
+                                                    if creatingBlock == BlockType::Elif
+                                                    && cblock.block_type != BlockType::If && cblock.block_type != BlockType::Elif {
+                                                        return Err(ParserError::HangingElif(tk.span));
+                                                    }
+
+                                                    //push block to parent
+                                                    let len = cblock.blocks.len();
+
+                                                    //set cblock and reset expr
+                                                    let mut cdir = new_block.parentDirectory.clone();
+                                                    cdir.push(BlockParent{
+                                                        t: BlockParentType::Block, 
+                                                        index:len
+                                                    });
+                                                    current_block = Some(cdir);
+
+                                                    //push block to parent
+                                                    cblock.lines.push(Line {index: len, t: LineType::Block});
+                                                    cblock.blocks.push(new_block);
+                                                }
+                                                None if creatingBlock == BlockType::Elif => {
+                                                    return Err(ParserError::HangingElif(tk.span));
+                                                }
+                                                None => { 
+                                                    new_block.parentDirectory.push(BlockParent{
+                                                        index: p,
+                                                        t: BlockParentType::Procedure
+                                                    });
+                                                    new_block.distance = 1;
+
+                                                    //push block to parent
+                                                    let len = program.procs[p].blocks.len();
+
+                                                    //set cblock and reset expr
+                                                    let mut cblock = new_block.parentDirectory.clone();
+                                                    cblock.push(BlockParent{
+                                                        t: BlockParentType::Block, 
+                                                        index:len
+                                                    });
+                                                    current_block = Some(cblock);
+
+                                                    //push block to parent
+                                                    program.procs[p].lines.push(Line {index: len, t: LineType::Block});
+                                                    program.procs[p].blocks.push(new_block);
+                                                }
+                                            }
+                                            expr = None;
                                         }
+                                        None => return Err(ParserError::AttemptedBlockInProgram(BlockType::If, tk.span))
                                     }
-                                    _ => return Err(ParserError::AttemptedExpressionInProgram)
+                                    creatingBlock = BlockType::None;
+                                }
+                                BlockType::None => {
+                                    match expr {
+                                        Some(ref mut exp) => exp.tks.push(tk),
+                                        None => return Err(ParserError::StrayValue(tk.tk_data.to_string(), tk.span))
+                                    };
                                 }
+                                _ => return Err(ParserError::UnimplementedBlockType(creatingBlock, tk.span))
                             }
-                            _ => return Err(ParserError::AttemptedExpressionInProgram)
                         }
-                        if pushExpr{
-                            program.expressions.push(unpkg_expr.clone());
+                        _ => {
+                            match expr {
+                                Some(ref mut exp) => exp.tks.push(tk),
+                                None => return Err(ParserError::StrayValue(tk.tk_data.to_string(), tk.span))
+                            };
                         }
                     }
-                };
-                expr = None;
-                if resolvableErrors.len() != 0 {
-                    return Err(resolvableErrors[0].clone());
                 }
-            }
-            TokenType::KeywordIf => {
-                tk=tk_iter.next().unwrap(); //next token
-                if tk.tk_data != "(" {return Err(ParserError::NoParaConBlockDef);}
-                creatingBlock = BlockType::If;
-            }
-            TokenType::KeywordElse => {
-                match current_proc { 
-                    Some(p) => {
-                        let mut new_block: Block<'a> = Default::default();
-                        new_block.block_type = BlockType::Else;
-
-                        //load up parentDirectory, if of block copy partent and add parent index
-                        match current_block {
-                            Some(ref dir) =>{ 
-                                new_block.parentDirectory = dir.clone();
-                                let mut cblock = program.getBlock_mut(dir);
-                                new_block.distance = cblock.distance + 1;
-
-                                if cblock.block_type != BlockType::If {return Err(ParserError::HangingElse);}
-
-                                //push block to parent
-                                let len = cblock.blocks.len();
-
-                                //set cblock and reset expr
-                                let mut cdir = new_block.parentDirectory.clone();
-                                cdir.push(BlockParent{
-                                    t: BlockParentType::Block, 
-                                    index:len
-                                });
-                                current_block = Some(cdir);
-
-                                //push block to parent
-                                cblock.lines.push(Line {index: len, t: LineType::Block});
-                                cblock.blocks.push(new_block);
+                TokenType::KeywordUint | TokenType::KeywordShort |
+                TokenType::KeywordInt | TokenType::KeywordSShort |
+                TokenType::KeywordChar | TokenType::KeywordString |
+                TokenType::KeywordBuffer if isCast(&expr, tk_iter.peek().map(|t| &**t)) => {
+                    match expr {
+                        Some(ref mut exp) => exp.tks.push(tk),
+                        None => return Err(ParserError::StrayValue(tk.tk_data.to_string(), tk.span))
+                    };
+                }
+                TokenType::KeywordUint => {
+                    declareVariable!(current_var_def, nextDAT, DataValueType::Uint, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                TokenType::KeywordShort => {
+                    declareVariable!(current_var_def, nextDAT, DataValueType::Short, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                TokenType::KeywordInt => {
+                    declareVariable!(current_var_def, nextDAT, DataValueType::Int, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                TokenType::KeywordSShort => {
+                    declareVariable!(current_var_def, nextDAT, DataValueType::SShort, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                TokenType::KeywordChar => {
+                    declareVariable!(current_var_def, nextDAT, DataValueType::Char, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                TokenType::KeywordString => {
+                    declareVariable!(current_var_def, nextDAT, DataValueType::String, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                TokenType::KeywordBuffer => {
+                    declareVariable!(current_var_def, nextDAT, DataValueType::Buffer, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                // ptr<T> between ( and ) is a cast, the type goes along on the token
+                TokenType::KeywordPtr if expr.is_some() => {
+                    let t = pointerType(tk.span, &mut tk_iter, &mut program)?;
+                    if !isCast(&expr, tk_iter.peek().map(|t| &**t)) {return Err(ParserError::TypeInExpression(t, tk.span));}
+                    tk.tk_comp_data = TokenCompData::Type(t);
+                    match expr {
+                        Some(ref mut exp) => exp.tks.push(tk),
+                        None => return Err(ParserError::StrayValue(tk.tk_data.to_string(), tk.span))
+                    };
+                }
+                TokenType::KeywordPtr => {
+                    let dvt = pointerType(tk.span, &mut tk_iter, &mut program)?;
+                    declareVariable!(current_var_def, nextDAT, dvt, tk.span, tk_iter, expr, program, current_proc, current_block);
+                    nextDAT = DataAllocationType::Stack(0);
+                }
+                TokenType::KeywordStruct => {
+                    if current_proc.is_some() {return Err(ParserError::StructInProc(tk.span));}
+                    let name = match tk_iter.next() {
+                        Some(n) => n,
+                        None => return Err(ParserError::MissingEndStatement(tk.span)),
+                    };
+                    if clashes(&program.structs, name.tk_data, name.span.file, nextPub) {
+                        return Err(ParserError::RedefinitionOfVariable(name.tk_data.to_string(), name.span));
+                    }
+                    program.structs.push(StructDef {label: name.tk_data, fields: vec![], size: 0, module: name.span.file, public: nextPub});
+                    nextPub = false;
+                    current_struct = Some(program.structs.len()-1);
+                }
+                // enum Dir: uint UP = 0 DOWN end, a member without a value is one more than the one before
+                TokenType::KeywordEnum => {
+                    if current_proc.is_some() || expr.is_some() {
+                        skipEnumBody(&mut tk_iter);
+                        resync = false;
+                        return Err(ParserError::InvalidEnum(tk.span));
+                    }
+                    let name = match tk_iter.next() {
+                        Some(n) if n.tk_type == TokenType::UnidentifiedLabel => n,
+                        _ => {
+                            skipEnumBody(&mut tk_iter);
+                            resync = false;
+                            return Err(ParserError::InvalidEnum(tk.span));
+                        }
+                    };
+                    let v = match (tk_iter.next_if(|t| t.tk_data == ":"), tk_iter.next().map(|t| t.tk_type)) {
+                        (Some(_), Some(TokenType::KeywordUint)) => DataValueType::Uint,
+                        (Some(_), Some(TokenType::KeywordShort)) => DataValueType::Short,
+                        (Some(_), Some(TokenType::KeywordInt)) => DataValueType::Int,
+                        (Some(_), Some(TokenType::KeywordSShort)) => DataValueType::SShort,
+                        (Some(_), Some(TokenType::KeywordChar)) => DataValueType::Char,
+                        _ => {
+                            skipEnumBody(&mut tk_iter);
+                            resync = false;
+                            return Err(ParserError::InvalidEnum(name.span));
+                        }
+                    };
+                    if clashes(&program.enums, name.tk_data, name.span.file, nextPub) || clashes(&program.structs, name.tk_data, name.span.file, nextPub) {
+                        errors.push(ParserError::RedefinitionOfVariable(name.tk_data.to_string(), name.span));
+                    }
+                    let mut def = EnumDef {label: name.tk_data, v, members: vec![], module: name.span.file, public: nextPub};
+                    nextPub = false;
+                    let mut next: i32 = 0;
+                    // a bad member is reported and the rest of the enum still declared
+                    loop {
+                        let member = match tk_iter.next() {
+                            Some(m) if m.tk_type == TokenType::KeywordEnd => break,
+                            Some(m) if m.tk_type == TokenType::UnidentifiedLabel => m,
+                            Some(m) => {
+                                errors.push(ParserError::InvalidEnum(m.span));
+                                continue;
                             }
-                            None => { 
-                                return Err(ParserError::HangingElse);
+                            None => return Err(ParserError::MissingEndStatement(name.span)),
+                        };
+                        if tk_iter.next_if(|t| t.tk_type == TokenType::OpAssign).is_some() {
+                            let value_tk = match tk_iter.next_if(|t| t.tk_type != TokenType::KeywordEnd) {
+                                Some(t) => t,
+                                None => {
+                                    errors.push(ParserError::InvalidEnum(member.span));
+                                    continue;
+                                }
+                            };
+                            match constNumber(&program, value_tk) {
+                                Ok(Some(c)) => next = c,
+                                Ok(None) => errors.push(ParserError::InvalidEnum(value_tk.span)),
+                                Err(e) => errors.push(e),
                             }
                         }
+                        if def.members.iter().any(|m| m.0 == member.tk_data) {
+                            errors.push(ParserError::RedefinitionOfVariable(member.tk_data.to_string(), member.span));
+                        } else if !v.fits(next) {
+                            errors.push(ParserError::ConstOutOfRange(next, v, member.span));
+                        }
+                        def.members.push((member.tk_data, next));
+                        next = next.saturating_add(1);
                     }
-                    None => { 
-                        return Err(ParserError::HangingElse);
+                    program.enums.push(def);
+                }
+                TokenType::KeywordPub => {
+                    let declares = matches!(tk_iter.peek().map(|t| t.tk_type), Some(
+                        TokenType::KeywordProc | TokenType::KeywordStruct | TokenType::KeywordEnum |
+                        TokenType::KeywordHeap | TokenType::KeywordStatic | TokenType::KeywordConst |
+                        TokenType::KeywordLABEL
+                    ));
+                    if current_proc.is_some() || !declares {return Err(ParserError::MisplacedPub(tk.span));}
+                    nextPub = true;
+                }
+                // raw data in the ROM, the name can be used like a static buffer
+                TokenType::KeywordLABEL => {
+                    if current_proc.is_some() || expr.is_some() {
+                        tk_iter.next_if(|t| t.tk_type != TokenType::KeywordRaw && t.tk_type != TokenType::AsmText);
+                        if tk_iter.next_if(|t| t.tk_type == TokenType::KeywordRaw).is_some() {
+                            skipAsmBody(&mut tk_iter);
+                            resync = false;
+                        }
+                        return Err(ParserError::InvalidRawLabel(tk.span));
+                    }
+                    let name = match tk_iter.next() {
+                        Some(n) if n.tk_type == TokenType::UnidentifiedLabel => n,
+                        Some(n) if n.tk_type == TokenType::AsmText || n.tk_type == TokenType::KeywordRaw => return Err(ParserError::InvalidRawLabel(n.span)),
+                        Some(n) => return Err(ParserError::RedefinitionOfVariable(n.tk_data.to_string(), n.span)),
+                        None => return Err(ParserError::InvalidRawLabel(tk.span)),
+                    };
+                    let taken = grabVariable!(name.tk_data, name.span.file, program, None::<usize>, None::<Vec<BlockParent>>)
+                        .is_some_and(|v| v.module == name.span.file || (nextPub && v.public));
+                    if taken {
+                        if tk_iter.next_if(|t| t.tk_type == TokenType::KeywordRaw).is_some() {
+                            skipAsmBody(&mut tk_iter);
+                            resync = false;
+                        }
+                        return Err(ParserError::RedefinitionOfVariable(name.tk_data.to_string(), name.span));
+                    }
+                    match tk_iter.next() {
+                        Some(r) if r.tk_type == TokenType::KeywordRaw => (),
+                        _ => return Err(ParserError::InvalidRawLabel(name.span)),
+                    }
+                    let mut words: Vec<&'a Token<'a>> = vec![];
+                    loop {
+                        match tk_iter.next() {
+                            Some(w) if w.tk_type == TokenType::AsmText => words.push(w),
+                            Some(w) if w.tk_type == TokenType::KeywordEnd => break,
+                            _ => return Err(ParserError::MissingEndStatement(name.span)),
+                        }
                     }
+                    program.static_variables.push(Variable {
+                        t: DataType {a: DataAllocationType::Static, v: DataValueType::Buffer},
+                        value: None,
+                        label: name.tk_data,
+                        module: name.span.file,
+                        public: nextPub
+                    });
+                    nextPub = false;
+                    program.raw_labels.push(RawLabel {label: name.tk_data, module: name.span.file, words});
                 }
-            }
-            TokenType::KeywordWhile => {
-                tk=tk_iter.next().unwrap(); //next token
-                if tk.tk_data != "(" {return Err(ParserError::NoParaConBlockDef);}
-                creatingBlock = BlockType::While;
-            }
-            TokenType::Symbol => {
-                match tk.tk_data {
-                    ")" => {
-                        match creatingBlock{
-                            BlockType::While |
-                            BlockType::If => {
-                                match current_proc { 
-                                    Some(p) => {
-                                        let mut unpkg_expr = match expr {
-                                            Some(ref mut exp) => exp,
-                                            None => return Err(ParserError::UnnecessarySemicolon)
-                                        };
-                                        unpkg_expr.t = match creatingBlock {
-                                            BlockType::If => ExpressionType::ConditionalIf,
-                                            BlockType::While => ExpressionType::ConditionalWhile,
-                                            _ => return Err(ParserError::UnidentifiedError)
-                                        };
-
-                                        let mut new_block: Block<'a> = Default::default();
-                                        new_block.con = Some(unpkg_expr.clone());
-                                        new_block.block_type = creatingBlock;
-
-                                        //load up parentDirectory, if of block copy partent and add parent index
-                                        match current_block {
-                                            Some(ref dir) =>{ 
-                                                new_block.parentDirectory = dir.clone();
-                                                let mut cblock = program.getBlock_mut(dir);
-                                                new_block.distance = cblock.distance + 1;
-
-                                                //push block to parent
-                                                let len = cblock.blocks.len();
-
-                                                //set cblock and reset expr
-                                                let mut cdir = new_block.parentDirectory.clone();
-                                                cdir.push(BlockParent{
-                                                    t: BlockParentType::Block, 
-                                                    index:len
-                                                });
-                                                current_block = Some(cdir);
-
-                                                //push block to parent
-                                                cblock.lines.push(Line {index: len, t: LineType::Block});
-                                                cblock.blocks.push(new_block);
-                                            }
-                                            None => { 
-                                                new_block.parentDirectory.push(BlockParent{
-                                                    index: p,
-                                                    t: BlockParentType::Procedure
-                                                });
-                                                new_block.distance = 1;
-
-                                                //push block to parent
-                                                let len = program.procs[p].blocks.len();
-
-                                                //set cblock and reset expr
-                                                let mut cblock = new_block.parentDirectory.clone();
-                                                cblock.push(BlockParent{
-                                                    t: BlockParentType::Block, 
-                                                    index:len
-                                                });
-                                                current_block = Some(cblock);
-
-                                                //push block to parent
-                                                program.procs[p].lines.push(Line {index: len, t: LineType::Block});
-                                                program.procs[p].blocks.push(new_block);
-                                            }
-                                        }
-                                        expr = None;
+                // KASM spliced into the proc, a word naming a variable becomes its operand
+                TokenType::KeywordAsm => {
+                    let p = match current_proc {
+                        Some(p) if expr.is_none() => p,
+                        _ => {
+                            skipAsmBody(&mut tk_iter);
+                            resync = false;
+                            return Err(ParserError::InvalidAsm(tk.span));
+                        }
+                    };
+                    let mut asm_expr: Expression = Expression {t: ExpressionType::Asm, ..Default::default()};
+                    loop {
+                        let word = match tk_iter.next() {
+                            Some(w) if w.tk_type == TokenType::AsmText => w,
+                            Some(w) if w.tk_type == TokenType::KeywordEnd => break,
+                            _ => return Err(ParserError::MissingEndStatement(tk.span)),
+                        };
+                        if let Some(v) = grabVariableSetToken!(word, program, current_proc, current_block) {
+                            // reported here, an early return would leave the end to close the proc
+                            if let Err(e) = checkVisible(v.module, v.public, word) {errors.push(e);}
+                            word.tk_type = TokenType::Variable;
+                            if v.t.a == DataAllocationType::Const {
+                                match v.value {
+                                    Some(c) => {
+                                        word.tk_type = TokenType::NumberLiteral;
+                                        word.tk_comp_data = TokenCompData::Const(c);
                                     }
-                                    None => return Err(ParserError::AttemptedBlockInProgram(BlockType::If))
+                                    None => errors.push(ParserError::ConstNoInitial(word.tk_data.to_string(), word.span))
                                 }
-                                creatingBlock = BlockType::None;
                             }
-                            BlockType::None => {
-                                match expr {
-                                    Some(ref mut exp) => exp.tks.push(tk),
-                                    None => return Err(ParserError::StrayValue(tk.tk_data.to_string()))
-                                };
-                            }
-                            _ => return Err(ParserError::UnimplementedBlockType(creatingBlock))
                         }
+                        asm_expr.tks.push(word);
                     }
-                    _ => {
-                        match expr {
-                            Some(ref mut exp) => exp.tks.push(tk),
-                            None => return Err(ParserError::StrayValue(tk.tk_data.to_string()))
-                        };
+                    match current_block {
+                        Some(ref directory) => {
+                            let block = program.getBlock_mut(directory);
+                            let len = block.expressions.len();
+                            block.lines.push(Line {index: len, t: LineType::Expression});
+                            block.expressions.push(asm_expr);
+                        }
+                        None => {
+                            let len = program.procs[p].expressions.len();
+                            program.procs[p].lines.push(Line {index: len, t: LineType::Expression});
+                            program.procs[p].expressions.push(asm_expr);
+                        }
                     }
                 }
-            }
-            TokenType::KeywordUint => {
-                declareVariable!(current_var_def, nextDAT, DataValueType::Uint, tk_iter, expr, program, current_proc);
-                nextDAT = DataAllocationType::Stack(0);
-            }
-            TokenType::KeywordShort => {
-                declareVariable!(current_var_def, nextDAT, DataValueType::Short, tk_iter, expr, program, current_proc);
-                nextDAT = DataAllocationType::Stack(0);
-            }
-            TokenType::KeywordString => {
-                declareVariable!(current_var_def, nextDAT, DataValueType::String, tk_iter, expr, program, current_proc);
-                nextDAT = DataAllocationType::Stack(0);
-            }
-            TokenType::KeywordBuffer => {
-                declareVariable!(current_var_def, nextDAT, DataValueType::Buffer, tk_iter, expr, program, current_proc);
-                nextDAT = DataAllocationType::Stack(0);
-            }
-            TokenType::KeywordConst => {
-                nextDAT = DataAllocationType::Const;
-            }
-            TokenType::KeywordHeap => {
-                nextDAT = DataAllocationType::Heap(0);
-            }
-            TokenType::KeywordStatic => {
-                nextDAT = DataAllocationType::Static;
-            }
-            TokenType::OpAssign => {
-                match expr {
-                    Some(ref mut exp) =>{
-                        exp.t = ExpressionType::Assignment;
-                        exp.tks.push(tk);
+                TokenType::KeywordRaw => {
+                    skipAsmBody(&mut tk_iter);
+                    resync = false;
+                    return Err(ParserError::InvalidRawLabel(tk.span));
+                }
+                // the file was already loaded by the module loader, its tokens are in front of these
+                TokenType::KeywordImport => {
+                    if current_proc.is_some() || expr.is_some() {return Err(ParserError::InvalidImport(tk.span));}
+                    match tk_iter.next() {
+                        Some(path) if path.tk_type == TokenType::StringLiteral => (),
+                        _ => return Err(ParserError::InvalidImport(tk.span)),
                     }
-                    None => return Err(ParserError::StrayAssignment)
-                };
-            }
-            TokenType::OpEq | TokenType::OpNEq | 
-            TokenType::OpLessEq | TokenType::OpGreatEq | 
-            TokenType::OpLess | TokenType::OpGreat |
-            TokenType::OpAdd |
-            TokenType::OpSubtract => {
-                match expr {
-                    Some(ref mut exp) => exp.tks.push(tk),
-                    None => return Err(ParserError::StrayOperator(tk.tk_data.to_string()))
-                };
-            }
-            TokenType::CharLiteral |
-            TokenType::StringLiteral |
-            TokenType::NumberLiteral |
-            TokenType::HexNumberLiteral => {
-                match expr {
-                    Some(ref mut exp) => exp.tks.push(tk),
-                    None =>{ 
-                        let mut built_expr: Expression = Default::default();
-                        built_expr.t = ExpressionType::Unspecified;
-                        built_expr.tks.push(tk);
-                        
-                        expr = Some(built_expr);
-                        warnings.push(ParserWarning::WarningPossibleStrayValue(tk.tk_data.to_string()))
+                    match tk_iter.next() {
+                        Some(end) if end.tk_type == TokenType::SymbolSemicolon => (),
+                        _ => return Err(ParserError::InvalidImport(tk.span)),
                     }
-                };
-            }
-            TokenType::EmbeddedFunction |
-            TokenType::Register => {
-                // write identify method in proc to do this
-                match expr {
-                    Some(ref mut exp) => exp.tks.push(tk),
-                    None => {
-                        let mut built_expr: Expression = Default::default();
-                        built_expr.t = ExpressionType::Unspecified;
-                        built_expr.tks.push(tk);
+                }
+                TokenType::KeywordConst => {
+                    nextDAT = DataAllocationType::Const;
+                }
+                TokenType::KeywordHeap => {
+                    nextDAT = DataAllocationType::Heap(0);
+                }
+                TokenType::KeywordStatic => {
+                    nextDAT = DataAllocationType::Static;
+                }
+                TokenType::OpAssign |
+                TokenType::OpAddAssign | TokenType::OpSubtractAssign |
+                TokenType::OpMultiplyAssign | TokenType::OpDivideAssign |
+                TokenType::OpModuloAssign |
+                TokenType::OpAndAssign | TokenType::OpOrAssign |
+                TokenType::OpXorAssign | TokenType::OpShiftLeftAssign |
+                TokenType::OpShiftRightAssign => {
+                    match expr {
+                        Some(ref mut exp) =>{
+                            exp.t = ExpressionType::Assignment;
+                            exp.tks.push(tk);
+                        }
+                        None => return Err(ParserError::StrayAssignment(tk.span))
+                    };
+                }
+                // x++ or ++x
+                TokenType::OpIncrement |
+                TokenType::OpDecrement => {
+                    match expr {
+                        Some(ref mut exp) =>{
+                            exp.t = ExpressionType::Assignment;
+                            exp.tks.push(tk);
+                        }
+                        None => {
+                            let mut built_expr: Expression = Expression {t: ExpressionType::Assignment, ..Default::default()};
+                            built_expr.tks.push(tk);
+
+                            expr = Some(built_expr);
+                        }
+                    };
+                }
+                TokenType::OpEq | TokenType::OpNEq | 
+                TokenType::OpLessEq | TokenType::OpGreatEq | 
+                TokenType::OpLess | TokenType::OpGreat |
+                TokenType::OpAdd |
+                TokenType::OpDivide | TokenType::OpModulo |
+                TokenType::OpOr |
+                TokenType::OpXor | TokenType::OpShiftLeft |
+                TokenType::OpShiftRight |
+                TokenType::OpLogicalAnd | TokenType::OpLogicalOr => {
+                    match expr {
+                        Some(ref mut exp) => exp.tks.push(tk),
+                        None => return Err(ParserError::StrayOperator(tk.tk_data.to_string(), tk.span))
+                    };
+                }
+                // can also be unary and start an expression, *p = v; or &x
+                TokenType::OpSubtract | TokenType::OpMultiply |
+                TokenType::OpAnd |
+                TokenType::OpNot |
+                TokenType::OpBitNot => {
+                    match expr {
+                        Some(ref mut exp) => exp.tks.push(tk),
+                        None => {
+                            let mut built_expr: Expression = Expression {t: ExpressionType::Unspecified, ..Default::default()};
+                            built_expr.tks.push(tk);
                         
-                        expr = Some(built_expr);
+                            expr = Some(built_expr);
+                        }
+                    };
+                }
+                TokenType::CharLiteral |
+                TokenType::StringLiteral |
+                TokenType::NumberLiteral |
+                TokenType::HexNumberLiteral => {
+                    match expr {
+                        Some(ref mut exp) => exp.tks.push(tk),
+                        None =>{ 
+                            let mut built_expr: Expression = Expression {t: ExpressionType::Unspecified, ..Default::default()};
+                            built_expr.tks.push(tk);
+                        
+                            expr = Some(built_expr);
+                            // a literal is a fine condition on its own
+                            if creatingBlock == BlockType::None {
+                                warnings.push(ParserWarning::WarningPossibleStrayValue(tk.tk_data.to_string(), tk.span))
+                            }
+                        }
+                    };
+                }
+                TokenType::EmbeddedFunction |
+                TokenType::Register => {
+                    // write identify method in proc to do this
+                    match expr {
+                        Some(ref mut exp) => exp.tks.push(tk),
+                        None => {
+                            let mut built_expr: Expression = Expression {t: ExpressionType::Unspecified, ..Default::default()};
+                            built_expr.tks.push(tk);
+                        
+                            expr = Some(built_expr);
+                        }
+                    };
+                }
+                TokenType::UnidentifiedLabel => {
+                    if let Some(e) = findEnum(&program, tk)? {
+                        let t = program.enumType(e);
+                        // Dir::UP is used as the number it stands for, the enum goes along for the checker
+                        if tk_iter.next_if(|t| t.tk_data == "::").is_some() {
+                            let member = match tk_iter.next() {
+                                Some(m) => m,
+                                None => return Err(ParserError::UnknownEnumMember(tk.tk_data.to_string(), String::new(), tk.span)),
+                            };
+                            let value = match program.enums[e].members.iter().find(|m| m.0 == member.tk_data) {
+                                Some(m) => m.1,
+                                None => return Err(ParserError::UnknownEnumMember(tk.tk_data.to_string(), member.tk_data.to_string(), member.span)),
+                            };
+                            tk.tk_type = TokenType::NumberLiteral;
+                            tk.tk_comp_data = TokenCompData::Member(e, value);
+                            tk.span = tk.span.to(member.span);
+                            match expr {
+                                Some(ref mut exp) => exp.tks.push(tk),
+                                None => {
+                                    let mut built_expr: Expression = Expression {t: ExpressionType::Unspecified, ..Default::default()};
+                                    built_expr.tks.push(tk);
+
+                                    expr = Some(built_expr);
+                                }
+                            };
+                            return Ok(());
+                        }
+                        // (Dir) x
+                        if isCast(&expr, tk_iter.peek().map(|t| &**t)) {
+                            tk.tk_comp_data = TokenCompData::Type(t);
+                            match expr {
+                                Some(ref mut exp) => exp.tks.push(tk),
+                                None => return Err(ParserError::StrayValue(tk.tk_data.to_string(), tk.span))
+                            };
+                            return Ok(());
+                        }
+                        declareVariable!(current_var_def, nextDAT, t, tk.span, tk_iter, expr, program, current_proc, current_block);
+                        nextDAT = DataAllocationType::Stack(0);
+                        return Ok(());
                     }
-                };
-            }
-            TokenType::UnidentifiedLabel => {
-                // identify
-                let _variable = match grabVariableSetToken!(tk, program, current_proc){
-                    Some(v) => {
-                        tk.tk_type = TokenType::Variable;
-                        if v.t.a == DataAllocationType::Const {
-                            match v.value {
-                                Some(ref value_tk) => {
-                                    tk.tk_type = value_tk.tk_type;
-                                    tk.tk_data = value_tk.tk_data;
+                    // a struct name starts a declaration
+                    if expr.is_none() {
+                        if let Some(i) = findStruct(&program, tk)? {
+                            declareVariable!(current_var_def, nextDAT, DataValueType::Struct(i), tk.span, tk_iter, expr, program, current_proc, current_block);
+                            nextDAT = DataAllocationType::Stack(0);
+                            return Ok(());
+                        }
+                    }
+                    // the name after a . is looked up in the struct once the types are known
+                    if let Some(ref mut exp) = expr {
+                        if exp.tks.last().is_some_and(|t| t.tk_type == TokenType::Symbol && t.tk_data == ".") {
+                            tk.tk_type = TokenType::Field;
+                            exp.tks.push(tk);
+                            return Ok(());
+                        }
+                    }
+                    // identify
+                    // an elif condition can not see into the blocks of its chain
+                    let scope = match creatingBlock {
+                        BlockType::Elif => current_block.as_ref().and_then(|dir| chainParent(&program, dir)),
+                        _ => current_block.clone()
+                    };
+                    match grabVariableSetToken!(tk, program, current_proc, scope){
+                        Some(v) => {
+                            checkVisible(v.module, v.public, tk)?;
+                            tk.tk_type = TokenType::Variable;
+                            if v.t.a == DataAllocationType::Const {
+                                match v.value {
+                                    // the name stays for messages, the value is read from the comp data
+                                    Some(c) => {
+                                        tk.tk_type = TokenType::NumberLiteral;
+                                        tk.tk_comp_data = TokenCompData::Const(c);
+                                    }
+                                    None => resolvableErrors.push(ParserError::ConstNoInitial(tk.tk_data.to_string(), tk.span))
                                 }
-                                None => resolvableErrors.push(ParserError::ConstNoInitial(tk.tk_data.to_string()))
                             }
                         }
-                    }
-                    // let case for other label types and if none of them are matched throw error
-                    None => {
-                        match grabProcedure!(tk.tk_data, program){
-                            Some(_) => {
-                                tk.tk_type = TokenType::ProcedureCall;
+                        // let case for other label types and if none of them are matched throw error
+                        None => {
+                            match grabProcedure!(tk.tk_data, tk.span.file, program){
+                                Some(p) => {
+                                    checkVisible(program.procs[p].module, program.procs[p].public, tk)?;
+                                    tk.tk_type = TokenType::ProcedureCall;
+                                    tk.tk_comp_data = TokenCompData::Proc(p);
+                                }
+                                None => return Err(ParserError::UnidentifiedLabel(tk.tk_data.to_string(), tk.span))
                             }
-                            None => return Err(ParserError::UnidentifiedLabel(tk.tk_data.to_string()))
                         }
-                    }
-                };
+                    };
                 
-                // write identify method in proc to do this
-                match expr {
-                    Some(ref mut exp) => exp.tks.push(tk),
-                    None => {
-                        let mut built_expr: Expression = Default::default();
-                        built_expr.t = ExpressionType::Unspecified;
-                        built_expr.tks.push(tk);
+                    // write identify method in proc to do this
+                    match expr {
+                        Some(ref mut exp) => exp.tks.push(tk),
+                        None => {
+                            let mut built_expr: Expression = Expression {t: ExpressionType::Unspecified, ..Default::default()};
+                            built_expr.tks.push(tk);
                         
-                        expr = Some(built_expr);
+                            expr = Some(built_expr);
+                        }
+                    };
+                }
+                _ => return Err(ParserError::UnidentifiedToken(tk.tk_data.to_string(), tk.span)),
+            }
+            return Ok(());
+        })();
+        index+=1;
+
+        if let Err(e) = result {
+            errors.push(e);
+            // the rest of a bad header is skipped up to its ), which still opens the block
+            // so the end of the block does not close the proc
+            if inHeader {
+                let mut depth = paren_depth;
+                while let Some(next) = tk_iter.peek() {
+                    if next.tk_type == TokenType::KeywordEnd || (next.tk_type == TokenType::SymbolSemicolon && forPart == ForPart::None) {
+                        inHeader = false;
+                        break;
                     }
-                };
+                    if next.tk_type == TokenType::Symbol && next.tk_data == ")" {
+                        if depth == 0 {break;}
+                        depth -= 1;
+                    }
+                    if next.tk_type == TokenType::Symbol && next.tk_data == "(" {depth += 1;}
+                    tk_iter.next();
+                    index+=1;
+                }
+                inHeader = inHeader && tk_iter.peek().is_some();
+            }
+            if inHeader {
+                paren_depth = 0;
+                current_var_def = None;
+                nextDAT = DataAllocationType::Stack(0);
+                resolvableErrors.clear();
+                if forPart != ForPart::None {
+                    forPart = ForPart::Step;
+                    creatingBlock = BlockType::For;
+                    expr = None;
+                }
+                else {expr = Some(Default::default());}
+                continue;
+            }
+            expr = None;
+            paren_depth = 0;
+            creatingBlock = BlockType::None;
+            forPart = ForPart::None;
+            current_var_def = None;
+            nextDAT = DataAllocationType::Stack(0);
+            nextPub = false;
+            resolvableErrors.clear();
+            if resync {
+                while let Some(next) = tk_iter.peek() {
+                    if next.tk_type == TokenType::KeywordEnd {break;}
+                    let skipped = tk_iter.next().unwrap();
+                    index+=1;
+                    if skipped.tk_type == TokenType::SymbolSemicolon {break;}
+                }
             }
-            _ => println!("Case not covered: {tk:?}"),
         }
-        index+=1;
     }
-    if current_proc.is_some() {return Err(ParserError::MissingEndStatement);}
-    return Ok(program);
+    if current_proc.is_some() {errors.push(ParserError::MissingEndStatement(proc_span));}
+    return program;
 }
\ No newline at end of file
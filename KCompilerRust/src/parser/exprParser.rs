@@ -0,0 +1,326 @@
+use crate::lexer::Token;
+use crate::lexer::TokenType;
+use crate::lexer::TokenCompData;
+use crate::lexer::Span;
+use crate::parser::ParserError;
+use crate::parser::parserTree::*;
+
+// binding power of a prefix operator, tighter than every binary operator
+const PREFIX_POWER: u8 = 30;
+
+// precedence of each binary operator, higher binds tighter
+// all binary operators are left associative
+fn binaryOp(t: TokenType) -> Option<(BinaryOp, u8)> {
+    match t {
+        TokenType::OpLogicalOr => return Some((BinaryOp::LogicalOr, 2)),
+        TokenType::OpLogicalAnd => return Some((BinaryOp::LogicalAnd, 3)),
+        TokenType::OpOr => return Some((BinaryOp::BitOr, 4)),
+        TokenType::OpXor => return Some((BinaryOp::BitXor, 5)),
+        TokenType::OpAnd => return Some((BinaryOp::BitAnd, 6)),
+        TokenType::OpEq => return Some((BinaryOp::Eq, 7)),
+        TokenType::OpNEq => return Some((BinaryOp::NEq, 7)),
+        TokenType::OpLess => return Some((BinaryOp::Less, 8)),
+        TokenType::OpGreat => return Some((BinaryOp::Great, 8)),
+        TokenType::OpLessEq => return Some((BinaryOp::LessEq, 8)),
+        TokenType::OpGreatEq => return Some((BinaryOp::GreatEq, 8)),
+        TokenType::OpShiftLeft => return Some((BinaryOp::ShiftLeft, 9)),
+        TokenType::OpShiftRight => return Some((BinaryOp::ShiftRight, 9)),
+        TokenType::OpAdd => return Some((BinaryOp::Add, 10)),
+        TokenType::OpSubtract => return Some((BinaryOp::Subtract, 10)),
+        TokenType::OpMultiply => return Some((BinaryOp::Multiply, 11)),
+        TokenType::OpDivide => return Some((BinaryOp::Divide, 11)),
+        TokenType::OpModulo => return Some((BinaryOp::Modulo, 11)),
+        _ => return None,
+    }
+}
+
+fn unaryOp(t: TokenType) -> Option<UnaryOp> {
+    match t {
+        TokenType::OpSubtract => return Some(UnaryOp::Negate),
+        TokenType::OpNot => return Some(UnaryOp::Not),
+        TokenType::OpBitNot => return Some(UnaryOp::BitNot),
+        TokenType::OpAnd => return Some(UnaryOp::AddressOf),
+        TokenType::OpMultiply => return Some(UnaryOp::Deref),
+        _ => return None,
+    }
+}
+
+// the type named by a cast, (short) x
+fn castType(tk: &Token<'_>) -> Option<DataValueType> {
+    match tk.tk_type {
+        // ptr<...> and enum names carry the type they name
+        TokenType::KeywordPtr |
+        TokenType::UnidentifiedLabel => {
+            match tk.tk_comp_data {
+                TokenCompData::Type(t) => return Some(t),
+                _ => return None,
+            }
+        }
+        TokenType::KeywordUint => return Some(DataValueType::Uint),
+        TokenType::KeywordShort => return Some(DataValueType::Short),
+        TokenType::KeywordInt => return Some(DataValueType::Int),
+        TokenType::KeywordSShort => return Some(DataValueType::SShort),
+        TokenType::KeywordChar => return Some(DataValueType::Char),
+        TokenType::KeywordString => return Some(DataValueType::String),
+        TokenType::KeywordBuffer => return Some(DataValueType::Buffer),
+        _ => return None,
+    }
+}
+
+// ++ and --, both before and after the variable
+fn stepOp(t: TokenType) -> Option<UnaryOp> {
+    match t {
+        TokenType::OpIncrement => return Some(UnaryOp::Increment),
+        TokenType::OpDecrement => return Some(UnaryOp::Decrement),
+        _ => return None,
+    }
+}
+
+// a &= b is read as a = a & b
+fn compoundOp(t: TokenType) -> Option<BinaryOp> {
+    match t {
+        TokenType::OpAddAssign => return Some(BinaryOp::Add),
+        TokenType::OpSubtractAssign => return Some(BinaryOp::Subtract),
+        TokenType::OpMultiplyAssign => return Some(BinaryOp::Multiply),
+        TokenType::OpDivideAssign => return Some(BinaryOp::Divide),
+        TokenType::OpModuloAssign => return Some(BinaryOp::Modulo),
+        TokenType::OpAndAssign => return Some(BinaryOp::BitAnd),
+        TokenType::OpOrAssign => return Some(BinaryOp::BitOr),
+        TokenType::OpXorAssign => return Some(BinaryOp::BitXor),
+        TokenType::OpShiftLeftAssign => return Some(BinaryOp::ShiftLeft),
+        TokenType::OpShiftRightAssign => return Some(BinaryOp::ShiftRight),
+        _ => return None,
+    }
+}
+
+// pratt parser over the tokens of one expression
+struct ExprParser<'t, 'a> {
+    tks: &'t [&'a Token<'a>],
+    pos: usize
+}
+
+impl<'t, 'a> ExprParser<'t, 'a> {
+    fn peek(&self) -> Option<&'a Token<'a>> {
+        return self.tks.get(self.pos).copied();
+    }
+
+    fn next(&mut self) -> Option<&'a Token<'a>> {
+        let tk = self.peek();
+        if tk.is_some() {self.pos += 1;}
+        return tk;
+    }
+
+    fn peekIs(&self, data: &str) -> bool {
+        match self.peek() {
+            Some(tk) => return tk.tk_type == TokenType::Symbol && tk.tk_data == data,
+            None => return false,
+        }
+    }
+
+    // span used when the expression ends early
+    fn endSpan(&self) -> Span {
+        match self.tks.last() {
+            Some(tk) => return tk.span,
+            None => return Default::default(),
+        }
+    }
+
+    fn expectSymbol(&mut self, data: &str) -> Result<&'a Token<'a>, ParserError> {
+        match self.next() {
+            Some(tk) if tk.tk_type == TokenType::Symbol && tk.tk_data == data => return Ok(tk),
+            Some(tk) => {
+                match data {
+                    ")" => return Err(ParserError::NoParaNotTerminated(tk.span)),
+                    "]" => return Err(ParserError::MissingClosingSqBracket(tk.span)),
+                    _ => return Err(ParserError::UnexpectedInExpression(tk.tk_data.to_string(), tk.span)),
+                }
+            }
+            None => return Err(ParserError::UnexpectedEndOfExpression(self.endSpan())),
+        }
+    }
+
+    fn parseExpr(&mut self, min_power: u8) -> Result<ExprNode<'a>, ParserError> {
+        let mut lhs = self.parsePrefix()?;
+
+        while let Some(tk) = self.peek() {
+            // assignment is right associative and binds loosest
+            if tk.tk_type == TokenType::OpAssign {
+                if min_power > 1 {break;}
+                self.next();
+                let rhs = self.parseExpr(1)?;
+                let span = lhs.span().to(rhs.span());
+                lhs = ExprNode::Assign(Box::new(lhs), Box::new(rhs), span);
+                continue;
+            }
+            if let Some(op) = compoundOp(tk.tk_type) {
+                if min_power > 1 {break;}
+                self.next();
+                let rhs = self.parseExpr(1)?;
+                let span = lhs.span().to(rhs.span());
+                let value = ExprNode::Binary(op, Box::new(lhs.clone()), Box::new(rhs), span);
+                lhs = ExprNode::Assign(Box::new(lhs), Box::new(value), span);
+                continue;
+            }
+
+            let (op, precedence) = match binaryOp(tk.tk_type) {
+                Some(o) => o,
+                None => break,
+            };
+            let left_power = precedence*2;
+            if left_power < min_power {break;}
+            self.next();
+            let rhs = self.parseExpr(left_power+1)?;
+            let span = lhs.span().to(rhs.span());
+            lhs = ExprNode::Binary(op, Box::new(lhs), Box::new(rhs), span);
+        }
+        return Ok(lhs);
+    }
+
+    fn parsePrefix(&mut self) -> Result<ExprNode<'a>, ParserError> {
+        let tk = match self.next() {
+            Some(tk) => tk,
+            None => return Err(ParserError::UnexpectedEndOfExpression(self.endSpan())),
+        };
+
+        if let Some(op) = stepOp(tk.tk_type) {
+            let operand = self.parseExpr(PREFIX_POWER)?;
+            let span = tk.span.to(operand.span());
+            return Ok(ExprNode::Unary(op, Box::new(operand), span));
+        }
+        if let Some(op) = unaryOp(tk.tk_type) {
+            let operand = self.parseExpr(PREFIX_POWER)?;
+            let span = tk.span.to(operand.span());
+            return Ok(ExprNode::Unary(op, Box::new(operand), span));
+        }
+
+        let node = match tk.tk_type {
+            TokenType::CharLiteral |
+            TokenType::StringLiteral |
+            TokenType::NumberLiteral |
+            TokenType::HexNumberLiteral => ExprNode::Literal(tk),
+            TokenType::Variable => ExprNode::Variable(tk),
+            TokenType::Register => ExprNode::Register(tk),
+            TokenType::ProcedureCall => {
+                if self.peekIs("(") {self.parseCall(tk)?}
+                else {ExprNode::Label(tk)}
+            }
+            TokenType::EmbeddedFunction => {
+                if !self.peekIs("(") {return Err(ParserError::NoParaProcCall(tk.tk_data.to_string(), tk.span));}
+                self.parseCall(tk)?
+            }
+            TokenType::Symbol => {
+                match tk.tk_data {
+                    "(" if self.peek().and_then(castType).is_some() => {
+                        let t = castType(self.next().unwrap()).unwrap();
+                        self.expectSymbol(")")?;
+                        // binds like a prefix operator, (short) a + b only casts a
+                        let operand = self.parseExpr(PREFIX_POWER)?;
+                        let span = tk.span.to(operand.span());
+                        return Ok(ExprNode::Cast(t, Box::new(operand), span));
+                    }
+                    "(" => {
+                        let inner = self.parseExpr(0)?;
+                        self.expectSymbol(")")?;
+                        inner
+                    }
+                    "[" => {
+                        let mut items: Vec<ExprNode<'a>> = vec![];
+                        while !self.peekIs("]") {
+                            if self.peek().is_none() {return Err(ParserError::MissingClosingSqBracket(self.endSpan()));}
+                            items.push(self.parseExpr(0)?);
+                            if self.peekIs(",") {self.next();}
+                        }
+                        let close = self.expectSymbol("]")?;
+                        ExprNode::List(items, tk.span.to(close.span))
+                    }
+                    _ => return Err(ParserError::ExpectedValue(tk.tk_data.to_string(), tk.span)),
+                }
+            }
+            _ => return Err(ParserError::ExpectedValue(tk.tk_data.to_string(), tk.span)),
+        };
+        return self.parsePostfix(node);
+    }
+
+    fn parsePostfix(&mut self, mut node: ExprNode<'a>) -> Result<ExprNode<'a>, ParserError> {
+        loop {
+            if self.peekIs("[") {
+                self.next();
+                let index = self.parseExpr(0)?;
+                let close = self.expectSymbol("]")?;
+                let span = node.span().to(close.span);
+                node = ExprNode::Index(Box::new(node), Box::new(index), span);
+            }
+            else if self.peekIs(".") {
+                let dot = self.next().unwrap();
+                let field = match self.next() {
+                    Some(tk) if tk.tk_type == TokenType::Field => tk,
+                    Some(tk) => return Err(ParserError::ExpectedField(tk.tk_data.to_string(), tk.span)),
+                    None => return Err(ParserError::ExpectedField(String::new(), dot.span)),
+                };
+                let span = node.span().to(field.span);
+                node = ExprNode::Field(Box::new(node), field, span);
+            }
+            else {break;}
+        }
+        if let Some(op) = self.peek().and_then(|tk| stepOp(tk.tk_type)) {
+            let tk = self.next().unwrap();
+            let span = node.span().to(tk.span);
+            node = ExprNode::Unary(op, Box::new(node), span);
+        }
+        return Ok(node);
+    }
+
+    // name ( arg, arg, ... )
+    fn parseCall(&mut self, name: &'a Token<'a>) -> Result<ExprNode<'a>, ParserError> {
+        self.expectSymbol("(")?;
+        let mut args: Vec<ExprNode<'a>> = vec![];
+        if !self.peekIs(")") {
+            loop {
+                args.push(self.parseExpr(0)?);
+                if self.peekIs(",") {
+                    self.next();
+                    continue;
+                }
+                break;
+            }
+        }
+        let close = self.expectSymbol(")")?;
+        return Ok(ExprNode::Call(name, args, name.span.to(close.span)));
+    }
+}
+
+// a ++ or -- anywhere in the node, x++ as a value would need the value from before the step
+fn findStep(node: &ExprNode<'_>) -> Option<(&'static str, Span)> {
+    match node {
+        ExprNode::Unary(UnaryOp::Increment, _, span) => return Some(("++", *span)),
+        ExprNode::Unary(UnaryOp::Decrement, _, span) => return Some(("--", *span)),
+        ExprNode::Unary(_, operand, _) |
+        ExprNode::Cast(_, operand, _) |
+        ExprNode::Field(operand, ..) => return findStep(operand),
+        ExprNode::Binary(_, lhs, rhs, _) |
+        ExprNode::Index(lhs, rhs, _) |
+        ExprNode::Assign(lhs, rhs, _) => return findStep(lhs).or(findStep(rhs)),
+        ExprNode::Call(_, args, _) |
+        ExprNode::List(args, _) => return args.iter().find_map(findStep),
+        _ => return None,
+    }
+}
+
+// builds the tree for the tokens of a single expression, every token has to be used
+pub fn buildExpressionTree<'a>(tks: &[&'a Token<'a>]) -> Result<ExprNode<'a>, ParserError> {
+    let mut parser = ExprParser {tks, pos: 0};
+    let node = parser.parseExpr(0)?;
+    if let Some(tk) = parser.peek() {
+        return Err(ParserError::UnexpectedInExpression(tk.tk_data.to_string(), tk.span));
+    }
+    // only the whole statement can be a step
+    let inner = match node {
+        ExprNode::Unary(UnaryOp::Increment, ref operand, _) |
+        ExprNode::Unary(UnaryOp::Decrement, ref operand, _) => operand,
+        _ => &node,
+    };
+    match findStep(inner) {
+        Some((op, span)) => return Err(ParserError::StepAsValue(op.to_string(), span)),
+        None => return Ok(node),
+    }
+}
@@ -1,4 +1,8 @@
 use crate::lexer::Token;
+use crate::lexer::TokenType;
+use crate::lexer::TokenCompData;
+use crate::lexer::VarDest;
+use crate::lexer::Span;
 use crate::parser::ParserError;
 
 #[derive(Default, Debug, PartialEq, Clone, Copy)]
@@ -7,22 +11,78 @@ pub enum DataValueType{
     Void,
     Uint,
     Short,
+    Int,
+    SShort,
     Char,
     String,
-    Buffer
+    Buffer,
+    Struct(usize), //index into Program::structs
+    Array(usize), //index into Program::arrays
+    Pointer(usize), //index into Program::pointers, the type pointed at
+    Enum(usize, &'static DataValueType) //index into Program::enums and the number type it is held as
 }
 
 impl DataValueType {
     pub fn size(self) -> Option<u8> {
         match self {
             DataValueType::Uint |
+            DataValueType::Int |
             DataValueType::Char => Some(1),
-            DataValueType::Short => Some(2),
+            DataValueType::Short |
+            DataValueType::SShort => Some(2),
             DataValueType::String |
-            DataValueType::Buffer => Some(2), //size of the pointer
+            DataValueType::Buffer |
+            DataValueType::Pointer(_) => Some(2), //size of the pointer
+            DataValueType::Enum(_, v) => v.size(),
             _ => None,
         }
     }
+
+    // 16-bit numbers and pointers, held in AX
+    pub fn isWord(self) -> bool {
+        match self {
+            DataValueType::Short |
+            DataValueType::SShort |
+            DataValueType::Pointer(_) => return true,
+            DataValueType::Enum(_, v) => return v.isWord(),
+            _ => return false,
+        }
+    }
+
+    pub fn isSigned(self) -> bool {
+        match self {
+            DataValueType::Int |
+            DataValueType::SShort => return true,
+            DataValueType::Enum(_, v) => return v.isSigned(),
+            _ => return false,
+        }
+    }
+
+    // whether the constant can be held without changing its value
+    pub fn fits(self, c: i32) -> bool {
+        match self {
+            DataValueType::Uint |
+            DataValueType::Char => return (0..=0xFF).contains(&c),
+            DataValueType::Int => return (-128..=127).contains(&c),
+            DataValueType::Short => return (0..=0xFFFF).contains(&c),
+            DataValueType::SShort => return (-32768..=32767).contains(&c),
+            // null
+            DataValueType::Pointer(_) => return c == 0,
+            DataValueType::Enum(_, v) => return v.fits(c),
+            _ => return false,
+        }
+    }
+}
+
+// calling convention, see documentation/callingconvention.txt
+// the first argument is passed in A (A and X for a short), the second in Y when it is 1 byte
+// the rest are put on the stack by the caller
+pub fn argInRegister(index: usize, v: DataValueType) -> bool {
+    match index {
+        0 => return true,
+        1 => return v.size() == Some(1),
+        _ => return false,
+    }
 }
 
 #[derive(Default, Debug, PartialEq, Clone, Copy)]
@@ -56,11 +116,108 @@ pub struct DataType{
     pub v: DataValueType
 }
 
+// fields are laid out in the order they are declared, offset is from the start of the struct
+#[derive(Default, Debug, PartialEq, Clone, Copy)]
+pub struct StructField<'a>{
+    pub label: &'a str,
+    pub v: DataValueType,
+    pub offset: u8
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct StructDef<'a>{
+    pub label: &'a str,
+    pub fields: Vec<StructField<'a>>,
+    pub size: u8,
+    pub module: usize, //file it is declared in
+    pub public: bool //declared pub, other files can use it
+}
+
+// enum Dir: uint UP DOWN end, the members are numbers counting up from the last one given
+#[derive(Default, Debug, Clone)]
+pub struct EnumDef<'a>{
+    pub label: &'a str,
+    pub v: DataValueType, //number type the values are held as
+    pub members: Vec<(&'a str, i32)>,
+    pub module: usize, //file it is declared in
+    pub public: bool //declared pub, other files can use it
+}
+
+// N numbers one after the other, the first at the lowest address
+#[derive(Default, Debug, PartialEq, Clone, Copy)]
+pub struct ArrayDef{
+    pub v: DataValueType,
+    pub len: u8
+}
+
+// LABEL name raw ... end, the words go into the ROM as they are written
+#[derive(Default, Debug, Clone)]
+pub struct RawLabel<'a>{
+    pub label: &'a str,
+    pub module: usize, //file it is declared in
+    pub words: Vec<&'a Token<'a>>
+}
+
 #[derive(Default, Debug, PartialEq, Clone, Copy)]
 pub struct Variable<'a>{
     pub t: DataType,
-    pub value: Option<&'a Token<'a>>,
-    pub label: &'a str
+    pub value: Option<i32>, //a const is worked out when it is declared
+    pub label: &'a str,
+    pub module: usize, //file it is declared in
+    pub public: bool //declared pub, other files can use it
+}
+
+// what one file declares and another can name when it is pub
+pub trait Symbol {
+    fn name(&self) -> &str;
+    fn module(&self) -> usize;
+    fn public(&self) -> bool;
+}
+
+impl Symbol for Variable<'_> {
+    fn name(&self) -> &str {return self.label;}
+    fn module(&self) -> usize {return self.module;}
+    fn public(&self) -> bool {return self.public;}
+}
+
+impl Symbol for StructDef<'_> {
+    fn name(&self) -> &str {return self.label;}
+    fn module(&self) -> usize {return self.module;}
+    fn public(&self) -> bool {return self.public;}
+}
+
+impl Symbol for EnumDef<'_> {
+    fn name(&self) -> &str {return self.label;}
+    fn module(&self) -> usize {return self.module;}
+    fn public(&self) -> bool {return self.public;}
+}
+
+impl Symbol for Procedure<'_> {
+    fn name(&self) -> &str {return self.label;}
+    fn module(&self) -> usize {return self.module;}
+    fn public(&self) -> bool {return self.public;}
+}
+
+// the one a file means by the name, its own first and then a pub one of another file
+// a private one of another file is still found so it can be reported as not pub
+pub fn findSymbol<T: Symbol>(symbols: &[T], label: &str, file: usize) -> Option<usize> {
+    return symbols.iter().position(|s| s.name() == label && s.module() == file)
+        .or_else(|| symbols.iter().position(|s| s.name() == label && s.public()))
+        .or_else(|| symbols.iter().position(|s| s.name() == label));
+}
+
+// a declaration clashes with the file's own names, a pub one also with the pub names of other files
+pub fn clashes<T: Symbol>(symbols: &[T], label: &str, file: usize, public: bool) -> bool {
+    return symbols.iter().any(|s| s.name() == label && (s.module() == file || (public && s.public())));
+}
+
+// the .kasm label, a private one of an imported file named like another gets its file number after it
+pub fn kasmLabel<T: Symbol>(symbols: &[T], i: usize) -> String {
+    let s = &symbols[i];
+    if s.public() || s.module() == 0 || symbols.iter().filter(|o| o.name() == s.name()).count() == 1 {
+        return s.name().to_string();
+    }
+    return format!("{}__{}", s.name(), s.module());
 }
 
 #[derive(Default, Debug, PartialEq, Clone, Copy)]
@@ -70,22 +227,167 @@ pub enum ExpressionType{
     Return,
     Assignment,
     ConditionalIf,
-    ConditionalWhile
+    ConditionalWhile,
+    Break,
+    Continue,
+    Asm //asm ... end, the tks are the KASM words with names resolved to variables
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    LogicalAnd, //short circuits, only ever lowered to branches
+    LogicalOr,
+    Eq,
+    NEq,
+    Less,
+    Great,
+    LessEq,
+    GreatEq
+}
+
+impl BinaryOp {
+    pub fn isComparison(self) -> bool {
+        match self {
+            BinaryOp::Eq | BinaryOp::NEq |
+            BinaryOp::Less | BinaryOp::Great |
+            BinaryOp::LessEq | BinaryOp::GreatEq => return true,
+            _ => return false,
+        }
+    }
+
+    pub fn isLogical(self) -> bool {
+        return self == BinaryOp::LogicalAnd || self == BinaryOp::LogicalOr;
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UnaryOp {
+    Negate,
+    Not,
+    BitNot,
+    Increment, //++ and --, only ever the root of a statement
+    Decrement,
+    AddressOf, //&x
+    Deref //*p
+}
+
+// expression tree built by the parser, the compiler lowers from this
+#[derive(Debug, Clone)]
+pub enum ExprNode<'a> {
+    Literal(&'a Token<'a>),
+    Variable(&'a Token<'a>),
+    Register(&'a Token<'a>),
+    Label(&'a Token<'a>), //procedure used as a label, not called
+    List(Vec<ExprNode<'a>>, Span), //[ 1 2 3 ] raw data
+    Unary(UnaryOp, Box<ExprNode<'a>>, Span),
+    Binary(BinaryOp, Box<ExprNode<'a>>, Box<ExprNode<'a>>, Span),
+    Call(&'a Token<'a>, Vec<ExprNode<'a>>, Span), //procedure or embedded function
+    Index(Box<ExprNode<'a>>, Box<ExprNode<'a>>, Span),
+    Assign(Box<ExprNode<'a>>, Box<ExprNode<'a>>, Span),
+    Cast(DataValueType, Box<ExprNode<'a>>, Span), //(type) value
+    Field(Box<ExprNode<'a>>, &'a Token<'a>, Span) //struct.field
+}
+
+// the number a literal stands for, a const that was put in for its name gives its value
+pub fn literalNumber(tk: &Token<'_>) -> Option<i32> {
+    if let Some(c) = tk.tk_comp_data.constant() {return Some(c);}
+    match tk.tk_type {
+        TokenType::NumberLiteral |
+        TokenType::HexNumberLiteral |
+        TokenType::CharLiteral => (),
+        _ => return None,
+    }
+    let (sign, text) = match tk.tk_data.strip_prefix('-') {
+        Some(t) => (-1, t),
+        None => (1, tk.tk_data),
+    };
+    let v = match text.strip_prefix("0x") {
+        Some(hex) => i32::from_str_radix(hex, 16).ok()?,
+        None if text.starts_with('\'') => *text.as_bytes().get(1)? as i32,
+        None => text.parse::<i32>().ok()?,
+    };
+    return Some(sign * v);
+}
+
+impl<'a> ExprNode<'a> {
+    // arithmetic made only of literals and consts, worked out exactly so a value too big
+    // for where it goes can be reported, None when it is only known once the program runs
+    pub fn constant(&self) -> Option<i32> {
+        match self {
+            ExprNode::Literal(tk) => return literalNumber(tk),
+            ExprNode::Unary(UnaryOp::Negate, operand, _) => return operand.constant()?.checked_neg(),
+            ExprNode::Binary(op, lhs, rhs, _) => {
+                let (l, r) = (lhs.constant()?, rhs.constant()?);
+                match op {
+                    BinaryOp::Add => return l.checked_add(r),
+                    BinaryOp::Subtract => return l.checked_sub(r),
+                    BinaryOp::Multiply => return l.checked_mul(r),
+                    BinaryOp::BitAnd => return Some(l & r),
+                    BinaryOp::BitOr => return Some(l | r),
+                    BinaryOp::BitXor => return Some(l ^ r),
+                    BinaryOp::ShiftLeft if (0..16).contains(&r) => return Some(l << r),
+                    BinaryOp::ShiftRight if (0..16).contains(&r) => return Some(l >> r),
+                    BinaryOp::Divide => return l.checked_div(r),
+                    BinaryOp::Modulo => return l.checked_rem(r),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ExprNode::Literal(tk) |
+            ExprNode::Variable(tk) |
+            ExprNode::Register(tk) |
+            ExprNode::Label(tk) => return tk.span,
+            ExprNode::List(.., s) |
+            ExprNode::Unary(.., s) |
+            ExprNode::Binary(.., s) |
+            ExprNode::Call(.., s) |
+            ExprNode::Index(.., s) |
+            ExprNode::Assign(.., s) |
+            ExprNode::Cast(.., s) |
+            ExprNode::Field(.., s) => return *s,
+        }
+    }
 }
 
 #[derive(Default, Debug)]
 pub struct Expression<'a> {
     pub t: ExpressionType,
-    pub tks: Vec<&'a Token<'a>>
+    pub tks: Vec<&'a Token<'a>>,
+    pub tree: Option<ExprNode<'a>> //built once the expression is terminated
+}
+
+impl<'a> Expression<'a> {
+    // span from the first to the last token of the expression
+    pub fn span(&self) -> Span {
+        match (self.tks.first(), self.tks.last()) {
+            (Some(f), Some(l)) => return f.span.to(l.span),
+            _ => return Default::default(),
+        }
+    }
 }
 
 impl <'a> Clone for Expression<'a> {
     fn clone(&self) -> Expression<'a> {
-        let mut e : Expression<'a> = Default::default();
-        e.t = self.t.clone();
+        let mut e : Expression<'a> = Expression {t: self.t, ..Default::default()};
         for tk in &self.tks {
             e.tks.push(tk);
         }
+        e.tree = self.tree.clone();
         return e;
     }
 }
@@ -120,7 +422,7 @@ pub struct BlockParent {
 pub enum BlockType {
     #[default]
     None, 
-    If, While, Else, Elif, Anon 
+    If, While, Else, Elif, For, Anon 
 }
 
 #[derive(Default, Debug)]
@@ -129,6 +431,8 @@ pub struct Block<'a>{
     pub parentDirectory: Vec<BlockParent>,
     pub block_type: BlockType, 
     pub con: Option<Expression<'a>>, 
+    pub step: Option<Expression<'a>>, //run after every pass of a for
+    pub scope_start: usize, //first proc variable declared for a for, hidden once it ends
     pub allocated_bytes: u8,
     pub variables: Vec<Variable<'a>>,
     pub expressions: Vec<Expression<'a>>,
@@ -138,14 +442,14 @@ pub struct Block<'a>{
 
 impl<'a> Block<'a>{
     pub fn getBlock(&self, directory: &[BlockParent]) -> &Block<'a> {
-        if directory.len() == 0 {
+        if directory.is_empty() {
             return self;
         }
         return self.blocks[directory[0].index].getBlock(&directory[1..directory.len()]);
     }
 
     pub fn getBlock_mut(& mut self, directory: &[BlockParent]) -> & mut Block<'a> {
-        if directory.len() == 0 {
+        if directory.is_empty() {
             return self;
         }
         return self.blocks[directory[0].index].getBlock_mut(&directory[1..directory.len()]);
@@ -156,6 +460,8 @@ impl<'a> Block<'a>{
 pub struct Procedure<'a>{
     pub allocated_bytes: u8,
     pub label: &'a str,
+    pub module: usize, //file it is declared in
+    pub public: bool, //declared pub, other files can call it
     pub retType: DataType,
     pub arguments: Vec<Variable<'a>>,
     pub variables: Vec<Variable<'a>>,
@@ -165,6 +471,18 @@ pub struct Procedure<'a>{
 }
 
 impl<'a> Procedure<'a>{
+    // stack arguments sit above the frame and the return address, the last one pushed is the closest
+    // needs the size of the whole frame so it is done once the proc is closed
+    pub fn placeStackArguments(&mut self) {
+        let mut addr = self.allocated_bytes + 3;
+        for i in (0..self.arguments.len()).rev() {
+            let arg = &mut self.arguments[i];
+            if argInRegister(i, arg.t.v) {continue;}
+            arg.t.a = DataAllocationType::Stack(addr);
+            addr += arg.t.v.size().unwrap_or(1);
+        }
+    }
+
     pub fn getBlock(&self, directory: &[BlockParent]) -> &Block<'a> {
         return self.blocks[directory[0].index].getBlock(&directory[1..directory.len()]);
     }
@@ -181,10 +499,128 @@ pub struct Program<'a>{
     pub const_variables: Vec<Variable<'a>>,
     pub static_variables: Vec<Variable<'a>>,
     pub expressions: Vec<Expression<'a>>,
-    pub procs: Vec<Procedure<'a>>
+    pub raw_labels: Vec<RawLabel<'a>>,
+    pub procs: Vec<Procedure<'a>>,
+    pub structs: Vec<StructDef<'a>>,
+    pub enums: Vec<EnumDef<'a>>,
+    pub arrays: Vec<ArrayDef>,
+    pub pointers: Vec<DataValueType>
 }
 
 impl<'a> Program<'a>{
+    // depths of the blocks on the directory whose variables are in scope, innermost first
+    // an if is over once its elif or else is running
+    pub fn liveScopes(&self, directory: &[BlockParent]) -> Vec<usize> {
+        let mut depths: Vec<usize> = vec![];
+        for depth in (1..directory.len()).rev() {
+            if depth+1 < directory.len() 
+            && matches!(self.getBlock(&directory[..depth+2]).block_type, BlockType::Elif | BlockType::Else) {
+                continue;
+            }
+            depths.push(depth);
+        }
+        return depths;
+    }
+
+    // size of a value, a struct is as big as its fields
+    pub fn sizeOf(&self, v: DataValueType) -> Option<u8> {
+        match v {
+            DataValueType::Struct(i) => return self.structs.get(i).map(|s| s.size),
+            DataValueType::Array(i) => return self.arrays.get(i).map(|a| a.len * a.v.size().unwrap_or(1)),
+            _ => return v.size(),
+        }
+    }
+
+    // arrays of the same type and length share a def
+    pub fn arrayOf(&mut self, v: DataValueType, len: u8) -> DataValueType {
+        let def = ArrayDef {v, len};
+        match self.arrays.iter().position(|a| *a == def) {
+            Some(i) => return DataValueType::Array(i),
+            None => {
+                self.arrays.push(def);
+                return DataValueType::Array(self.arrays.len()-1);
+            }
+        }
+    }
+
+    pub fn pointerTo(&mut self, v: DataValueType) -> DataValueType {
+        match self.pointers.iter().position(|p| *p == v) {
+            Some(i) => return DataValueType::Pointer(i),
+            None => {
+                self.pointers.push(v);
+                return DataValueType::Pointer(self.pointers.len()-1);
+            }
+        }
+    }
+
+    // the type a pointer points at
+    pub fn pointee(&self, v: DataValueType) -> Option<DataValueType> {
+        match v {
+            DataValueType::Pointer(i) => return self.pointers.get(i).copied(),
+            _ => return None,
+        }
+    }
+
+    // the type of a value of the enum, the number type is kept on it so its size is known without the program
+    pub fn enumType(&self, i: usize) -> DataValueType {
+        let base: &'static DataValueType = match self.enums[i].v {
+            DataValueType::Short => &DataValueType::Short,
+            DataValueType::Int => &DataValueType::Int,
+            DataValueType::SShort => &DataValueType::SShort,
+            DataValueType::Char => &DataValueType::Char,
+            _ => &DataValueType::Uint,
+        };
+        return DataValueType::Enum(i, base);
+    }
+
+    pub fn structField(&self, v: DataValueType, label: &str) -> Option<&StructField<'a>> {
+        match v {
+            DataValueType::Struct(i) => return self.structs.get(i)?.fields.iter().find(|f| f.label == label),
+            _ => return None,
+        }
+    }
+
+    // the heap, const or static variable a file means by the name, looked up like findSymbol over all three
+    pub fn programVariable(&self, label: &str, file: usize) -> Option<(usize, VarDest)> {
+        let lists = [
+            (&self.heap_variables, VarDest::Heap),
+            (&self.const_variables, VarDest::ProgramConst),
+            (&self.static_variables, VarDest::ProgramStatic)
+        ];
+        for pass in 0..3 {
+            for (list, dest) in lists {
+                let found = list.iter().position(|v| v.label == label && match pass {
+                    0 => v.module == file,
+                    1 => v.public,
+                    _ => true
+                });
+                if let Some(i) = found {return Some((i, dest));}
+            }
+        }
+        return None;
+    }
+
+    // the .kasm label of a proc
+    pub fn procLabel(&self, i: usize) -> String {
+        return kasmLabel(&self.procs, i);
+    }
+
+    // the .kasm label of a static variable or raw label
+    pub fn staticLabel(&self, label: &str, module: usize) -> String {
+        match self.static_variables.iter().position(|v| v.label == label && v.module == module) {
+            Some(i) => return kasmLabel(&self.static_variables, i),
+            None => return label.to_string(),
+        }
+    }
+
+    pub fn programVariableAt(&self, i: usize, dest: VarDest) -> &Variable<'a> {
+        match dest {
+            VarDest::Heap => return &self.heap_variables[i],
+            VarDest::ProgramConst => return &self.const_variables[i],
+            _ => return &self.static_variables[i],
+        }
+    }
+
     pub fn getBlock(&self, directory: &[BlockParent]) -> &Block<'a> {
         return self.procs[directory[0].index].getBlock(&directory[1..directory.len()]);
     }
@@ -194,13 +630,15 @@ impl<'a> Program<'a>{
     }
 }
 
-pub fn toValueType(s: &str) -> Result<DataValueType, ParserError>{
+pub fn toValueType(s: &str, span: Span) -> Result<DataValueType, ParserError>{
     match s {
         "void" => return Ok(DataValueType::Void),
         "uint" => return Ok(DataValueType::Uint),
         "short" => return Ok(DataValueType::Short),
+        "int" => return Ok(DataValueType::Int),
+        "sshort" => return Ok(DataValueType::SShort),
         "char" => return Ok(DataValueType::Char),
         "string" => return Ok(DataValueType::String),
-        &_ => return Err(ParserError::UnidentifiedType(s.to_string()))
+        &_ => return Err(ParserError::UnidentifiedType(s.to_string(), span))
     }
 }
\ No newline at end of file
@@ -107,6 +107,32 @@ class K_CPU{
         PPU.window.end();
     }
 
+    //------------FLAGS------------
+    //laid out like the 6502: NF bit7, VF bit6, ZF bit1, CF bit0
+    void setNZ(uint8_t value){
+        P &= 0b01111101;
+        if(value == 0) P |= 0b00000010;
+        P |= value & 0b10000000;
+    }
+
+    //CMP/CPX/CPY, CF is set when reg >= value (unsigned), NF is bit 7 of the difference
+    void compare(uint8_t reg, uint8_t value){
+        P &= 0b11111110;
+        if(reg >= value) P |= 0b00000001;
+        setNZ(reg - value);
+    }
+
+    //ADC, SBC is A + ~value + CF
+    uint8_t addWithCarry(uint8_t value){
+        int sum = A + value + (P & 0b00000001);
+        uint8_t result = sum;
+        P &= 0b10111110;
+        if(sum > 0xFF) P |= 0b00000001;
+        if(((A ^ result) & (value ^ result) & 0b10000000) != 0) P |= 0b01000000; //signed overflow
+        setNZ(result);
+        return result;
+    }
+
     ReturnPackage executeProgramTick(std::vector<SDL_Event> *events){
         #define GET_CHAR (*(ROM+PC))
         #define GET_NEXT_CHARI (*(ROM+(++PC)))
@@ -569,6 +595,180 @@ class K_CPU{
                 }
                 break;
 
+                //------------INDEXED BY Y------------
+                //LDAY
+                case 0xB9:
+                {
+                    uint16_t address = GET_NEXT_CHARI;
+                    address = (address << 8) + GET_NEXT_CHARI + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to A:" << (int)address << std::endl;
+                    #endif
+                    A = *ptr;
+                }
+                break;
+                //LDASY
+                case 0x7D:
+                {
+                    uint8_t page = 0x01;
+                    uint8_t addr = S+GET_NEXT_CHARI+Y;
+                    uint8_t* ptr = RAM.getRAddress(page, addr);
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to A: byte at stack index:" << (int)addr << std::endl;
+                    #endif
+                    A = *ptr;
+                }
+                break;
+                //LDARY
+                case 0xBD:
+                {
+                    uint16_t address = GET_NEXT_CHARI;
+                    address = (address << 8) + GET_NEXT_CHARI + Y;
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to A: ROM byte at:" << (int)address << std::endl;
+                    #endif
+                    A = ROM[address];
+                }
+                break;
+                //LDXY
+                case 0xBE:
+                {
+                    uint16_t address = GET_NEXT_CHARI;
+                    address = (address << 8) + GET_NEXT_CHARI + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to X:" << (int)address << std::endl;
+                    #endif
+                    X = *ptr;
+                }
+                break;
+                //LDXSY
+                case 0xDD:
+                {
+                    uint8_t page = 0x01;
+                    uint8_t addr = S+GET_NEXT_CHARI+Y;
+                    uint8_t* ptr = RAM.getRAddress(page, addr);
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to X: byte at stack index:" << (int)addr << std::endl;
+                    #endif
+                    X = *ptr;
+                }
+                break;
+                //LDXRY
+                case 0xBF:
+                {
+                    uint16_t address = GET_NEXT_CHARI;
+                    address = (address << 8) + GET_NEXT_CHARI + Y;
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to X: ROM byte at:" << (int)address << std::endl;
+                    #endif
+                    X = ROM[address];
+                }
+                break;
+                //STAY
+                case 0x99:
+                {
+                    uint16_t address = GET_NEXT_CHARI;
+                    address = (address << 8) + GET_NEXT_CHARI + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Stored A to:" << (int)address << std::endl;
+                    #endif
+                    RAM.assignToAddress(ptr, A);
+                }
+                break;
+                //STASY
+                case 0x1D:
+                {
+                    uint8_t page = 0x01;
+                    uint8_t addr = S+GET_NEXT_CHARI+Y;
+                    uint8_t* ptr = RAM.getRAddress(page, addr);
+                    #ifdef __DEBUG__
+                    std::cout << "Stored A to stack position:" << (int)addr << std::endl;
+                    #endif
+                    RAM.assignToAddress(ptr, A);
+                }
+                break;
+                //STXY
+                case 0x9E:
+                {
+                    uint16_t address = GET_NEXT_CHARI;
+                    address = (address << 8) + GET_NEXT_CHARI + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Stored X to:" << (int)address << std::endl;
+                    #endif
+                    RAM.assignToAddress(ptr, X);
+                }
+                break;
+                //STXSY
+                case 0x3D:
+                {
+                    uint8_t page = 0x01;
+                    uint8_t addr = S+GET_NEXT_CHARI+Y;
+                    uint8_t* ptr = RAM.getRAddress(page, addr);
+                    #ifdef __DEBUG__
+                    std::cout << "Stored X to stack position:" << (int)addr << std::endl;
+                    #endif
+                    RAM.assignToAddress(ptr, X);
+                }
+                break;
+
+                //------------INDIRECT, POINTER PLUS Y------------
+                //LDAIY
+                case 0xB1:
+                {
+                    uint8_t pointer = GET_NEXT_CHARI;
+                    uint16_t address = *RAM.getRAddress(ABH, (uint8_t)(pointer+1));
+                    address = (address << 8) + *RAM.getRAddress(ABH, pointer) + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to A through pointer:" << (int)address << std::endl;
+                    #endif
+                    A = *ptr;
+                }
+                break;
+                //LDXIY
+                case 0xB3:
+                {
+                    uint8_t pointer = GET_NEXT_CHARI;
+                    uint16_t address = *RAM.getRAddress(ABH, (uint8_t)(pointer+1));
+                    address = (address << 8) + *RAM.getRAddress(ABH, pointer) + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Loaded to X through pointer:" << (int)address << std::endl;
+                    #endif
+                    X = *ptr;
+                }
+                break;
+                //STAIY
+                case 0x91:
+                {
+                    uint8_t pointer = GET_NEXT_CHARI;
+                    uint16_t address = *RAM.getRAddress(ABH, (uint8_t)(pointer+1));
+                    address = (address << 8) + *RAM.getRAddress(ABH, pointer) + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Stored A through pointer to:" << (int)address << std::endl;
+                    #endif
+                    RAM.assignToAddress(ptr, A);
+                }
+                break;
+                //STXIY
+                case 0x93:
+                {
+                    uint8_t pointer = GET_NEXT_CHARI;
+                    uint16_t address = *RAM.getRAddress(ABH, (uint8_t)(pointer+1));
+                    address = (address << 8) + *RAM.getRAddress(ABH, pointer) + Y;
+                    uint8_t* ptr = RAM.getRAddress(address);
+                    #ifdef __DEBUG__
+                    std::cout << "Stored X through pointer to:" << (int)address << std::endl;
+                    #endif
+                    RAM.assignToAddress(ptr, X);
+                }
+                break;
+
                 //------------TRANSFERS------------
                 case 0xAA: X = A; break; //TAX
                 case 0x8A: A = X; break; //TXA
@@ -580,39 +780,44 @@ class K_CPU{
                 //------------ARITHMETIC------------
                 //ADCC (ADD CARRY AND FLAGS)
                 case 0x69:{
-                    int x = A + GET_NEXT_CHARI;
+                    A = addWithCarry(GET_NEXT_CHARI);
                     #ifdef __DEBUG__
-                    std::cout << "ADD A OUT " << x << std::endl;
+                    std::cout << "ADD A OUT " << (int)A << std::endl;
                     #endif
-                    if(x < 0){P &= 0b10111110; P += 0b00000001; x=0;}
-                    else if(x==0){P &= 0b10111110; P += 0b01000000;}
-                    else{P &= 0b10111110;}
-                    A = x;
+                } break;
+                //ADC $$
+                case 0x65:{
+                    uint8_t* ptr = RAM.getRAddress(ABH, GET_NEXT_CHARI);
+                    A = addWithCarry(*ptr);
                 } break;
                 //ADC $$$$
                 case 0x6D: {
                     uint8_t page = GET_NEXT_CHARI;
                     uint8_t addr = GET_NEXT_CHARI;
                     uint8_t* ptr = RAM.getRAddress(page, addr);
-                    int x = A + *ptr;
+                    A = addWithCarry(*ptr);
                     #ifdef __DEBUG__
-                    std::cout << "Added A and " << page << ":" << addr << " = " << x << std::endl;
+                    std::cout << "Added A and " << page << ":" << addr << " = " << (int)A << std::endl;
                     #endif
-                    if(x < 0){P &= 0b10111110; P += 0b00000001; x=0;}
-                    else if(x==0){P &= 0b10111110; P += 0b01000000;}
-                    else{P &= 0b10111110;}
-                    A = x;
-                }
+                } break;
                 //SBCC (ADD CARRY AND FLAGS)
                 case 0xE9:{ 
-                    int x = A - GET_NEXT_CHARI;
+                    A = addWithCarry(~GET_NEXT_CHARI);
                     #ifdef __DEBUG__
-                    std::cout << "MINUS A OUT " << x << std::endl;
+                    std::cout << "MINUS A OUT " << (int)A << std::endl;
                     #endif
-                    if(x < 0){P &= 0b10111110; P += 0b00000001; x=0;}
-                    else if(x==0){P &= 0b10111110; P += 0b01000000;}
-                    else{P &= 0b10111110;}
-                    A = x;
+                } break;
+                //SBC $$
+                case 0xE5:{
+                    uint8_t* ptr = RAM.getRAddress(ABH, GET_NEXT_CHARI);
+                    A = addWithCarry(~(*ptr));
+                } break;
+                //SBC $$$$
+                case 0xED:{
+                    uint8_t page = GET_NEXT_CHARI;
+                    uint8_t addr = GET_NEXT_CHARI;
+                    uint8_t* ptr = RAM.getRAddress(page, addr);
+                    A = addWithCarry(~(*ptr));
                 } break;
 
                 //------------DEC/INC------------
@@ -631,6 +836,38 @@ class K_CPU{
                 case 0xE8:{X++;} break;
                 case 0xC8:{Y++;} break;
 
+                //------------LOGIC GATES------------
+                //ANDC
+                case 0x29:{A &= GET_NEXT_CHARI; setNZ(A);} break;
+                //AND $$
+                case 0x25:{A &= *RAM.getRAddress(ABH, GET_NEXT_CHARI); setNZ(A);} break;
+                //AND $$$$
+                case 0x2D:{
+                    uint8_t page = GET_NEXT_CHARI;
+                    uint8_t addr = GET_NEXT_CHARI;
+                    A &= *RAM.getRAddress(page, addr);
+                    setNZ(A);} break;
+                //XORC
+                case 0x49:{A ^= GET_NEXT_CHARI; setNZ(A);} break;
+                //XOR $$
+                case 0x45:{A ^= *RAM.getRAddress(ABH, GET_NEXT_CHARI); setNZ(A);} break;
+                //XOR $$$$
+                case 0x4D:{
+                    uint8_t page = GET_NEXT_CHARI;
+                    uint8_t addr = GET_NEXT_CHARI;
+                    A ^= *RAM.getRAddress(page, addr);
+                    setNZ(A);} break;
+                //ORAC
+                case 0x09:{A |= GET_NEXT_CHARI; setNZ(A);} break;
+                //ORA $$
+                case 0x05:{A |= *RAM.getRAddress(ABH, GET_NEXT_CHARI); setNZ(A);} break;
+                //ORA $$$$
+                case 0x0D:{
+                    uint8_t page = GET_NEXT_CHARI;
+                    uint8_t addr = GET_NEXT_CHARI;
+                    A |= *RAM.getRAddress(page, addr);
+                    setNZ(A);} break;
+
                 //------------JUMP------------
                 //JMPA
                 case 0x4C:{uint16_t addr = GET_NEXT_CHARI; addr = addr << 8; addr+=GET_NEXT_CHARI; PC = addr-1;} break;
@@ -652,39 +889,57 @@ class K_CPU{
 
                 //------------COMPARE------------
                 //CPXC
-                case 0xE0:{int x = X-GET_NEXT_CHARI; if(x < 0){P &= 0b10111110; P += 0b00000001;}else if(x==0){P &= 0b10111110; P += 0b01000000;}else{P &= 0b10111110;}} break;
+                case 0xE0:{compare(X, GET_NEXT_CHARI);} break;
+                //CPX $$
+                case 0xE4:{compare(X, *RAM.getRAddress(ABH, GET_NEXT_CHARI));} break;
                 //CPX 
                 case 0xEC:{
                     uint8_t page = GET_NEXT_CHARI;
                     uint8_t addr = GET_NEXT_CHARI;
                     uint8_t* ptr = RAM.getRAddress(page, addr);
-                    int x = X-*ptr; if(x < 0){P &= 0b10111110; P += 0b00000001;}else if(x==0){P &= 0b10111110; P += 0b01000000;}else{P &= 0b10111110;}} break;
+                    compare(X, *ptr);} break;
                 //CPYC
-                case 0xC0:{int x = Y-GET_NEXT_CHARI; if(x < 0){P &= 0b10111110; P += 0b00000001;}else if(x==0){P &= 0b10111110; P += 0b01000000;}else{P &= 0b10111110;}} break;
+                case 0xC0:{compare(Y, GET_NEXT_CHARI);} break;
+                //CPY $$
+                case 0xC4:{compare(Y, *RAM.getRAddress(ABH, GET_NEXT_CHARI));} break;
                 //CPY 
                 case 0xCC:{
                     uint8_t page = GET_NEXT_CHARI;
                     uint8_t addr = GET_NEXT_CHARI;
                     uint8_t* ptr = RAM.getRAddress(page, addr);
-                    int x = Y-*ptr; if(x < 0){P &= 0b10111110; P += 0b00000001;}else if(x==0){P &= 0b10111110; P += 0b01000000;}else{P &= 0b10111110;}} break;
+                    compare(Y, *ptr);} break;
                 //CMPC
-                case 0xC9:{int x = A-GET_NEXT_CHARI; if(x < 0){P &= 0b10111110; P += 0b00000001;}else if(x==0){P &= 0b10111110; P += 0b01000000;}else{P &= 0b10111110;}} break;
+                case 0xC9:{compare(A, GET_NEXT_CHARI);} break;
+                //CMP $$
+                case 0xC5:{compare(A, *RAM.getRAddress(ABH, GET_NEXT_CHARI));} break;
                 //CMP
                 case 0xCD:{
                     uint8_t page = GET_NEXT_CHARI;
                     uint8_t addr = GET_NEXT_CHARI;
                     uint8_t* ptr = RAM.getRAddress(page, addr);
-                    int x = A-*ptr; if(x < 0){P &= 0b10111110; P += 0b00000001;}else if(x==0){P &= 0b10111110; P += 0b01000000;}else{P &= 0b10111110;}} break;
+                    compare(A, *ptr);} break;
 
                 //------------BRANCH------------
                 //BEQ
-                case 0xF0:{int bytes = GET_NEXT_CHARI; if((P&0b01000000)!=0)PC+=bytes;} break; //equal
+                case 0xF0:{int bytes = GET_NEXT_CHARI; if((P&0b00000010)!=0)PC+=bytes;} break; //equal
                 //BNE 
-                case 0xD0:{int bytes = GET_NEXT_CHARI; if((P&0b01000000)==0)PC+=bytes;} break; //not equal
+                case 0xD0:{int bytes = GET_NEXT_CHARI; if((P&0b00000010)==0)PC+=bytes;} break; //not equal
                 //BMI
-                case 0x30:{int bytes = GET_NEXT_CHARI; if((P&0b00000001)==1)PC+=bytes;} break; //less than
+                case 0x30:{int bytes = GET_NEXT_CHARI; if((P&0b10000000)!=0)PC+=bytes;} break; //negative
                 //BPL
-                case 0x10:{int bytes = GET_NEXT_CHARI; if((P&0b00000001)!=1)PC+=bytes;} break; //greater
+                case 0x10:{int bytes = GET_NEXT_CHARI; if((P&0b10000000)==0)PC+=bytes;} break; //positive
+                //BVC
+                case 0x50:{int bytes = GET_NEXT_CHARI; if((P&0b01000000)==0)PC+=bytes;} break; //no signed overflow
+                //BVS
+                case 0x70:{int bytes = GET_NEXT_CHARI; if((P&0b01000000)!=0)PC+=bytes;} break; //signed overflow
+                //BCC
+                case 0x90:{int bytes = GET_NEXT_CHARI; if((P&0b00000001)==0)PC+=bytes;} break; //unsigned less than
+                //BCS
+                case 0xB0:{int bytes = GET_NEXT_CHARI; if((P&0b00000001)!=0)PC+=bytes;} break; //unsigned greater or equal
+
+                //------------FLAGS------------
+                case 0x18:{P &= 0b11111110;} break; //CLC
+                case 0x38:{P |= 0b00000001;} break; //SEC
 
                 //------------BREAK------------
                 case 0x04:{std::cout << "Program returned with: " << (int)A << std::endl; return {true, A};}break;
@@ -21,6 +21,14 @@ static std::unordered_map<std::string,uint16_t> OPCodetable({
     {"LDX",  0xAEA2},
     {"LDXC", 0xFFA6},
     {"LDXS", 0xFFDC},
+    {"LDAY", 0xB9FF},
+    {"LDASY",0xFF7D},
+    {"LDARY",0xBDFF},
+    {"LDXY", 0xBEFF},
+    {"LDXSY",0xFFDD},
+    {"LDXRY",0xBFFF},
+    {"LDAIY",0xFFB1},
+    {"LDXIY",0xFFB3},
     
     {"TAX",  0xFFAA},
     {"TXA",  0xFF8A},
@@ -38,9 +46,15 @@ static std::unordered_map<std::string,uint16_t> OPCodetable({
     {"STAS", 0xFF1C},
     {"STX",  0x8E82},
     {"STXS", 0xFF3C},
+    {"STAY", 0x99FF},
+    {"STASY",0xFF1D},
+    {"STXY", 0x9EFF},
+    {"STXSY",0xFF3D},
+    {"STAIY",0xFF91},
+    {"STXIY",0xFF93},
 
     {"ADCC", 0xFF69},
-    {"ADC",  0xFF6D},
+    {"ADC",  0x6D65},
     {"SBCC", 0x00E9},
     {"SBC",  0xEDE5},
 
@@ -80,5 +94,8 @@ static std::unordered_map<std::string,uint16_t> OPCodetable({
     {"BNE",  0xFFD0},
     {"BEQ",  0xFFF0},
 
+    {"CLC",  0xFF18},
+    {"SEC",  0xFF38},
+
     {"BRK", 0xFF04},
 });
\ No newline at end of file